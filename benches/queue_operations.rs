@@ -0,0 +1,193 @@
+// Throughput baselines for the core queue operations against a temp database.
+// Run with `cargo bench`; criterion keeps previous results under target/criterion and
+// reports the change against them on the next run.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use criterion::{BatchSize, Criterion, Throughput, criterion_group, criterion_main};
+use qlite::message::MessageAttributeValue;
+use qlite::queue_service::QueueService;
+use tempfile::TempDir;
+use tokio::runtime::Runtime;
+
+const BODY: &str = "benchmark message body of a typical small size";
+const LARGE_QUEUE_MESSAGES: usize = 10_000;
+
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to build runtime")
+}
+
+async fn create_service(queue_name: &str) -> (TempDir, Arc<QueueService>) {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("bench.db");
+    let service = QueueService::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create queue service");
+    service
+        .create_queue(queue_name)
+        .await
+        .expect("Failed to create queue");
+    (temp_dir, Arc::new(service))
+}
+
+// (queue_name, message_id, body, attributes, deduplication_id, delay_seconds, message_group_id)
+type BatchEntry = (
+    String,
+    String,
+    String,
+    Option<HashMap<String, MessageAttributeValue>>,
+    Option<String>,
+    u32,
+    Option<String>,
+);
+
+fn batch_entries(service: &QueueService, queue_name: &str) -> Vec<BatchEntry> {
+    (0..10)
+        .map(|_| {
+            (
+                queue_name.to_string(),
+                service.id_generator().generate(queue_name),
+                BODY.to_string(),
+                None,
+                None,
+                0,
+                None,
+            )
+        })
+        .collect()
+}
+
+fn bench_send(c: &mut Criterion) {
+    let rt = runtime();
+    let (_temp_dir, service) = rt.block_on(create_service("send-bench"));
+
+    let mut group = c.benchmark_group("send");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("send_message", |b| {
+        b.to_async(&rt).iter(|| async {
+            service
+                .send_message("send-bench", BODY, None, None)
+                .await
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn bench_receive(c: &mut Criterion) {
+    let rt = runtime();
+    let (_temp_dir, service) = rt.block_on(create_service("receive-bench"));
+
+    let mut group = c.benchmark_group("receive");
+    group.throughput(Throughput::Elements(1));
+    // Each measured receive needs a message to claim, so the sends happen off the clock
+    group.bench_function("receive_and_delete", |b| {
+        b.to_async(&rt).iter_custom(|iters| {
+            let service = Arc::clone(&service);
+            async move {
+                for _ in 0..iters {
+                    service
+                        .send_message("receive-bench", BODY, None, None)
+                        .await
+                        .unwrap();
+                }
+
+                let started = Instant::now();
+                for _ in 0..iters {
+                    let message = service
+                        .receive_message("receive-bench")
+                        .await
+                        .unwrap()
+                        .expect("a message was sent for every iteration");
+                    service
+                        .delete_message(&message.receipt_handle)
+                        .await
+                        .unwrap();
+                }
+                started.elapsed()
+            }
+        })
+    });
+    group.finish();
+}
+
+fn bench_batch(c: &mut Criterion) {
+    let rt = runtime();
+    let (_temp_dir, service) = rt.block_on(create_service("batch-bench"));
+
+    let mut group = c.benchmark_group("batch");
+    group.throughput(Throughput::Elements(10));
+    group.bench_function("send_messages_batch_10", |b| {
+        b.to_async(&rt).iter_batched(
+            || batch_entries(&service, "batch-bench"),
+            |entries| async { service.send_messages_batch(entries).await.unwrap() },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("receive_and_delete_batch_10", |b| {
+        b.to_async(&rt).iter_custom(|iters| {
+            let service = Arc::clone(&service);
+            async move {
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..iters {
+                    service
+                        .send_messages_batch(batch_entries(&service, "batch-bench"))
+                        .await
+                        .unwrap();
+
+                    let started = Instant::now();
+                    let messages = service
+                        .receive_messages_batch("batch-bench", 10)
+                        .await
+                        .unwrap();
+                    let handles = messages.into_iter().map(|m| m.receipt_handle).collect();
+                    service
+                        .delete_messages_batch("batch-bench", handles)
+                        .await
+                        .unwrap();
+                    elapsed += started.elapsed();
+                }
+                elapsed
+            }
+        })
+    });
+    group.finish();
+}
+
+fn bench_queue_attributes(c: &mut Criterion) {
+    let rt = runtime();
+    let (_temp_dir, service) = rt.block_on(async {
+        let (temp_dir, service) = create_service("large-queue").await;
+        for _ in 0..LARGE_QUEUE_MESSAGES / 10 {
+            service
+                .send_messages_batch(batch_entries(&service, "large-queue"))
+                .await
+                .unwrap();
+        }
+        (temp_dir, service)
+    });
+
+    c.bench_function("get_queue_attributes_10k_messages", |b| {
+        b.to_async(&rt).iter(|| async {
+            service
+                .get_queue_attributes("large-queue")
+                .await
+                .unwrap()
+                .unwrap()
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_send,
+    bench_receive,
+    bench_batch,
+    bench_queue_attributes
+);
+criterion_main!(benches);
@@ -0,0 +1,111 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::{
+    api::{ApiError, api_error},
+    http_server::AppState,
+};
+
+// Operator endpoints under /admin. Unlike /api they can touch the filesystem, so they
+// require `Authorization: Bearer <admin_token>` and are disabled when no token is set.
+fn authorize(state: &AppState, headers: &HeaderMap) -> Result<(), ApiError> {
+    let Some(expected) = state.queue_service.admin_token() else {
+        return Err(api_error(
+            StatusCode::FORBIDDEN,
+            "Admin endpoints are disabled; set server.admin_token or QLITE_ADMIN_TOKEN".to_string(),
+        ));
+    };
+
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if provided != Some(expected) {
+        return Err(api_error(
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid admin token".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BackupRequest {
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackupResponse {
+    pub path: String,
+    pub size_bytes: u64,
+    pub duration_ms: u64,
+}
+
+pub async fn backup(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<BackupRequest>,
+) -> Result<Json<BackupResponse>, ApiError> {
+    authorize(&state, &headers)?;
+
+    // The backup API overwrites its destination, so never point it at an existing file
+    if Path::new(&request.path).exists() {
+        return Err(api_error(
+            StatusCode::CONFLICT,
+            format!("Backup destination '{}' already exists", request.path),
+        ));
+    }
+
+    let started = Instant::now();
+    match state.queue_service.backup_to(&request.path).await {
+        Ok(size_bytes) => Ok(Json(BackupResponse {
+            path: request.path,
+            size_bytes,
+            duration_ms: started.elapsed().as_millis() as u64,
+        })),
+        Err(e) => Err(api_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to back up database: {}", e),
+        )),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CheckpointResponse {
+    // True if readers or writers stopped the checkpoint from finishing
+    pub busy: bool,
+    pub log_frames: i64,
+    pub checkpointed_frames: i64,
+    pub wal_size_bytes: u64,
+    pub duration_ms: u64,
+}
+
+pub async fn checkpoint(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<CheckpointResponse>, ApiError> {
+    authorize(&state, &headers)?;
+
+    let started = Instant::now();
+    match state.queue_service.wal_checkpoint().await {
+        Ok(checkpoint) => Ok(Json(CheckpointResponse {
+            busy: checkpoint.busy,
+            log_frames: checkpoint.log_frames,
+            checkpointed_frames: checkpoint.checkpointed_frames,
+            wal_size_bytes: state.queue_service.wal_size_bytes(),
+            duration_ms: started.elapsed().as_millis() as u64,
+        })),
+        Err(e) => Err(api_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to checkpoint WAL: {}", e),
+        )),
+    }
+}
@@ -0,0 +1,58 @@
+use serde::Serialize;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::config::AlertsConfig;
+
+// Payload POSTed to the DLQ webhook when a message is dead-lettered
+#[derive(Debug, Clone, Serialize)]
+pub struct DlqAlert {
+    pub queue: String,
+    pub dlq: String,
+    pub message_id: String,
+    pub reason: String,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AlertNotifier {
+    client: reqwest::Client,
+    dlq_webhook_url: Option<String>,
+}
+
+impl AlertNotifier {
+    pub fn new(config: &AlertsConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            client,
+            dlq_webhook_url: config.dlq_webhook_url.clone(),
+        }
+    }
+
+    // Fire-and-forget so a slow or unreachable webhook never blocks DLQ movement
+    pub fn dlq_message(&self, alert: DlqAlert) {
+        let Some(url) = self.dlq_webhook_url.clone() else {
+            return;
+        };
+        let client = self.client.clone();
+
+        tokio::spawn(async move {
+            match client.post(&url).json(&alert).send().await {
+                Ok(response) if !response.status().is_success() => {
+                    warn!(
+                        "DLQ webhook {} returned {} for message {}",
+                        url,
+                        response.status(),
+                        alert.message_id
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to send DLQ webhook to {}: {}", url, e),
+            }
+        });
+    }
+}
@@ -0,0 +1,655 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, stream};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{
+    config::QueueConfig,
+    database::{ExportedMessage, PurgeFilter, is_valid_attribute_name},
+    http_server::AppState,
+    queue_service::NackOutcome,
+};
+
+#[derive(Debug, Serialize)]
+pub struct ApiResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+pub(crate) type ApiError = (StatusCode, Json<ApiResponse>);
+
+pub(crate) fn api_error(status: StatusCode, message: String) -> ApiError {
+    (
+        status,
+        Json(ApiResponse {
+            success: false,
+            message,
+        }),
+    )
+}
+
+// JSON management endpoints, available regardless of whether the UI is enabled
+#[derive(Debug, Serialize)]
+pub struct QueueSummary {
+    pub name: String,
+    pub created_at: String,
+    pub tags: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueueListResponse {
+    pub queues: Vec<QueueSummary>,
+}
+
+// Collect `tag.<key>=<value>` query parameters into a tag filter, sorted by key
+pub fn tag_filter_from_query(
+    params: &HashMap<String, String>,
+) -> Result<Vec<(String, String)>, String> {
+    let mut filter = Vec::new();
+    for (name, value) in params {
+        if let Some(key) = name.strip_prefix("tag.") {
+            if key.is_empty() {
+                return Err("Tag filter key must not be empty".to_string());
+            }
+            filter.push((key.to_string(), value.clone()));
+        }
+    }
+    filter.sort();
+    Ok(filter)
+}
+
+pub async fn list_queues(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<QueueListResponse>, ApiError> {
+    let filter =
+        tag_filter_from_query(&params).map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?;
+
+    match state.queue_service.list_queues_with_tags(&filter).await {
+        Ok(queues) => Ok(Json(QueueListResponse {
+            queues: queues
+                .into_iter()
+                .map(|(name, created_at, tags)| QueueSummary {
+                    name,
+                    created_at,
+                    tags: tags.into_iter().collect(),
+                })
+                .collect(),
+        })),
+        Err(e) => Err(api_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to list queues: {}", e),
+        )),
+    }
+}
+
+pub async fn get_queue_config(
+    State(state): State<Arc<AppState>>,
+    Path(queue_name): Path<String>,
+) -> Result<Json<QueueConfig>, ApiError> {
+    match state.queue_service.get_queue_config(&queue_name).await {
+        Ok(Some(config)) => Ok(Json(config)),
+        Ok(None) => Err(api_error(
+            StatusCode::NOT_FOUND,
+            format!("Queue '{}' does not exist", queue_name),
+        )),
+        Err(e) => Err(api_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to get queue config: {}", e),
+        )),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CloneQueueRequest {
+    pub new_name: String,
+}
+
+pub async fn clone_queue(
+    State(state): State<Arc<AppState>>,
+    Path(queue_name): Path<String>,
+    Json(request): Json<CloneQueueRequest>,
+) -> Result<(StatusCode, Json<QueueConfig>), ApiError> {
+    let source_config = match state.queue_service.get_queue_config(&queue_name).await {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            return Err(api_error(
+                StatusCode::NOT_FOUND,
+                format!("Queue '{}' does not exist", queue_name),
+            ));
+        }
+        Err(e) => {
+            return Err(api_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to get queue config: {}", e),
+            ));
+        }
+    };
+
+    // The clone keeps the source queue type, so the new name must carry the same suffix
+    let target_config = QueueConfig {
+        name: request.new_name.clone(),
+        ..source_config
+    };
+    if let Err(e) = target_config.validate_name() {
+        return Err(api_error(StatusCode::BAD_REQUEST, e.to_string()));
+    }
+
+    match state
+        .queue_service
+        .get_queue_attributes(&request.new_name)
+        .await
+    {
+        Ok(Some(_)) => {
+            return Err(api_error(
+                StatusCode::CONFLICT,
+                format!("Queue '{}' already exists", request.new_name),
+            ));
+        }
+        Ok(None) => {}
+        Err(e) => {
+            return Err(api_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to check queue: {}", e),
+            ));
+        }
+    }
+
+    match state
+        .queue_service
+        .clone_queue(&queue_name, &request.new_name)
+        .await
+    {
+        Ok(Some(config)) => Ok((StatusCode::CREATED, Json(config))),
+        Ok(None) => Err(api_error(
+            StatusCode::NOT_FOUND,
+            format!("Queue '{}' does not exist", queue_name),
+        )),
+        Err(e) => Err(api_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to clone queue: {}", e),
+        )),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct NackResponse {
+    pub message_id: String,
+    pub nack_count: u32,
+    pub dead_lettered: bool,
+}
+
+pub async fn nack_message(
+    State(state): State<Arc<AppState>>,
+    Path(message_id): Path<String>,
+) -> Result<Json<NackResponse>, ApiError> {
+    match state.queue_service.nack_message(&message_id).await {
+        Ok(Some(outcome)) => {
+            let (nack_count, dead_lettered) = match outcome {
+                NackOutcome::Requeued { nack_count } => (nack_count, false),
+                NackOutcome::DeadLettered { nack_count } => (nack_count, true),
+            };
+            Ok(Json(NackResponse {
+                message_id,
+                nack_count,
+                dead_lettered,
+            }))
+        }
+        Ok(None) => Err(api_error(
+            StatusCode::NOT_FOUND,
+            format!("Message '{}' is not in flight", message_id),
+        )),
+        Err(e) => Err(api_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to nack message: {}", e),
+        )),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeliveryEntry {
+    pub delivered_at: String,
+    pub receipt_handle: String,
+    pub consumer_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeliveriesResponse {
+    pub message_id: String,
+    pub deliveries: Vec<DeliveryEntry>,
+}
+
+pub async fn message_deliveries(
+    State(state): State<Arc<AppState>>,
+    Path(message_id): Path<String>,
+) -> Result<Json<DeliveriesResponse>, ApiError> {
+    if !state.queue_service.delivery_log_enabled() {
+        return Err(api_error(
+            StatusCode::NOT_FOUND,
+            "Delivery log is disabled; set logging.delivery_log or QLITE_DELIVERY_LOG".to_string(),
+        ));
+    }
+
+    match state
+        .queue_service
+        .get_message_deliveries(&message_id)
+        .await
+    {
+        Ok(records) => Ok(Json(DeliveriesResponse {
+            message_id,
+            deliveries: records
+                .into_iter()
+                .map(|record| DeliveryEntry {
+                    delivered_at: record.delivered_at,
+                    receipt_handle: record.receipt_handle,
+                    consumer_id: record.consumer_id,
+                })
+                .collect(),
+        })),
+        Err(e) => Err(api_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to load deliveries: {}", e),
+        )),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReceiveCountResponse {
+    pub message_id: String,
+    pub receive_count: i64,
+}
+
+pub async fn message_receive_count(
+    State(state): State<Arc<AppState>>,
+    Path(message_id): Path<String>,
+) -> Result<Json<ReceiveCountResponse>, ApiError> {
+    match state
+        .queue_service
+        .get_message_receive_count(&message_id)
+        .await
+    {
+        Ok(Some(receive_count)) => Ok(Json(ReceiveCountResponse {
+            message_id,
+            receive_count,
+        })),
+        Ok(None) => Err(api_error(
+            StatusCode::NOT_FOUND,
+            format!("Message '{}' not found", message_id),
+        )),
+        Err(e) => Err(api_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to load receive count: {}", e),
+        )),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PurgeResponse {
+    pub queue_name: String,
+    pub purged: u32,
+}
+
+pub async fn purge_filtered(
+    State(state): State<Arc<AppState>>,
+    Path(queue_name): Path<String>,
+    Json(filter): Json<PurgeFilter>,
+) -> Result<Json<PurgeResponse>, ApiError> {
+    // Refuse filters that would match everything to avoid accidental full purges
+    if let Err(e) = filter.validate() {
+        return Err(api_error(StatusCode::BAD_REQUEST, e));
+    }
+
+    match state.queue_service.get_queue_attributes(&queue_name).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Err(api_error(
+                StatusCode::NOT_FOUND,
+                format!("Queue '{}' does not exist", queue_name),
+            ));
+        }
+        Err(e) => {
+            return Err(api_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to check queue: {}", e),
+            ));
+        }
+    }
+
+    match state
+        .queue_service
+        .purge_filtered(&queue_name, &filter)
+        .await
+    {
+        Ok(purged) => Ok(Json(PurgeResponse { queue_name, purged })),
+        Err(e) => Err(api_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to purge messages: {}", e),
+        )),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AttributeIndexRequest {
+    pub attribute_name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttributeIndexResponse {
+    pub queue_name: String,
+    pub attribute_name: String,
+    pub index_name: String,
+}
+
+pub async fn create_attribute_index(
+    State(state): State<Arc<AppState>>,
+    Path(queue_name): Path<String>,
+    Json(request): Json<AttributeIndexRequest>,
+) -> Result<(StatusCode, Json<AttributeIndexResponse>), ApiError> {
+    if !is_valid_attribute_name(&request.attribute_name) {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            format!("Invalid attribute name '{}'", request.attribute_name),
+        ));
+    }
+
+    match state.queue_service.get_queue_attributes(&queue_name).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Err(api_error(
+                StatusCode::NOT_FOUND,
+                format!("Queue '{}' does not exist", queue_name),
+            ));
+        }
+        Err(e) => {
+            return Err(api_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to check queue: {}", e),
+            ));
+        }
+    }
+
+    match state
+        .queue_service
+        .create_attribute_index(&queue_name, &request.attribute_name)
+        .await
+    {
+        Ok(index_name) => Ok((
+            StatusCode::CREATED,
+            Json(AttributeIndexResponse {
+                queue_name,
+                attribute_name: request.attribute_name,
+                index_name,
+            }),
+        )),
+        Err(e) => Err(api_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to create attribute index: {}", e),
+        )),
+    }
+}
+
+const DEFAULT_LOCK_LEASE_SECONDS: u64 = 30;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct LockRequest {
+    pub lease_seconds: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LockResponse {
+    pub queue_name: String,
+    pub token: String,
+    pub lease_seconds: u64,
+}
+
+// Exclusive receive lease: other consumers get OverLimit until it's released or expires
+pub async fn acquire_lock(
+    State(state): State<Arc<AppState>>,
+    Path(queue_name): Path<String>,
+    request: Option<Json<LockRequest>>,
+) -> Result<Json<LockResponse>, ApiError> {
+    let lease_seconds = request
+        .and_then(|Json(r)| r.lease_seconds)
+        .unwrap_or(DEFAULT_LOCK_LEASE_SECONDS);
+    if lease_seconds == 0 {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "lease_seconds must be > 0".to_string(),
+        ));
+    }
+
+    match state.queue_service.get_queue_attributes(&queue_name).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Err(api_error(
+                StatusCode::NOT_FOUND,
+                format!("Queue '{}' does not exist", queue_name),
+            ));
+        }
+        Err(e) => {
+            return Err(api_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to check queue: {}", e),
+            ));
+        }
+    }
+
+    match state
+        .queue_service
+        .acquire_receive_lock(&queue_name, Duration::from_secs(lease_seconds))
+        .await
+    {
+        Some(token) => Ok(Json(LockResponse {
+            queue_name,
+            token,
+            lease_seconds,
+        })),
+        None => Err(api_error(
+            StatusCode::CONFLICT,
+            format!("Queue '{}' is already locked", queue_name),
+        )),
+    }
+}
+
+pub async fn release_lock(
+    State(state): State<Arc<AppState>>,
+    Path((queue_name, token)): Path<(String, String)>,
+) -> Result<Json<ApiResponse>, ApiError> {
+    if state
+        .queue_service
+        .release_receive_lock(&queue_name, &token)
+        .await
+    {
+        Ok(Json(ApiResponse {
+            success: true,
+            message: format!("Lock on queue '{}' released", queue_name),
+        }))
+    } else {
+        Err(api_error(
+            StatusCode::NOT_FOUND,
+            format!("No active lock on queue '{}' for this token", queue_name),
+        ))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplayRequest {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayedMessage {
+    pub original_id: String,
+    pub message_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayResponse {
+    pub queue_name: String,
+    pub replayed: usize,
+    pub messages: Vec<ReplayedMessage>,
+}
+
+fn parse_timestamp(name: &str, value: &str) -> Result<DateTime<Utc>, ApiError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| {
+            api_error(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid '{}' timestamp '{}': {}", name, value, e),
+            )
+        })
+}
+
+// Non-destructive: processed messages are copied into the queue with new ids
+pub async fn replay_messages(
+    State(state): State<Arc<AppState>>,
+    Path(queue_name): Path<String>,
+    Json(request): Json<ReplayRequest>,
+) -> Result<Json<ReplayResponse>, ApiError> {
+    let from = parse_timestamp("from", &request.from)?;
+    let to = parse_timestamp("to", &request.to)?;
+    if from > to {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "'from' must not be after 'to'".to_string(),
+        ));
+    }
+
+    match state.queue_service.get_queue_attributes(&queue_name).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Err(api_error(
+                StatusCode::NOT_FOUND,
+                format!("Queue '{}' does not exist", queue_name),
+            ));
+        }
+        Err(e) => {
+            return Err(api_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to check queue: {}", e),
+            ));
+        }
+    }
+
+    match state
+        .queue_service
+        .replay_messages(&queue_name, from, to)
+        .await
+    {
+        Ok(replayed) => Ok(Json(ReplayResponse {
+            queue_name,
+            replayed: replayed.len(),
+            messages: replayed
+                .into_iter()
+                .map(|(original_id, message_id)| ReplayedMessage {
+                    original_id,
+                    message_id,
+                })
+                .collect(),
+        })),
+        Err(e) => Err(api_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to replay messages: {}", e),
+        )),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    // One JSON object per line (default)
+    #[default]
+    Ndjson,
+    Csv,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportParams {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+const CSV_HEADER: &str = "id,created_at,status,receive_count,visibility_timeout,processed_at,deleted_at,deduplication_id,attributes,body\n";
+
+// Quote a CSV field when it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_row(message: &ExportedMessage) -> String {
+    let optional = |value: &Option<String>| csv_field(value.as_deref().unwrap_or(""));
+    format!(
+        "{},{},{},{},{},{},{},{},{},{}\n",
+        csv_field(&message.id),
+        csv_field(&message.created_at),
+        csv_field(&message.status),
+        message.receive_count,
+        optional(&message.visibility_timeout),
+        optional(&message.processed_at),
+        optional(&message.deleted_at),
+        optional(&message.deduplication_id),
+        optional(&message.attributes),
+        csv_field(&message.body),
+    )
+}
+
+// Every message in the queue, in any status, streamed as it's read so exports of large
+// queues don't have to fit in memory
+pub async fn export_queue(
+    State(state): State<Arc<AppState>>,
+    Path(queue_name): Path<String>,
+    Query(params): Query<ExportParams>,
+) -> Result<Response, ApiError> {
+    match state.queue_service.get_queue_attributes(&queue_name).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Err(api_error(
+                StatusCode::NOT_FOUND,
+                format!("Queue '{}' does not exist", queue_name),
+            ));
+        }
+        Err(e) => {
+            return Err(api_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to check queue: {}", e),
+            ));
+        }
+    }
+
+    let format = params.format;
+    let rows = state.queue_service.stream_all_queue_messages(&queue_name);
+    let lines = stream::unfold(rows, move |mut rows| async move {
+        let line =
+            rows.recv()
+                .await?
+                .map_err(std::io::Error::other)
+                .and_then(|message| match format {
+                    ExportFormat::Ndjson => serde_json::to_string(&message)
+                        .map(|json| json + "\n")
+                        .map_err(std::io::Error::other),
+                    ExportFormat::Csv => Ok(csv_row(&message)),
+                });
+        Some((line, rows))
+    });
+
+    let (content_type, csv_header) = match format {
+        ExportFormat::Ndjson => ("application/x-ndjson", None),
+        ExportFormat::Csv => ("text/csv", Some(Ok(CSV_HEADER.to_string()))),
+    };
+    let body = Body::from_stream(stream::iter(csv_header).chain(lines));
+    Ok(([(header::CONTENT_TYPE, content_type)], body).into_response())
+}
@@ -9,8 +9,76 @@ pub struct Config {
     pub queues: QueueDefaults,
     pub metrics: MetricsConfig,
     pub retention: RetentionConfig,
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
+    #[serde(default)]
+    pub runtime: RuntimeConfig,
+    #[serde(default)]
+    pub testing: TestingConfig,
+    #[serde(default)]
+    pub chaos: ChaosConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    #[serde(default)]
+    pub routing: RoutingConfig,
+    #[serde(default)]
+    pub receipt_handles: ReceiptHandleConfig,
 }
 
+const TEMPLATE_SECTION_COMMENTS: &[(&str, &str)] = &[
+    (
+        "server",
+        "HTTP listener. Set base_url when clients reach QLite through a different address, and admin_token to enable /admin",
+    ),
+    (
+        "database",
+        "SQLite file location and tuning. restore_from seeds a missing database from a backup",
+    ),
+    (
+        "queues",
+        "Defaults for queues that don't set their own attributes",
+    ),
+    ("metrics", "Prometheus metrics endpoint"),
+    (
+        "retention",
+        "Message retention: KeepForever only hides processed messages, Delete removes them after delete_after_days",
+    ),
+    (
+        "alerts",
+        "Set dlq_webhook_url to be notified when a message is dead-lettered",
+    ),
+    (
+        "webhooks",
+        "Event webhooks for sent, received and deleted messages",
+    ),
+    (
+        "runtime",
+        "Tokio runtime: MultiThread or CurrentThread, with optional worker_threads",
+    ),
+    (
+        "testing",
+        "Simulated latency and injected faults for client testing",
+    ),
+    (
+        "chaos",
+        "Randomly shift visibility timeouts by up to visibility_jitter seconds",
+    ),
+    (
+        "logging",
+        "delivery_log records every receive for GET /api/message/<id>/deliveries",
+    ),
+    (
+        "routing",
+        "Copy messages to other queues by attribute value: [[routing.rules]] with source_queue, attribute, value and target_queue",
+    ),
+    (
+        "receipt_handles",
+        "Plain or Signed receipt handles. Signed handles are HMAC'd with secret, or a random key when unset. include_sequence_number appends FIFO sequence numbers",
+    ),
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub port: u16,
@@ -18,6 +86,25 @@ pub struct ServerConfig {
     pub enable_ui: bool,
     pub base_url: Option<String>,
     pub max_connections: usize,
+    // Bearer token required by the /admin endpoints; they are disabled when unset
+    #[serde(default)]
+    pub admin_token: Option<String>,
+    // Larger request bodies are rejected with RequestTooLarge before being buffered
+    #[serde(default = "default_max_request_body_bytes")]
+    pub max_request_body_bytes: usize,
+    // Messages the UI loads per page of a queue's message list
+    #[serde(default = "default_ui_messages_per_page")]
+    pub ui_messages_per_page: usize,
+}
+
+// Form encoding can triple a 256 KiB body plus 256 KiB of attributes (every byte as %XX),
+// and parameter names come on top, so leave room for that
+fn default_max_request_body_bytes() -> usize {
+    2 * 1024 * 1024
+}
+
+fn default_ui_messages_per_page() -> usize {
+    crate::database::DEFAULT_MESSAGE_LIST_LIMIT
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +112,16 @@ pub struct DatabaseConfig {
     pub path: String,
     pub connection_pool_size: usize,
     pub busy_timeout_ms: u32,
+    // Backup to seed `path` from at startup; ignored if `path` already exists
+    #[serde(default)]
+    pub restore_from: Option<String>,
+    // Seconds between PRAGMA wal_checkpoint(TRUNCATE) runs; 0 disables periodic checkpoints
+    #[serde(default = "default_wal_checkpoint_interval_seconds")]
+    pub wal_checkpoint_interval_seconds: u32,
+}
+
+fn default_wal_checkpoint_interval_seconds() -> u32 {
+    300
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +131,64 @@ pub struct QueueDefaults {
     pub max_receive_count: u32,
     pub receive_message_wait_time_seconds: u32,
     pub fifo_throughput_limit: u32,
+    #[serde(default)]
+    pub message_id_format: MessageIdFormat,
+    #[serde(default)]
+    pub batch_mode: BatchMode,
+    // ChangeMessageVisibility can't push visibility past this many seconds after the
+    // receive, so a buggy consumer can't hide a message forever (AWS caps it at 12 hours)
+    #[serde(default = "default_max_visibility_extension_seconds")]
+    pub max_visibility_extension_seconds: u32,
+    // Standard queues deliver oldest first when true. AWS only promises best-effort ordering,
+    // so false hands out available messages in random order to surface consumers that
+    // depend on send order, at the cost of a full scan of the queue's visible messages per receive
+    #[serde(default = "default_standard_queue_strict_order")]
+    pub standard_queue_strict_order: bool,
+    // SendMessageBatch and DeleteMessageBatch requests with more entries than this fail
+    // with TooManyEntriesInBatchRequest (AWS allows 10)
+    #[serde(default = "default_max_batch_entries")]
+    pub max_batch_entries: usize,
+    // Lowercase queue names wherever they're given, so "Orders" and "orders" are one queue. AWS names
+    // are case-sensitive, hence off by default
+    #[serde(default)]
+    pub case_insensitive_queue_names: bool,
+    // Delay for sends without DelaySeconds on queues without their own DelaySeconds, to
+    // simulate propagation delay (at most 900, like DelaySeconds). A queue DelaySeconds of 0
+    // counts as unset.
+    #[serde(default)]
+    pub default_message_delay_seconds: u32,
+}
+
+fn default_max_visibility_extension_seconds() -> u32 {
+    43_200
+}
+
+fn default_standard_queue_strict_order() -> bool {
+    true
+}
+
+fn default_max_batch_entries() -> usize {
+    10
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BatchMode {
+    /// Each SendMessageBatch entry succeeds or fails on its own, as in AWS (default)
+    #[default]
+    PerEntry,
+    /// Any failed entry fails the whole request and nothing from it is stored
+    AllOrNothing,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MessageIdFormat {
+    /// Random UUID v4 (default)
+    #[default]
+    UuidV4,
+    /// Time-ordered UUID v7, so IDs sort roughly by send time
+    UuidV7,
+    /// Queue name prefix followed by a UUID v4, e.g. `orders-<uuid>`
+    Prefixed,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +196,180 @@ pub struct MetricsConfig {
     pub enabled: bool,
     pub endpoint: String,
     pub collection_interval_seconds: u32,
+    #[serde(default = "default_counter_reconcile_interval_seconds")]
+    pub counter_reconcile_interval_seconds: u32,
+}
+
+fn default_counter_reconcile_interval_seconds() -> u32 {
+    300
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertsConfig {
+    // Webhook POSTed whenever a message is moved to a dead letter queue
+    pub dlq_webhook_url: Option<String>,
+    #[serde(default = "default_alert_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_alert_timeout_ms() -> u64 {
+    5000
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            dlq_webhook_url: None,
+            timeout_ms: default_alert_timeout_ms(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhooksConfig {
+    #[serde(default)]
+    pub subscriptions: Vec<WebhookSubscription>,
+    #[serde(default = "default_webhook_max_concurrency")]
+    pub max_concurrency: usize,
+    #[serde(default = "default_webhook_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "default_alert_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_webhook_max_concurrency() -> usize {
+    8
+}
+
+fn default_webhook_max_retries() -> u32 {
+    3
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        Self {
+            subscriptions: Vec::new(),
+            max_concurrency: default_webhook_max_concurrency(),
+            max_retries: default_webhook_max_retries(),
+            timeout_ms: default_alert_timeout_ms(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookSubscription {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    // Only deliver events for this queue; all queues when omitted
+    pub queue: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum WebhookEvent {
+    #[serde(rename = "message_sent")]
+    Sent,
+    #[serde(rename = "message_received")]
+    Received,
+    #[serde(rename = "message_deleted")]
+    Deleted,
+    #[serde(rename = "message_dlq")]
+    Dlq,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuntimeConfig {
+    #[serde(default)]
+    pub flavor: RuntimeFlavor,
+    // Worker threads for the multi-threaded runtime; defaults to the number of CPUs
+    pub worker_threads: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RuntimeFlavor {
+    /// Work-stealing scheduler across `worker_threads` threads (default)
+    #[default]
+    MultiThread,
+    /// Everything runs on the main thread; lowest footprint, no parallelism
+    CurrentThread,
+}
+
+impl RuntimeConfig {
+    pub fn worker_threads(&self) -> usize {
+        self.worker_threads.unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        })
+    }
+}
+
+// Testing aids that make QLite behave less like a fast local process. Never enable
+// these for anything other than exercising client behaviour.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TestingConfig {
+    // Artificial delay before every send and receive response, in milliseconds
+    #[serde(default)]
+    pub simulate_latency_ms: u64,
+    // Share of SQS requests (0.0-1.0) answered with ServiceUnavailable or Throttling
+    #[serde(default)]
+    pub fault_injection_rate: f64,
+}
+
+// Chaos options deliberately break timing guarantees to flush out consumers that assume
+// exactly-once delivery. Off by default.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChaosConfig {
+    // Receives shorten or extend the visibility timeout by a random amount of up to this
+    // many seconds, so messages may reappear early or stay hidden longer than configured
+    #[serde(default)]
+    pub visibility_jitter: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoutingConfig {
+    #[serde(default)]
+    pub rules: Vec<RoutingRule>,
+}
+
+// QLite extension: a message sent to source_queue whose `attribute` has the string value
+// `value` is also copied to target_queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingRule {
+    pub source_queue: String,
+    pub attribute: String,
+    pub value: String,
+    pub target_queue: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReceiptHandleConfig {
+    #[serde(default)]
+    pub encoding: ReceiptHandleEncoding,
+    // HMAC key for Signed handles. A random key is generated at startup when unset, which
+    // invalidates outstanding handles on restart.
+    #[serde(default)]
+    pub secret: Option<String>,
+    // Debugging aid: handles for FIFO messages also carry the message's sequence number
+    #[serde(default)]
+    pub include_sequence_number: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReceiptHandleEncoding {
+    /// "<message id>:<token>", checked against the token stored with the message (default)
+    #[default]
+    Plain,
+    /// A signed token carrying the message, queue and expiry; tampered or expired
+    /// handles are rejected with ReceiptHandleIsInvalid
+    Signed,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    // Record every receive in message_delivery_log. Off by default since it adds a
+    // write per delivered message.
+    #[serde(default)]
+    pub delivery_log: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +378,13 @@ pub struct RetentionConfig {
     pub batch_size: u32,
     pub mode: RetentionMode,
     pub delete_after_days: Option<u32>, // Only used in Delete mode
+    // Dead-lettered messages older than this are deleted; None keeps them forever
+    #[serde(default)]
+    pub dlq_retention_seconds: Option<u64>,
+    // Consumer-deleted messages are removed this long after deletion, in either mode;
+    // None keeps them (KeepForever) until the retention period removes them (Delete)
+    #[serde(default)]
+    pub prune_deleted_after_seconds: Option<u64>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -59,6 +395,34 @@ pub enum RetentionMode {
     Delete,
 }
 
+impl std::str::FromStr for RetentionMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace(['-', '_'], "").as_str() {
+            "keepforever" => Ok(RetentionMode::KeepForever),
+            "delete" => Ok(RetentionMode::Delete),
+            _ => Err(format!(
+                "invalid retention mode '{}', expected keep-forever or delete",
+                s
+            )),
+        }
+    }
+}
+
+// Server settings given as command line flags; unset fields keep the loaded value
+#[derive(Debug, Clone, Default)]
+pub struct CliOverrides {
+    pub port: Option<u16>,
+    pub host: Option<String>,
+    pub base_url: Option<String>,
+    pub enable_ui: bool,
+    pub db_path: Option<String>,
+    pub retention_mode: Option<RetentionMode>,
+    pub retention_days: Option<u32>,
+    pub default_visibility_timeout: Option<u32>,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -68,11 +432,16 @@ impl Default for Config {
                 enable_ui: false,
                 base_url: None,
                 max_connections: 1000,
+                admin_token: None,
+                max_request_body_bytes: default_max_request_body_bytes(),
+                ui_messages_per_page: default_ui_messages_per_page(),
             },
             database: DatabaseConfig {
                 path: "qlite.db".to_string(),
                 connection_pool_size: 10,
                 busy_timeout_ms: 5000,
+                restore_from: None,
+                wal_checkpoint_interval_seconds: default_wal_checkpoint_interval_seconds(),
             },
             queues: QueueDefaults {
                 visibility_timeout_seconds: 30,
@@ -80,18 +449,36 @@ impl Default for Config {
                 max_receive_count: 10,
                 receive_message_wait_time_seconds: 0,
                 fifo_throughput_limit: 300,
+                message_id_format: MessageIdFormat::default(),
+                batch_mode: BatchMode::default(),
+                max_visibility_extension_seconds: default_max_visibility_extension_seconds(),
+                standard_queue_strict_order: default_standard_queue_strict_order(),
+                max_batch_entries: default_max_batch_entries(),
+                case_insensitive_queue_names: false,
+                default_message_delay_seconds: 0,
             },
             metrics: MetricsConfig {
                 enabled: true,
                 endpoint: "/metrics".to_string(),
                 collection_interval_seconds: 60,
+                counter_reconcile_interval_seconds: default_counter_reconcile_interval_seconds(),
             },
             retention: RetentionConfig {
                 cleanup_interval_seconds: 3600, // 1 hour
                 batch_size: 1000,
                 mode: RetentionMode::KeepForever, // Default: keep messages forever
                 delete_after_days: Some(14),      // Only used in Delete mode
+                dlq_retention_seconds: None,      // Keep dead letters forever
+                prune_deleted_after_seconds: None, // Keep deleted messages
             },
+            alerts: AlertsConfig::default(),
+            webhooks: WebhooksConfig::default(),
+            runtime: RuntimeConfig::default(),
+            testing: TestingConfig::default(),
+            chaos: ChaosConfig::default(),
+            logging: LoggingConfig::default(),
+            routing: RoutingConfig::default(),
+            receipt_handles: ReceiptHandleConfig::default(),
         }
     }
 }
@@ -107,6 +494,42 @@ impl Config {
         Ok(config)
     }
 
+    // The default config as TOML, with a comment above each section. Optional settings
+    // that are unset by default (admin_token, restore_from, ...) are left out.
+    pub fn template_toml() -> Result<String, ConfigError> {
+        let toml = toml::to_string_pretty(&Self::default())
+            .map_err(|e| ConfigError::Parse(e.to_string()))?;
+
+        let mut output = String::from(
+            "# QLite configuration, generated with the default values.\n\
+             # QLITE_* environment variables and server flags override these settings.\n\n",
+        );
+        for line in toml.lines() {
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']'))
+                && let Some((_, comment)) = TEMPLATE_SECTION_COMMENTS
+                    .iter()
+                    .find(|(name, _)| *name == section)
+            {
+                output.push_str(&format!("# {}\n", comment));
+            }
+            output.push_str(line);
+            output.push('\n');
+        }
+        Ok(output)
+    }
+
+    pub fn write_template<P: AsRef<Path>>(path: P, overwrite: bool) -> Result<(), ConfigError> {
+        let path = path.as_ref();
+        if path.exists() && !overwrite {
+            return Err(ConfigError::Io(format!(
+                "{} already exists (use --force to overwrite)",
+                path.display()
+            )));
+        }
+
+        fs::write(path, Self::template_toml()?).map_err(|e| ConfigError::Io(e.to_string()))
+    }
+
     pub fn load_with_overrides() -> Result<Self, ConfigError> {
         let mut config = if Path::new("qlite.toml").exists() {
             Self::load_from_file("qlite.toml")?
@@ -121,6 +544,45 @@ impl Config {
         Ok(config)
     }
 
+    // Command line flags win over the environment and the config file, so they're applied
+    // after load_with_overrides and validated again
+    pub fn apply_cli_overrides(&mut self, overrides: &CliOverrides) -> Result<(), ConfigError> {
+        if let Some(port) = overrides.port {
+            self.server.port = port;
+        }
+        if let Some(host) = &overrides.host {
+            self.server.host = host.clone();
+        }
+        if let Some(base_url) = &overrides.base_url {
+            self.server.base_url = Some(base_url.clone());
+        }
+        if overrides.enable_ui {
+            self.server.enable_ui = true;
+        }
+        if let Some(db_path) = &overrides.db_path {
+            self.database.path = db_path.clone();
+        }
+        if let Some(mode) = overrides.retention_mode {
+            self.retention.mode = mode;
+        }
+        if let Some(days) = overrides.retention_days {
+            self.retention.delete_after_days = Some(days);
+        }
+        if let Some(timeout) = overrides.default_visibility_timeout {
+            self.queues.visibility_timeout_seconds = timeout;
+        }
+
+        self.validate()
+    }
+
+    // Base URL used in queue URLs, defaulting to localhost on the configured port
+    pub fn effective_base_url(&self) -> String {
+        self.server
+            .base_url
+            .clone()
+            .unwrap_or_else(|| format!("http://localhost:{}", self.server.port))
+    }
+
     fn apply_env_overrides(&mut self) {
         if let Ok(port) = std::env::var("QLITE_PORT")
             && let Ok(port_num) = port.parse::<u16>()
@@ -140,13 +602,63 @@ impl Config {
             self.server.enable_ui = enable_ui.to_lowercase() == "true";
         }
 
+        if let Ok(webhook_url) = std::env::var("QLITE_DLQ_WEBHOOK_URL") {
+            self.alerts.dlq_webhook_url = Some(webhook_url);
+        }
+
+        if let Ok(restore_from) = std::env::var("QLITE_RESTORE_FROM") {
+            self.database.restore_from = Some(restore_from);
+        }
+
+        if let Ok(admin_token) = std::env::var("QLITE_ADMIN_TOKEN") {
+            self.server.admin_token = Some(admin_token);
+        }
+
+        if let Ok(secret) = std::env::var("QLITE_RECEIPT_HANDLE_SECRET") {
+            self.receipt_handles.secret = Some(secret);
+        }
+
         if let Ok(base_url) = std::env::var("QLITE_BASE_URL") {
             self.server.base_url = Some(base_url);
         }
 
+        if let Ok(max_body) = std::env::var("QLITE_MAX_REQUEST_BODY_BYTES")
+            && let Ok(bytes) = max_body.parse::<usize>()
+        {
+            self.server.max_request_body_bytes = bytes;
+        }
+
+        if let Ok(per_page) = std::env::var("QLITE_UI_MESSAGES_PER_PAGE")
+            && let Ok(count) = per_page.parse::<usize>()
+        {
+            self.server.ui_messages_per_page = count;
+        }
+
+        if let Ok(worker_threads) = std::env::var("QLITE_WORKER_THREADS")
+            && let Ok(count) = worker_threads.parse::<usize>()
+        {
+            self.runtime.worker_threads = Some(count);
+        }
+
+        if let Ok(latency) = std::env::var("QLITE_SIMULATE_LATENCY_MS")
+            && let Ok(ms) = latency.parse::<u64>()
+        {
+            self.testing.simulate_latency_ms = ms;
+        }
+
+        if let Ok(rate) = std::env::var("QLITE_FAULT_INJECTION_RATE")
+            && let Ok(rate) = rate.parse::<f64>()
+        {
+            self.testing.fault_injection_rate = rate;
+        }
+
         if let Ok(metrics_enabled) = std::env::var("QLITE_METRICS_ENABLED") {
             self.metrics.enabled = metrics_enabled.to_lowercase() == "true";
         }
+
+        if let Ok(delivery_log) = std::env::var("QLITE_DELIVERY_LOG") {
+            self.logging.delivery_log = delivery_log.to_lowercase() == "true";
+        }
     }
 
     fn validate(&self) -> Result<(), ConfigError> {
@@ -162,6 +674,30 @@ impl Config {
             ));
         }
 
+        if let Some(base_url) = &self.server.base_url {
+            let valid = reqwest::Url::parse(base_url).is_ok_and(|url| {
+                matches!(url.scheme(), "http" | "https") && url.host_str().is_some()
+            });
+            if !valid {
+                return Err(ConfigError::Validation(format!(
+                    "Base URL '{}' must be an absolute http(s) URL",
+                    base_url
+                )));
+            }
+        }
+
+        if self.server.max_request_body_bytes == 0 {
+            return Err(ConfigError::Validation(
+                "Max request body bytes must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.server.ui_messages_per_page == 0 {
+            return Err(ConfigError::Validation(
+                "UI messages per page must be greater than 0".to_string(),
+            ));
+        }
+
         if self.database.path.is_empty() {
             return Err(ConfigError::Validation(
                 "Database path cannot be empty".to_string(),
@@ -193,11 +729,107 @@ impl Config {
             ));
         }
 
+        if self.alerts.timeout_ms == 0 {
+            return Err(ConfigError::Validation(
+                "Alert timeout must be > 0".to_string(),
+            ));
+        }
+
+        if self.webhooks.max_concurrency == 0 {
+            return Err(ConfigError::Validation(
+                "Webhook max concurrency must be > 0".to_string(),
+            ));
+        }
+
+        if self.webhooks.timeout_ms == 0 {
+            return Err(ConfigError::Validation(
+                "Webhook timeout must be > 0".to_string(),
+            ));
+        }
+
+        if let Some(subscription) = self
+            .webhooks
+            .subscriptions
+            .iter()
+            .find(|s| s.url.is_empty() || s.events.is_empty())
+        {
+            return Err(ConfigError::Validation(format!(
+                "Webhook subscription '{}' needs a url and at least one event",
+                subscription.url
+            )));
+        }
+
+        if let Some(rule) = self.routing.rules.iter().find(|r| {
+            r.source_queue.is_empty()
+                || r.attribute.is_empty()
+                || r.target_queue.is_empty()
+                || r.source_queue == r.target_queue
+        }) {
+            return Err(ConfigError::Validation(format!(
+                "Routing rule from '{}' to '{}' needs an attribute and distinct source and target queues",
+                rule.source_queue, rule.target_queue
+            )));
+        }
+
+        if self.receipt_handles.secret.as_deref() == Some("") {
+            return Err(ConfigError::Validation(
+                "Receipt handle secret must not be empty".to_string(),
+            ));
+        }
+
+        if self.retention.dlq_retention_seconds == Some(0) {
+            return Err(ConfigError::Validation(
+                "DLQ retention must be > 0 seconds".to_string(),
+            ));
+        }
+
+        if self.retention.prune_deleted_after_seconds == Some(0) {
+            return Err(ConfigError::Validation(
+                "Deleted message pruning must be > 0 seconds".to_string(),
+            ));
+        }
+
+        if !(0.0..=1.0).contains(&self.testing.fault_injection_rate) {
+            return Err(ConfigError::Validation(
+                "Fault injection rate must be between 0.0 and 1.0".to_string(),
+            ));
+        }
+
+        if self.runtime.worker_threads == Some(0) {
+            return Err(ConfigError::Validation(
+                "Worker threads must be > 0".to_string(),
+            ));
+        }
+
+        if self.metrics.counter_reconcile_interval_seconds == 0 {
+            return Err(ConfigError::Validation(
+                "Counter reconcile interval must be > 0".to_string(),
+            ));
+        }
+
+        if self.queues.max_visibility_extension_seconds == 0 {
+            return Err(ConfigError::Validation(
+                "Max visibility extension must be > 0".to_string(),
+            ));
+        }
+
+        if self.queues.max_batch_entries == 0 {
+            return Err(ConfigError::Validation(
+                "Max batch entries must be > 0".to_string(),
+            ));
+        }
+
+        if self.queues.default_message_delay_seconds > 900 {
+            return Err(ConfigError::Validation(
+                "Default message delay must be <= 900 seconds".to_string(),
+            ));
+        }
+
         Ok(())
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct QueueConfig {
     pub name: String,
     pub is_fifo: bool,
@@ -208,6 +840,23 @@ pub struct QueueConfig {
     pub dead_letter_target_arn: Option<String>,
     pub delay_seconds: u32,
     pub receive_message_wait_time_seconds: u32,
+    // QLite extension: when false, deliveries don't increment receive_count
+    #[serde(default = "default_count_receives")]
+    pub count_receives: bool,
+    // QLite extension: a FIFO message can only be deleted after every earlier message in its group
+    #[serde(default)]
+    pub ack_watermark: bool,
+    // QLite extension: how long a deduplication id suppresses resends (AWS fixes it at 5 minutes)
+    #[serde(default = "default_deduplication_window_seconds")]
+    pub deduplication_window_seconds: u32,
+}
+
+fn default_count_receives() -> bool {
+    true
+}
+
+pub fn default_deduplication_window_seconds() -> u32 {
+    300
 }
 
 // QueueType enum removed - using is_fifo boolean instead
@@ -225,6 +874,9 @@ impl Default for QueueConfig {
             dead_letter_target_arn: None,
             delay_seconds: 0,
             receive_message_wait_time_seconds: defaults.receive_message_wait_time_seconds,
+            count_receives: true,
+            ack_watermark: false,
+            deduplication_window_seconds: default_deduplication_window_seconds(),
         }
     }
 }
@@ -240,14 +892,101 @@ impl QueueConfig {
         }
     }
 
-    #[allow(dead_code)]
-    pub fn validate(&self) -> Result<(), ConfigError> {
+    // Set one SQS queue attribute, as named in CreateQueue. Unknown attributes and values
+    // that don't parse are ignored.
+    pub fn apply_attribute(&mut self, name: &str, value: &str) {
+        match name {
+            "FifoQueue" => self.is_fifo = value.eq_ignore_ascii_case("true"),
+            "ContentBasedDeduplication" => {
+                self.content_based_deduplication = value.eq_ignore_ascii_case("true")
+            }
+            "VisibilityTimeout" => {
+                if let Ok(seconds) = value.parse() {
+                    self.visibility_timeout_seconds = seconds;
+                }
+            }
+            "MessageRetentionPeriod" => {
+                if let Ok(seconds) = value.parse() {
+                    self.message_retention_period_seconds = seconds;
+                }
+            }
+            "DelaySeconds" => {
+                if let Ok(seconds) = value.parse() {
+                    self.delay_seconds = seconds;
+                }
+            }
+            "ReceiveMessageWaitTimeSeconds" => {
+                if let Ok(seconds) = value.parse() {
+                    self.receive_message_wait_time_seconds = seconds;
+                }
+            }
+            "RedrivePolicy" => {
+                if let Ok(policy) = serde_json::from_str::<serde_json::Value>(value) {
+                    self.max_receive_count = policy
+                        .get("maxReceiveCount")
+                        .and_then(|v| v.as_u64())
+                        .map(|v| v as u32);
+                    self.dead_letter_target_arn = policy
+                        .get("deadLetterTargetArn")
+                        .and_then(|v| v.as_str())
+                        .map(str::to_string);
+                }
+            }
+            "CountReceives" => self.count_receives = !value.eq_ignore_ascii_case("false"),
+            "AckWatermark" => self.ack_watermark = value.eq_ignore_ascii_case("true"),
+            "DeduplicationWindowSeconds" => {
+                if let Ok(seconds) = value.parse() {
+                    self.deduplication_window_seconds = seconds;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // The first attribute (by name) whose value differs from this config; CreateQueue for
+    // an existing queue fails on it. Attributes that aren't given don't conflict.
+    pub fn conflicting_attribute<'a>(
+        &self,
+        attributes: &'a std::collections::HashMap<String, String>,
+    ) -> Option<&'a str> {
+        let mut names: Vec<&String> = attributes.keys().collect();
+        names.sort();
+        names
+            .into_iter()
+            .find(|name| {
+                let mut requested = self.clone();
+                requested.apply_attribute(name, &attributes[*name]);
+                requested != *self
+            })
+            .map(String::as_str)
+    }
+
+    // Check the queue name against SQS naming rules and the FIFO suffix
+    pub fn validate_name(&self) -> Result<(), ConfigError> {
         if self.name.is_empty() {
             return Err(ConfigError::Validation(
                 "Queue name cannot be empty".to_string(),
             ));
         }
 
+        if self.name.len() > 80 {
+            return Err(ConfigError::Validation(
+                "Queue name cannot exceed 80 characters".to_string(),
+            ));
+        }
+
+        let base_name = self.name.strip_suffix(".fifo").unwrap_or(&self.name);
+        if base_name.is_empty()
+            || !base_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(ConfigError::Validation(
+                "Queue name can only contain alphanumeric characters, hyphens and underscores"
+                    .to_string(),
+            ));
+        }
+
         if self.is_fifo {
             if !self.name.ends_with(".fifo") {
                 return Err(ConfigError::Validation(
@@ -260,6 +999,13 @@ impl QueueConfig {
             ));
         }
 
+        Ok(())
+    }
+
+    #[allow(dead_code)]
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.validate_name()?;
+
         if self.visibility_timeout_seconds == 0 {
             return Err(ConfigError::Validation(
                 "Visibility timeout must be > 0".to_string(),
@@ -322,6 +1068,84 @@ mod tests {
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn test_cli_overrides() {
+        let mut config = Config::default();
+        config.server.enable_ui = true;
+        config.server.host = "127.0.0.1".to_string();
+
+        let overrides = CliOverrides {
+            port: Some(9324),
+            db_path: Some("/data/queues.db".to_string()),
+            retention_mode: Some("delete".parse().unwrap()),
+            retention_days: Some(3),
+            default_visibility_timeout: Some(120),
+            ..Default::default()
+        };
+        config.apply_cli_overrides(&overrides).unwrap();
+
+        assert_eq!(config.server.port, 9324);
+        assert_eq!(config.database.path, "/data/queues.db");
+        assert_eq!(config.retention.mode, RetentionMode::Delete);
+        assert_eq!(config.retention.delete_after_days, Some(3));
+        assert_eq!(config.queues.visibility_timeout_seconds, 120);
+        assert_eq!(config.effective_base_url(), "http://localhost:9324");
+        // Flags that weren't given keep the loaded values
+        assert_eq!(config.server.host, "127.0.0.1");
+        assert!(config.server.enable_ui);
+
+        let overrides = CliOverrides {
+            default_visibility_timeout: Some(0),
+            ..Default::default()
+        };
+        assert!(config.apply_cli_overrides(&overrides).is_err());
+        assert!("sometimes".parse::<RetentionMode>().is_err());
+
+        for base_url in ["localhost:3000", "ftp://queues.local", "not a url"] {
+            let overrides = CliOverrides {
+                base_url: Some(base_url.to_string()),
+                ..Default::default()
+            };
+            assert!(
+                config.apply_cli_overrides(&overrides).is_err(),
+                "{}",
+                base_url
+            );
+        }
+    }
+
+    #[test]
+    fn test_template_round_trips_to_default_config() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("qlite.toml");
+        Config::write_template(&path, false).unwrap();
+
+        let template = fs::read_to_string(&path).unwrap();
+        assert!(template.contains("# Prometheus metrics endpoint\n[metrics]"));
+
+        let parsed = Config::load_from_file(&path).unwrap();
+        assert_eq!(
+            toml::to_string(&parsed).unwrap(),
+            toml::to_string(&Config::default()).unwrap()
+        );
+
+        // An existing file is only replaced when asked to
+        assert!(Config::write_template(&path, false).is_err());
+        assert!(Config::write_template(&path, true).is_ok());
+    }
+
+    #[test]
+    fn test_runtime_worker_threads() {
+        let mut config = Config::default();
+        assert!(config.runtime.worker_threads() >= 1);
+
+        config.runtime.worker_threads = Some(4);
+        assert_eq!(config.runtime.worker_threads(), 4);
+
+        config.runtime.worker_threads = Some(0);
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_fifo_queue_validation() {
         let mut config = QueueConfig::new("test.fifo".to_string(), true);
@@ -339,4 +1163,20 @@ mod tests {
         config.name = "test.fifo".to_string();
         assert!(config.validate().is_err());
     }
+
+    #[test]
+    fn test_queue_name_characters() {
+        let mut config = QueueConfig::new("orders_v2-test".to_string(), false);
+        assert!(config.validate_name().is_ok());
+
+        config.name = "orders queue".to_string();
+        assert!(config.validate_name().is_err());
+
+        config.name = "a".repeat(81);
+        assert!(config.validate_name().is_err());
+
+        config.name = ".fifo".to_string();
+        config.is_fifo = true;
+        assert!(config.validate_name().is_err());
+    }
 }
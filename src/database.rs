@@ -1,8 +1,22 @@
+use crate::message::IdGenerator;
+use crate::metrics::Metrics;
 use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio_rusqlite::{Connection, OptionalExtension, Result};
-use tracing::info;
+use tracing::{info, warn};
+
+// Aggregate FILTER clauses need SQLite 3.30.0; json_extract and json_each need the JSON1
+// functions, which are built in from 3.38.0 and optional before that
+const MIN_SQLITE_VERSION: i32 = 3_030_000;
+
+// Timestamps are stored as RFC 3339 UTC strings. Their fractional digits vary, so compare
+// them in SQL through julianday() (including against julianday('now')), never as raw text.
 
 // Type aliases to fix clippy warnings
+// (name, created_at, tags sorted by key)
+pub type QueueWithTags = (String, String, Vec<(String, String)>);
 pub type DelayedMessageTuple = (
     String,
     String,
@@ -10,8 +24,44 @@ pub type DelayedMessageTuple = (
     Option<String>,
     Option<String>,
     Option<String>,
+    Option<String>,
+);
+// (id, body, created_at, attributes, receipt_handle, receive_count, first_received_at,
+//  visibility_deadline)
+pub type ReceivedRow = (
+    String,
+    String,
+    String,
+    Option<String>,
+    String,
+    u32,
+    String,
+    String,
+);
+// (id, body, created_at, visibility_timeout, receive_count, attributes, deduplication_id,
+//  status, processed_at, deleted_at)
+pub type QueueMessageRow = (
+    String,
+    String,
+    String,
+    Option<String>,
+    u32,
+    Option<String>,
+    Option<String>,
+    String,
+    Option<String>,
+    Option<String>,
 );
 
+// A message receive_message moved to its queue's DLQ instead of delivering
+#[derive(Debug, Clone)]
+pub struct DeadLettered {
+    pub message_id: String,
+    pub queue_name: String,
+    pub dlq_name: String,
+    pub reason: String,
+}
+
 // Struct to fix too_many_arguments warning
 #[derive(Debug)]
 pub struct SendMessageParams<'a> {
@@ -24,46 +74,885 @@ pub struct SendMessageParams<'a> {
     pub message_group_id: Option<&'a str>,
 }
 
+// Attribute names are interpolated into JSON paths and index names, so keep them simple
+pub fn is_valid_attribute_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+// Expression over an attribute's string value. Queries must spell it exactly like this
+// (with the name inlined, not bound) for SQLite to use an attribute index.
+fn attribute_value_expr(name: &str) -> String {
+    format!("json_extract(attributes, '$.\"{}\".StringValue')", name)
+}
+
+fn sql_string_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+// Conditions for a filtered purge; all provided conditions must match
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct PurgeFilter {
+    pub status: Option<String>,
+    pub body_contains: Option<String>,
+    pub attribute_name: Option<String>,
+    // When omitted, any message carrying attribute_name matches
+    pub attribute_value: Option<String>,
+}
+
+impl PurgeFilter {
+    const STATUSES: [&'static str; 4] = ["active", "processing", "deleted", "dlq_pending"];
+
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.status.is_none() && self.body_contains.is_none() && self.attribute_name.is_none() {
+            return Err(
+                "At least one of status, body_contains or attribute_name is required".to_string(),
+            );
+        }
+
+        if let Some(status) = &self.status
+            && !Self::STATUSES.contains(&status.as_str())
+        {
+            return Err(format!(
+                "Invalid status '{}', expected one of: {}",
+                status,
+                Self::STATUSES.join(", ")
+            ));
+        }
+
+        if self.body_contains.as_ref().is_some_and(|s| s.is_empty()) {
+            return Err("body_contains cannot be empty".to_string());
+        }
+
+        if self.attribute_value.is_some() && self.attribute_name.is_none() {
+            return Err("attribute_value requires attribute_name".to_string());
+        }
+
+        if let Some(name) = &self.attribute_name
+            && !is_valid_attribute_name(name)
+        {
+            return Err(format!("Invalid attribute name '{}'", name));
+        }
+
+        Ok(())
+    }
+}
+
+// A schema change applied once, in version order, and recorded in schema_migrations
+struct Migration {
+    version: i64,
+    description: &'static str,
+    step: MigrationStep,
+}
+
+enum MigrationStep {
+    // ALTER TABLE <table> ADD COLUMN <column> <ddl>, skipped if the column exists
+    AddColumn {
+        table: &'static str,
+        column: &'static str,
+        ddl: &'static str,
+    },
+    // Arbitrary statement(s), e.g. a data backfill
+    Sql(&'static str),
+}
+
+// Append new migrations to the end; never edit or reorder applied ones.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "add messages.status",
+        step: MigrationStep::AddColumn {
+            table: "messages",
+            column: "status",
+            ddl: "TEXT DEFAULT 'active'",
+        },
+    },
+    Migration {
+        version: 2,
+        description: "add messages.processed_at",
+        step: MigrationStep::AddColumn {
+            table: "messages",
+            column: "processed_at",
+            ddl: "TEXT",
+        },
+    },
+    Migration {
+        version: 3,
+        description: "add messages.deleted_at",
+        step: MigrationStep::AddColumn {
+            table: "messages",
+            column: "deleted_at",
+            ddl: "TEXT",
+        },
+    },
+    Migration {
+        version: 4,
+        description: "add messages.delay_until",
+        step: MigrationStep::AddColumn {
+            table: "messages",
+            column: "delay_until",
+            ddl: "TEXT",
+        },
+    },
+    Migration {
+        version: 5,
+        description: "add messages.message_group_id",
+        step: MigrationStep::AddColumn {
+            table: "messages",
+            column: "message_group_id",
+            ddl: "TEXT",
+        },
+    },
+    Migration {
+        version: 6,
+        description: "add messages.sequence_number",
+        step: MigrationStep::AddColumn {
+            table: "messages",
+            column: "sequence_number",
+            ddl: "INTEGER",
+        },
+    },
+    Migration {
+        version: 7,
+        description: "add queue_config.count_receives",
+        step: MigrationStep::AddColumn {
+            table: "queue_config",
+            column: "count_receives",
+            ddl: "BOOLEAN DEFAULT TRUE",
+        },
+    },
+    Migration {
+        version: 8,
+        description: "add messages.receive_count",
+        step: MigrationStep::AddColumn {
+            table: "messages",
+            column: "receive_count",
+            ddl: "INTEGER DEFAULT 0",
+        },
+    },
+    Migration {
+        version: 9,
+        description: "add messages.nack_count",
+        step: MigrationStep::AddColumn {
+            table: "messages",
+            column: "nack_count",
+            ddl: "INTEGER DEFAULT 0",
+        },
+    },
+    Migration {
+        version: 10,
+        description: "backfill NULL messages.status as active",
+        step: MigrationStep::Sql("UPDATE messages SET status = 'active' WHERE status IS NULL"),
+    },
+    Migration {
+        version: 11,
+        description: "add queue_config.ack_watermark",
+        step: MigrationStep::AddColumn {
+            table: "queue_config",
+            column: "ack_watermark",
+            ddl: "BOOLEAN DEFAULT FALSE",
+        },
+    },
+    Migration {
+        version: 12,
+        description: "add messages.receipt_token",
+        step: MigrationStep::AddColumn {
+            table: "messages",
+            column: "receipt_token",
+            ddl: "TEXT",
+        },
+    },
+    Migration {
+        version: 13,
+        description: "add messages.first_received_at",
+        step: MigrationStep::AddColumn {
+            table: "messages",
+            column: "first_received_at",
+            ddl: "TEXT",
+        },
+    },
+    Migration {
+        version: 14,
+        description: "backfill messages.first_received_at from processed_at",
+        step: MigrationStep::Sql(
+            "UPDATE messages SET first_received_at = processed_at WHERE first_received_at IS NULL AND processed_at IS NOT NULL",
+        ),
+    },
+    Migration {
+        version: 15,
+        description: "return dlq_pending messages to their queue so the next receive dead-letters them",
+        step: MigrationStep::Sql(
+            "UPDATE messages SET status = 'active' WHERE status = 'dlq_pending'",
+        ),
+    },
+    Migration {
+        version: 16,
+        description: "add queue_config.deduplication_window_seconds",
+        step: MigrationStep::AddColumn {
+            table: "queue_config",
+            column: "deduplication_window_seconds",
+            ddl: "INTEGER DEFAULT 300",
+        },
+    },
+];
+
+// Databases created before migrations were tracked may already have the column, so
+// check first instead of ignoring ALTER errors, which would also hide real failures
+fn add_column_if_missing(
+    conn: &rusqlite::Connection,
+    table: &str,
+    column: &str,
+    ddl: &str,
+) -> rusqlite::Result<bool> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        [table, column],
+        |row| row.get::<_, i64>(0),
+    )? > 0;
+
+    if exists {
+        return Ok(false);
+    }
+
+    conn.execute(
+        &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, ddl),
+        [],
+    )?;
+    Ok(true)
+}
+
+// Apply every migration newer than the recorded schema version. Returns how many ran.
+fn apply_migrations(conn: &mut rusqlite::Connection) -> rusqlite::Result<usize> {
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        )
+        "#,
+        [],
+    )?;
+
+    let current: i64 = conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+        [],
+        |row| row.get(0),
+    )?;
+
+    let mut applied = 0;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.transaction()?;
+        match &migration.step {
+            MigrationStep::AddColumn { table, column, ddl } => {
+                add_column_if_missing(&tx, table, column, ddl)?;
+            }
+            MigrationStep::Sql(sql) => tx.execute_batch(sql)?,
+        }
+        tx.execute(
+            "INSERT INTO schema_migrations (version, description, applied_at) VALUES (?1, ?2, ?3)",
+            rusqlite::params![
+                migration.version,
+                migration.description,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        tx.commit()?;
+
+        info!(
+            "Applied schema migration {}: {}",
+            migration.version, migration.description
+        );
+        applied += 1;
+    }
+
+    Ok(applied)
+}
+
 #[derive(Clone)]
 pub struct Database {
     connection: Connection,
+    // Separate connection for WAL checkpoints so they don't queue behind regular queries
+    checkpoint_connection: Connection,
+    path: String,
+    // Set once the schema and every performance index have been applied
+    schema_ready: Arc<AtomicBool>,
+    // How long a received message stays hidden (queues.visibility_timeout_seconds)
+    visibility_timeout_seconds: u32,
+    // Chaos testing: receives randomly shift the visibility timeout by up to this many seconds
+    visibility_jitter_seconds: u32,
+    // Standard queues receive oldest first when set, otherwise in random order
+    // (queues.standard_queue_strict_order)
+    strict_standard_order: bool,
+    // FIFO receipt handles carry the message's sequence number
+    // (receipt_handles.include_sequence_number)
+    sequence_in_receipt_handles: bool,
+    // Receives record time-to-first-receive here when set
+    metrics: Option<Arc<Metrics>>,
+}
+
+// Move a message into dead_letter_messages on the caller's connection or transaction.
+// Returns the source queue and DLQ names, or None if nothing was moved.
+fn move_to_dlq(
+    conn: &rusqlite::Connection,
+    message_id: &str,
+    failure_reason: &str,
+    moved_at: &str,
+) -> rusqlite::Result<Option<(String, String)>> {
+    // First, get the message details and queue configuration
+    let message_result = conn.prepare(
+        "SELECT queue_name, body, created_at, attributes, receive_count FROM messages WHERE id = ?1 AND status != 'deleted'"
+    )?.query_row([&message_id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,  // queue_name
+            row.get::<_, String>(1)?,  // body
+            row.get::<_, String>(2)?,  // created_at
+            row.get::<_, Option<String>>(3)?,  // attributes
+            row.get::<_, i32>(4)?      // receive_count
+        ))
+    });
+
+    if let Ok((queue_name, body, created_at, attributes, receive_count)) = message_result {
+        // Get DLQ configuration from queue_config
+        if let Some(dlq_arn) = conn
+            .prepare("SELECT dead_letter_target_arn FROM queue_config WHERE name = ?1")?
+            .query_row([&queue_name], |row| row.get::<_, Option<String>>(0))
+            .optional()?
+        {
+            if let Some(dlq_name) = dlq_arn {
+                // Extract DLQ name from ARN (simplified - assume it's just the queue name for now)
+                let dlq_queue_name = dlq_name_from_arn(&dlq_name);
+
+                // Create JSON representation of original message data
+                let original_message_data = serde_json::json!({
+                    "messageId": message_id,
+                    "body": body,
+                    "attributes": attributes,
+                    "createdAt": created_at,
+                    "receiveCount": receive_count
+                })
+                .to_string();
+
+                // Insert into dead_letter_messages table
+                conn.execute(
+                    r#"
+                    INSERT INTO dead_letter_messages
+                    (id, original_queue_name, dlq_name, failure_reason, moved_at,
+                     original_message_data, original_body, original_attributes,
+                     receive_count, original_created_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                    "#,
+                    rusqlite::params![
+                        message_id,
+                        queue_name,
+                        dlq_queue_name,
+                        failure_reason,
+                        moved_at,
+                        original_message_data,
+                        body,
+                        attributes.unwrap_or_default(),
+                        receive_count,
+                        created_at,
+                    ],
+                )?;
+
+                // Remove original message from messages table
+                conn.execute("DELETE FROM messages WHERE id = ?1", [&message_id])?;
+
+                Ok(Some((queue_name, dlq_queue_name.to_string())))
+            } else {
+                // No DLQ configured for this queue
+                Ok(None)
+            }
+        } else {
+            // No queue configuration found
+            Ok(None)
+        }
+    } else {
+        // Message not found or already deleted
+        Ok(None)
+    }
+}
+
+// Existing queues whose redrive policy targets the given DLQ, by name or ARN
+fn dlq_source_queues(conn: &rusqlite::Connection, dlq_name: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT c.name, c.dead_letter_target_arn FROM queue_config c
+         JOIN queues q ON q.name = c.name
+         WHERE c.dead_letter_target_arn IS NOT NULL ORDER BY c.name",
+    )?;
+
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+
+    let mut source_queues = Vec::new();
+    for row in rows {
+        let (name, dlq_arn) = row?;
+        if dlq_name_from_arn(&dlq_arn) == dlq_name {
+            source_queues.push(name);
+        }
+    }
+    Ok(source_queues)
+}
+
+// ORDER BY clause for picking the next messages to receive. FIFO queues follow
+// sequence_number; standard queues go oldest first unless strict ordering is off.
+fn receive_order(is_fifo: bool, strict_standard_order: bool) -> &'static str {
+    if is_fifo {
+        "sequence_number ASC"
+    } else if strict_standard_order {
+        "created_at ASC"
+    } else {
+        "random()"
+    }
+}
+
+// Receives call this with a message's receive count after the current delivery. Past the
+// queue's max receive count, when it has a DLQ, the message is moved there instead of
+// being delivered; returns what was moved.
+fn dead_letter_if_exhausted(
+    conn: &rusqlite::Connection,
+    message_id: &str,
+    new_receive_count: i64,
+    max_receive_count: Option<i32>,
+    dlq_arn: Option<&str>,
+    moved_at: &str,
+) -> rusqlite::Result<Option<DeadLettered>> {
+    let (Some(max_receive_count), Some(_)) = (max_receive_count, dlq_arn) else {
+        return Ok(None);
+    };
+    if new_receive_count <= i64::from(max_receive_count) {
+        return Ok(None);
+    }
+
+    let reason = format!(
+        "Message exceeded max receive count of {}",
+        max_receive_count
+    );
+    conn.execute(
+        "UPDATE messages SET receive_count = ?2 WHERE id = ?1",
+        rusqlite::params![message_id, new_receive_count],
+    )?;
+    Ok(
+        move_to_dlq(conn, message_id, &reason, moved_at)?.map(|(queue_name, dlq_name)| {
+            DeadLettered {
+                message_id: message_id.to_string(),
+                queue_name,
+                dlq_name,
+                reason,
+            }
+        }),
+    )
+}
+
+// The earliest message of queue ?1 with deduplication id ?2 still inside the queue's
+// deduplication window (5 minutes for queues without config)
+const DUPLICATE_MESSAGE_QUERY: &str = r#"
+    SELECT id FROM messages
+    WHERE queue_name = ?1 AND deduplication_id = ?2
+    AND julianday(created_at) > julianday('now') - COALESCE(
+        (SELECT deduplication_window_seconds FROM queue_config WHERE name = ?1), 300
+    ) / 86400.0
+    ORDER BY created_at ASC LIMIT 1
+"#;
+
+// Receive filter that holds back a FIFO message while another message of its group is
+// still in flight, so each group is delivered in order. In-flight messages whose ids are
+// in the JSON array bound to `delivered_param` don't count. Empty for standard queues.
+fn fifo_group_filter(is_fifo: bool, delivered_param: &str) -> String {
+    if !is_fifo {
+        return String::new();
+    }
+    format!(
+        r#"AND NOT EXISTS (
+                        SELECT 1 FROM messages inflight
+                        WHERE inflight.queue_name = messages.queue_name
+                        AND COALESCE(inflight.message_group_id, '') = COALESCE(messages.message_group_id, '')
+                        AND inflight.status = 'processing'
+                        AND julianday(inflight.visibility_timeout) > julianday('now')
+                        AND inflight.id NOT IN (SELECT value FROM json_each({}))
+                    )"#,
+        delivered_param
+    )
+}
+
+// Record how long a message waited before its first receive, based on its created_at
+fn observe_first_receive(metrics: &Option<Arc<Metrics>>, created_at: &str) {
+    if let Some(metrics) = metrics
+        && let Ok(created_at) = chrono::DateTime::parse_from_rfc3339(created_at)
+    {
+        let waited = Utc::now().signed_duration_since(created_at);
+        metrics.observe_time_to_first_receive(waited.num_microseconds().unwrap_or(0) as f64 / 1e6);
+    }
+}
+
+// Deduplication id for FIFO queues with ContentBasedDeduplication: the hex SHA-256 of
+// the body, as AWS computes it
+pub fn content_dedup_id(body: &str) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(body.as_bytes()))
+}
+
+// (is_fifo, content_based_deduplication) for a queue; a queue without config is standard
+fn fifo_settings(conn: &rusqlite::Connection, queue_name: &str) -> rusqlite::Result<(bool, bool)> {
+    Ok(conn
+        .query_row(
+            "SELECT is_fifo, content_based_deduplication FROM queue_config WHERE name = ?1",
+            [queue_name],
+            |row| Ok((row.get::<_, i32>(0)? != 0, row.get::<_, i32>(1)? != 0)),
+        )
+        .optional()?
+        .unwrap_or((false, false)))
+}
+
+// Copy all pages in one step (-1), so the copy can't straddle a concurrent write. Busy and
+// Locked are transient, so wait briefly and try again.
+fn run_backup(backup: &rusqlite::backup::Backup) -> rusqlite::Result<()> {
+    loop {
+        match backup.step(-1)? {
+            rusqlite::backup::StepResult::Done => return Ok(()),
+            rusqlite::backup::StepResult::More => {}
+            _ => std::thread::sleep(std::time::Duration::from_millis(50)),
+        }
+    }
+}
+
+// Earlier-sequence messages in the same group that haven't been deleted yet. Always 0
+// unless the message's queue has ack_watermark enabled.
+fn unacked_predecessors(conn: &rusqlite::Connection, message_id: &str) -> rusqlite::Result<u32> {
+    conn.query_row(
+        r#"
+        SELECT COUNT(*) FROM messages m
+        JOIN messages t ON t.id = ?1
+        JOIN queue_config q ON q.name = t.queue_name
+        WHERE q.ack_watermark = 1
+          AND t.sequence_number IS NOT NULL
+          AND m.queue_name = t.queue_name
+          AND COALESCE(m.message_group_id, '') = COALESCE(t.message_group_id, '')
+          AND m.sequence_number < t.sequence_number
+          AND m.status != 'deleted'
+        "#,
+        [message_id],
+        |row| row.get(0),
+    )
+}
+
+// Token for the receipt handle of a new delivery
+fn new_receipt_token() -> String {
+    format!("{:016x}", fastrand::u64(..))
+}
+
+// The handle a receive hands out; sequence_number is only Some for FIFO messages
+fn issue_receipt_handle(
+    message_id: &str,
+    token: &str,
+    sequence_number: Option<i64>,
+    include_sequence_number: bool,
+) -> String {
+    match sequence_number {
+        Some(sequence_number) if include_sequence_number => {
+            crate::message::receipt_handle_with_sequence(message_id, token, sequence_number)
+        }
+        _ => crate::message::receipt_handle(message_id, token),
+    }
+}
+
+enum ReceiptHandleStatus {
+    Current(String),
+    // The message has been received again since this handle was issued
+    Stale,
+    Unknown,
+}
+
+// Resolve a receipt handle to its message id. Bare message ids are accepted as the
+// message's current handle.
+fn resolve_receipt_handle(
+    conn: &rusqlite::Connection,
+    receipt_handle: &str,
+) -> rusqlite::Result<ReceiptHandleStatus> {
+    let message_id = crate::message::receipt_message_id(receipt_handle);
+    let Some(receipt_token) = conn
+        .query_row(
+            "SELECT receipt_token FROM messages WHERE id = ?1",
+            [message_id],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()?
+    else {
+        return Ok(ReceiptHandleStatus::Unknown);
+    };
+
+    Ok(match crate::message::receipt_token(receipt_handle) {
+        Some(token) if receipt_token.as_deref() != Some(token) => ReceiptHandleStatus::Stale,
+        _ => ReceiptHandleStatus::Current(message_id.to_string()),
+    })
+}
+
+fn delete_in_order(
+    conn: &rusqlite::Connection,
+    receipt_handle: &str,
+    deleted_at: &str,
+) -> rusqlite::Result<DeleteOutcome> {
+    let message_id = match resolve_receipt_handle(conn, receipt_handle)? {
+        ReceiptHandleStatus::Current(message_id) => message_id,
+        ReceiptHandleStatus::Stale => return Ok(DeleteOutcome::StaleHandle),
+        ReceiptHandleStatus::Unknown => return Ok(DeleteOutcome::NotFound),
+    };
+
+    let pending = unacked_predecessors(conn, &message_id)?;
+    if pending > 0 {
+        return Ok(DeleteOutcome::OutOfOrder { pending });
+    }
+
+    let changes = conn.execute(
+        "UPDATE messages SET status = 'deleted', deleted_at = ?2 WHERE id = ?1",
+        [message_id.as_str(), deleted_at],
+    )?;
+    Ok(if changes > 0 {
+        DeleteOutcome::Deleted
+    } else {
+        DeleteOutcome::NotFound
+    })
+}
+
+// Deadline for a delivery's visibility timeout, randomly moved earlier or later by up to
+// jitter_seconds (never before now) when visibility chaos is enabled
+fn visibility_deadline(timeout_seconds: i64, jitter_seconds: u32) -> String {
+    let jitter = jitter_seconds as i64;
+    let offset = if jitter > 0 {
+        fastrand::i64(-jitter..=jitter)
+    } else {
+        0
+    };
+    (Utc::now() + chrono::Duration::seconds((timeout_seconds + offset).max(0))).to_rfc3339()
 }
 
 impl Database {
     pub async fn new(db_path: &str) -> Result<Self> {
-        let connection = Connection::open(db_path).await?;
+        if rusqlite::version_number() < MIN_SQLITE_VERSION {
+            warn!(
+                "SQLite {} is older than 3.30.0; some operations will fail. Build with the bundled-sqlite feature or upgrade the system library",
+                rusqlite::version()
+            );
+        }
 
-        let db = Database { connection };
+        let connection = Connection::open(db_path).await?;
+        let checkpoint_connection = Connection::open(db_path).await?;
+
+        let db = Database {
+            connection,
+            checkpoint_connection,
+            path: db_path.to_string(),
+            schema_ready: Arc::new(AtomicBool::new(false)),
+            visibility_timeout_seconds: 30,
+            visibility_jitter_seconds: 0,
+            strict_standard_order: true,
+            sequence_in_receipt_handles: false,
+            metrics: None,
+        };
         db.init_performance_settings().await?;
         db.init_schema().await?;
         db.create_performance_indexes().await?;
+        db.schema_ready.store(true, Ordering::Release);
 
         Ok(db)
     }
 
+    pub fn schema_ready(&self) -> bool {
+        self.schema_ready.load(Ordering::Acquire)
+    }
+
+    pub fn with_visibility_timeout(mut self, timeout_seconds: u32) -> Self {
+        self.visibility_timeout_seconds = timeout_seconds;
+        self
+    }
+
+    pub fn with_visibility_jitter(mut self, jitter_seconds: u32) -> Self {
+        self.visibility_jitter_seconds = jitter_seconds;
+        self
+    }
+
+    pub fn with_strict_standard_order(mut self, strict: bool) -> Self {
+        self.strict_standard_order = strict;
+        self
+    }
+
+    pub fn with_sequence_in_receipt_handles(mut self, enabled: bool) -> Self {
+        self.sequence_in_receipt_handles = enabled;
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    // Copy the database to a new file with SQLite's online backup API, which yields a
+    // consistent snapshot even while WAL-mode writes continue. Returns the backup size.
+    pub async fn backup_to(&self, path: &str) -> Result<u64> {
+        let path = path.to_string();
+
+        self.connection
+            .call(move |conn| {
+                let mut destination = rusqlite::Connection::open(&path)?;
+                {
+                    let backup = rusqlite::backup::Backup::new(conn, &mut destination)?;
+                    run_backup(&backup)?;
+                }
+                drop(destination);
+
+                let size = std::fs::metadata(&path)
+                    .map_err(|e| tokio_rusqlite::Error::Other(Box::new(e)))?
+                    .len();
+                Ok(size)
+            })
+            .await
+    }
+
+    // Run PRAGMA wal_checkpoint(TRUNCATE), copying the WAL back into the database and
+    // truncating it to zero bytes. `busy` means readers or writers kept it from completing.
+    pub async fn wal_checkpoint(&self) -> Result<WalCheckpoint> {
+        self.checkpoint_connection
+            .call(|conn| {
+                conn.pragma_update(None, "busy_timeout", 5000)?;
+                let checkpoint = conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| {
+                    Ok(WalCheckpoint {
+                        busy: row.get::<_, i64>(0)? != 0,
+                        log_frames: row.get(1)?,
+                        checkpointed_frames: row.get(2)?,
+                    })
+                })?;
+                Ok(checkpoint)
+            })
+            .await
+    }
+
+    // Size of the -wal file; 0 when it has been truncated or doesn't exist
+    pub fn wal_size_bytes(&self) -> u64 {
+        std::fs::metadata(format!("{}-wal", self.path))
+            .map(|metadata| metadata.len())
+            .unwrap_or(0)
+    }
+
+    // Main database file size plus its page and freelist counts; free pages are space a
+    // VACUUM would give back
+    pub async fn storage_stats(&self) -> Result<StorageStats> {
+        let size_bytes = std::fs::metadata(&self.path)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+
+        self.connection
+            .call(move |conn| {
+                let page_count = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+                let freelist_count =
+                    conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+                Ok(StorageStats {
+                    size_bytes,
+                    page_count,
+                    freelist_count,
+                })
+            })
+            .await
+    }
+
+    // Seed a new database file from a backup. Refuses to touch an existing database
+    // (returns false) and rejects anything that isn't an intact SQLite file.
+    pub fn restore_from_backup(backup_path: &str, db_path: &str) -> rusqlite::Result<bool> {
+        if std::path::Path::new(db_path).exists() {
+            return Ok(false);
+        }
+
+        let source = rusqlite::Connection::open_with_flags(
+            backup_path,
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+        )?;
+        // Fails with "file is not a database" for non-SQLite files
+        let check: String = source.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+        if check != "ok" {
+            return Err(rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CORRUPT),
+                Some(format!("Backup failed integrity check: {}", check)),
+            ));
+        }
+
+        // Copy into a temporary file and move it into place, so a failed copy doesn't leave
+        // a partial database that later startups would take for an existing one
+        let temp_path = format!("{}.restoring", db_path);
+        let _ = std::fs::remove_file(&temp_path);
+        let copied = (|| {
+            let mut destination = rusqlite::Connection::open(&temp_path)?;
+            let backup = rusqlite::backup::Backup::new(&source, &mut destination)?;
+            run_backup(&backup)
+        })()
+        .and_then(|()| {
+            std::fs::rename(&temp_path, db_path).map_err(|e| {
+                rusqlite::Error::SqliteFailure(
+                    rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CANTOPEN),
+                    Some(format!(
+                        "Failed to move restored database into place: {}",
+                        e
+                    )),
+                )
+            })
+        });
+        if copied.is_err() {
+            let _ = std::fs::remove_file(&temp_path);
+        }
+        copied.map(|()| true)
+    }
+
+    // Apply any pending schema migrations; a no-op once the schema is current
+    #[allow(dead_code)]
+    pub async fn run_migrations(&self) -> Result<usize> {
+        self.connection
+            .call(|conn| Ok(apply_migrations(conn)?))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn schema_version(&self) -> Result<i64> {
+        self.connection
+            .call(|conn| {
+                Ok(conn.query_row(
+                    "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+                    [],
+                    |row| row.get(0),
+                )?)
+            })
+            .await
+    }
+
     async fn init_performance_settings(&self) -> Result<()> {
         info!("Applying database performance optimizations");
 
         self.connection
             .call(|conn| {
-                // Enable WAL mode for better concurrency
-                let _ = conn.prepare("PRAGMA journal_mode=WAL")?.query([])?;
-                info!("Enabled WAL mode for better concurrent access");
+                // Enable WAL mode for better concurrency. PRAGMAs only take effect once
+                // stepped, so read each result rather than just preparing the statement.
+                let journal_mode: String =
+                    conn.query_row("PRAGMA journal_mode=WAL", [], |row| row.get(0))?;
+                info!(
+                    "Enabled {} journal mode for better concurrent access",
+                    journal_mode
+                );
 
                 // Set synchronous to NORMAL for better performance while maintaining crash safety
-                let _ = conn.prepare("PRAGMA synchronous=NORMAL")?.query([])?;
+                conn.pragma_update(None, "synchronous", "NORMAL")?;
 
                 // Increase cache size to 8MB for better performance
-                let _ = conn.prepare("PRAGMA cache_size=-8192")?.query([])?;
+                conn.pragma_update(None, "cache_size", -8192)?;
 
                 // Store temporary tables in memory for speed
-                let _ = conn.prepare("PRAGMA temp_store=MEMORY")?.query([])?;
+                conn.pragma_update(None, "temp_store", "MEMORY")?;
 
                 // Enable memory mapping for better I/O performance (256MB)
-                let _ = conn.prepare("PRAGMA mmap_size=268435456")?.query([])?;
+                conn.query_row("PRAGMA mmap_size=268435456", [], |_| Ok(()))?;
 
                 // Optimize for concurrent access
-                let _ = conn.prepare("PRAGMA busy_timeout=5000")?.query([])?;
+                conn.query_row("PRAGMA busy_timeout=5000", [], |_| Ok(()))?;
 
                 info!("Applied performance settings: WAL mode, 8MB cache, memory mapping");
                 Ok(())
@@ -87,40 +976,14 @@ impl Database {
                         deduplication_id TEXT,
                         status TEXT DEFAULT 'active',
                         processed_at TEXT,
-                        deleted_at TEXT
+                        deleted_at TEXT,
+                        receipt_token TEXT,
+                        first_received_at TEXT
                     )
                     "#,
                     [],
                 )?;
 
-                // Add status column to existing tables if it doesn't exist
-                let _ = conn.execute(
-                    "ALTER TABLE messages ADD COLUMN status TEXT DEFAULT 'active'",
-                    [],
-                );
-                let _ = conn.execute(
-                    "ALTER TABLE messages ADD COLUMN processed_at TEXT",
-                    [],
-                );
-                let _ = conn.execute(
-                    "ALTER TABLE messages ADD COLUMN deleted_at TEXT",
-                    [],
-                );
-
-                // Add DelaySeconds support columns
-                let _ = conn.execute(
-                    "ALTER TABLE messages ADD COLUMN delay_until TEXT",
-                    [],
-                );
-                let _ = conn.execute(
-                    "ALTER TABLE messages ADD COLUMN message_group_id TEXT",
-                    [],
-                );
-                let _ = conn.execute(
-                    "ALTER TABLE messages ADD COLUMN sequence_number INTEGER",
-                    [],
-                );
-
                 // Create queue_config table for SetQueueAttributes support
                 conn.execute(
                     r#"
@@ -133,7 +996,10 @@ impl Database {
                         max_receive_count INTEGER,
                         dead_letter_target_arn TEXT,
                         delay_seconds INTEGER DEFAULT 0,
-                        receive_message_wait_time_seconds INTEGER DEFAULT 0
+                        receive_message_wait_time_seconds INTEGER DEFAULT 0,
+                        count_receives BOOLEAN DEFAULT TRUE,
+                        ack_watermark BOOLEAN DEFAULT FALSE,
+                        deduplication_window_seconds INTEGER DEFAULT 300
                     )
                     "#,
                     [],
@@ -164,6 +1030,37 @@ impl Database {
                     [],
                 )?;
 
+                // Cost-allocation style key/value tags per queue (TagQueue/ListQueueTags)
+                conn.execute(
+                    r#"
+                    CREATE TABLE IF NOT EXISTS queue_tags (
+                        queue_name TEXT NOT NULL,
+                        tag_key TEXT NOT NULL,
+                        tag_value TEXT NOT NULL,
+                        PRIMARY KEY (queue_name, tag_key)
+                    )
+                    "#,
+                    [],
+                )?;
+
+                // One row per receive, written only when logging.delivery_log is enabled
+                conn.execute(
+                    r#"
+                    CREATE TABLE IF NOT EXISTS message_delivery_log (
+                        id INTEGER PRIMARY KEY AUTOINCREMENT,
+                        message_id TEXT NOT NULL,
+                        delivered_at TEXT NOT NULL,
+                        receipt_handle TEXT NOT NULL,
+                        consumer_id TEXT
+                    )
+                    "#,
+                    [],
+                )?;
+                conn.execute(
+                    "CREATE INDEX IF NOT EXISTS idx_delivery_log_message ON message_delivery_log(message_id)",
+                    [],
+                )?;
+
                 // Create dead_letter_messages table for DLQ support
                 conn.execute(
                     r#"
@@ -194,11 +1091,63 @@ impl Database {
                     [],
                 )?;
 
-                // Add receive_count column to messages table for DLQ functionality
-                let _ = conn.execute(
-                    "ALTER TABLE messages ADD COLUMN receive_count INTEGER DEFAULT 0",
+                // Bring older databases up to the current column layout
+                apply_migrations(conn)?;
+
+                // Maintained per-queue/per-status message counters, kept in sync by triggers
+                let counters_exist: bool = conn.query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'queue_counters'",
                     [],
-                );
+                    |row| row.get::<_, i64>(0),
+                )? > 0;
+
+                conn.execute(
+                    r#"
+                    CREATE TABLE IF NOT EXISTS queue_counters (
+                        queue_name TEXT NOT NULL,
+                        status TEXT NOT NULL,
+                        count INTEGER NOT NULL DEFAULT 0,
+                        PRIMARY KEY (queue_name, status)
+                    )
+                    "#,
+                    [],
+                )?;
+
+                // Seed counters from existing rows the first time the table is created
+                if !counters_exist {
+                    conn.execute(
+                        "INSERT INTO queue_counters (queue_name, status, count) SELECT queue_name, COALESCE(status, 'active'), COUNT(*) FROM messages GROUP BY queue_name, COALESCE(status, 'active')",
+                        [],
+                    )?;
+                }
+
+                conn.execute_batch(
+                    r#"
+                    CREATE TRIGGER IF NOT EXISTS trg_queue_counters_insert AFTER INSERT ON messages
+                    BEGIN
+                        INSERT INTO queue_counters (queue_name, status, count)
+                        VALUES (NEW.queue_name, COALESCE(NEW.status, 'active'), 1)
+                        ON CONFLICT(queue_name, status) DO UPDATE SET count = count + 1;
+                    END;
+
+                    CREATE TRIGGER IF NOT EXISTS trg_queue_counters_delete AFTER DELETE ON messages
+                    BEGIN
+                        UPDATE queue_counters SET count = count - 1
+                        WHERE queue_name = OLD.queue_name AND status = COALESCE(OLD.status, 'active');
+                    END;
+
+                    CREATE TRIGGER IF NOT EXISTS trg_queue_counters_update AFTER UPDATE OF status, queue_name ON messages
+                    WHEN COALESCE(OLD.status, 'active') != COALESCE(NEW.status, 'active')
+                        OR OLD.queue_name != NEW.queue_name
+                    BEGIN
+                        UPDATE queue_counters SET count = count - 1
+                        WHERE queue_name = OLD.queue_name AND status = COALESCE(OLD.status, 'active');
+                        INSERT INTO queue_counters (queue_name, status, count)
+                        VALUES (NEW.queue_name, COALESCE(NEW.status, 'active'), 1)
+                        ON CONFLICT(queue_name, status) DO UPDATE SET count = count + 1;
+                    END;
+                    "#,
+                )?;
 
                 Ok(())
             })
@@ -266,219 +1215,474 @@ impl Database {
 
         self.connection
             .call(move |conn| {
-                conn.execute(
+                let created = conn.execute(
                     "INSERT OR IGNORE INTO queues (name, created_at) VALUES (?1, ?2)",
                     [&queue_name, &created_at],
                 )?;
+                // Older versions left the configuration of deleted queues behind
+                if created > 0 {
+                    conn.execute("DELETE FROM queue_config WHERE name = ?1", [&queue_name])?;
+                }
                 Ok(())
             })
             .await
     }
 
-    pub async fn delete_queue(&self, queue_name: &str) -> Result<bool> {
+    // A queue other queues dead-letter into is only deleted with `force`, which also removes
+    // the redrive policy from those queues. The dead-lettered messages it holds go with it.
+    pub async fn delete_queue(&self, queue_name: &str, force: bool) -> Result<QueueDeletion> {
         let queue_name = queue_name.to_string();
 
         self.connection
             .call(move |conn| {
+                let tx = conn.unchecked_transaction()?;
+
+                let exists = tx
+                    .query_row(
+                        "SELECT 1 FROM queues WHERE name = ?1",
+                        [&queue_name],
+                        |_| Ok(()),
+                    )
+                    .optional()?
+                    .is_some();
+                if !exists {
+                    return Ok(QueueDeletion::NotFound);
+                }
+
+                let source_queues: Vec<String> = dlq_source_queues(&tx, &queue_name)?
+                    .into_iter()
+                    .filter(|source| *source != queue_name)
+                    .collect();
+                if !source_queues.is_empty() && !force {
+                    return Ok(QueueDeletion::DeadLetterTargetInUse { source_queues });
+                }
+                for source in &source_queues {
+                    tx.execute(
+                        "UPDATE queue_config SET dead_letter_target_arn = NULL, max_receive_count = NULL WHERE name = ?1",
+                        [source],
+                    )?;
+                }
+
                 // First delete all messages in the queue
-                conn.execute("DELETE FROM messages WHERE queue_name = ?1", [&queue_name])?;
+                tx.execute("DELETE FROM messages WHERE queue_name = ?1", [&queue_name])?;
+                tx.execute(
+                    "DELETE FROM dead_letter_messages WHERE dlq_name = ?1",
+                    [&queue_name],
+                )?;
+
+                tx.execute(
+                    "DELETE FROM queue_tags WHERE queue_name = ?1",
+                    [&queue_name],
+                )?;
+                tx.execute("DELETE FROM queue_config WHERE name = ?1", [&queue_name])?;
+
+                // Drop the queue's attribute indexes. Names alone are ambiguous ("a" and
+                // "a__b" share a prefix), so also match the partial index's queue condition.
+                let index_names: Vec<String> = tx
+                    .prepare(
+                        "SELECT name FROM sqlite_master WHERE type = 'index' AND tbl_name = 'messages'
+                         AND substr(name, 1, length(?1)) = ?1 AND substr(sql, -length(?2)) = ?2",
+                    )?
+                    .query_map(
+                        [
+                            format!("idx_attr__{}__", queue_name),
+                            format!(" WHERE queue_name = {}", sql_string_literal(&queue_name)),
+                        ],
+                        |row| row.get(0),
+                    )?
+                    .collect::<rusqlite::Result<_>>()?;
+                for index_name in &index_names {
+                    tx.execute(
+                        &format!("DROP INDEX \"{}\"", index_name.replace('"', "\"\"")),
+                        [],
+                    )?;
+                }
 
                 // Then delete the queue itself
-                let changes = conn.execute("DELETE FROM queues WHERE name = ?1", [&queue_name])?;
+                tx.execute("DELETE FROM queues WHERE name = ?1", [&queue_name])?;
+                tx.commit()?;
+
+                Ok(QueueDeletion::Deleted)
+            })
+            .await
+    }
+
+    // Delete every message in a queue. Returns None if the queue doesn't exist.
+    pub async fn purge_queue(&self, queue_name: &str) -> Result<Option<u32>> {
+        let queue_name = queue_name.to_string();
+
+        self.connection
+            .call(move |conn| {
+                let exists = conn
+                    .query_row(
+                        "SELECT 1 FROM queues WHERE name = ?1",
+                        [&queue_name],
+                        |_| Ok(()),
+                    )
+                    .optional()?
+                    .is_some();
+                if !exists {
+                    return Ok(None);
+                }
+
+                let deleted =
+                    conn.execute("DELETE FROM messages WHERE queue_name = ?1", [&queue_name])?;
+                Ok(Some(deleted as u32))
+            })
+            .await
+    }
+
+    // Find a message with the same deduplication id sent within the queue's deduplication window
+    async fn find_duplicate(&self, queue_name: &str, dedup_id: &str) -> Result<Option<String>> {
+        let queue_name = queue_name.to_string();
+        let dedup_id = dedup_id.to_string();
+
+        self.connection
+            .call(move |conn| {
+                let original_id = conn
+                    .query_row(DUPLICATE_MESSAGE_QUERY, [&queue_name, &dedup_id], |row| {
+                        row.get::<_, String>(0)
+                    })
+                    .optional()?;
+                Ok(original_id)
+            })
+            .await
+    }
+
+    // Receives the next eligible message. Messages in delivered_ids were already handed out
+    // by the same request, so they don't hold back the rest of their FIFO group and one
+    // receive can return several messages of a group in order.
+    // Also returns the messages that were dead-lettered along the way: a message whose
+    // receive count would pass the queue's maxReceiveCount is moved to the DLQ and the
+    // next eligible message is tried instead
+    pub async fn receive_next_message(
+        &self,
+        queue_name: &str,
+        delivered_ids: &[String],
+    ) -> Result<(Option<ReceivedRow>, Vec<DeadLettered>)> {
+        let queue_name = queue_name.to_string();
+        let delivered_ids =
+            serde_json::to_string(delivered_ids).unwrap_or_else(|_| "[]".to_string());
+        let processed_at = Utc::now().to_rfc3339();
+        let timeout_seconds = self.visibility_timeout_seconds as i64;
+        let jitter_seconds = self.visibility_jitter_seconds;
+        let strict_standard_order = self.strict_standard_order;
+        let sequence_in_receipt_handles = self.sequence_in_receipt_handles;
+        let metrics = self.metrics.clone();
+
+        self.connection
+            .call(move |conn| {
+                let tx = conn.unchecked_transaction()?;
+
+                // Check if this is a FIFO queue to determine ordering, whether the queue
+                // overrides the default visibility timeout, and its redrive policy
+                let queue_config_result = tx.prepare(
+                    "SELECT is_fifo, count_receives, visibility_timeout_seconds, max_receive_count, dead_letter_target_arn FROM queue_config WHERE name = ?1"
+                )?.query_row([&queue_name], |row| {
+                    Ok((
+                        row.get::<_, i32>(0)? != 0,
+                        row.get::<_, Option<i32>>(1)?.unwrap_or(1) != 0,
+                        row.get::<_, Option<i64>>(2)?,
+                        row.get::<_, Option<i32>>(3)?,
+                        row.get::<_, Option<String>>(4)?,
+                    ))
+                }).optional()?;
+
+                let (is_fifo, count_receives, queue_timeout_seconds, max_receive_count, dlq_arn) =
+                    queue_config_result.unwrap_or((false, true, None, None, None));
+                let timeout_seconds = queue_timeout_seconds.unwrap_or(timeout_seconds);
+
+                let mut stmt = tx.prepare(&format!(
+                    r#"
+                    SELECT id, body, created_at, attributes, processed_at IS NULL, first_received_at, receive_count, sequence_number
+                    FROM messages
+                    WHERE queue_name = ?1
+                    AND (status = 'active' OR (status = 'processing' AND visibility_timeout IS NOT NULL))
+                    AND (visibility_timeout IS NULL OR julianday(visibility_timeout) <= julianday('now'))
+                    AND (delay_until IS NULL OR julianday(delay_until) <= julianday('now'))
+                    {}
+                    ORDER BY {}
+                    LIMIT 1
+                    "#,
+                    fifo_group_filter(is_fifo, "?2"),
+                    receive_order(is_fifo, strict_standard_order)
+                ))?;
+
+                // Only FIFO queries reference the delivered ids
+                let query_params = if is_fifo {
+                    vec![&queue_name, &delivered_ids]
+                } else {
+                    vec![&queue_name]
+                };
+
+                let mut dead_lettered = Vec::new();
+                let received = loop {
+                    let row = stmt
+                        .query_row(rusqlite::params_from_iter(&query_params), |row| {
+                            Ok((
+                                row.get::<_, String>(0)?,
+                                row.get::<_, String>(1)?,
+                                row.get::<_, String>(2)?,
+                                row.get::<_, Option<String>>(3)?,
+                                row.get::<_, bool>(4)?,
+                                row.get::<_, Option<String>>(5)?,
+                                row.get::<_, Option<i32>>(6)?.unwrap_or(0),
+                                row.get::<_, Option<i64>>(7)?,
+                            ))
+                        })
+                        .optional()?;
+                    let Some((id, body, created_at, attributes, first_receive, first_received_at, current_receive_count, sequence_number)) = row else {
+                        break None;
+                    };
+
+                    // Queues with count_receives disabled never dead-letter from redelivery
+                    let new_receive_count = if count_receives {
+                        current_receive_count + 1
+                    } else {
+                        current_receive_count
+                    };
+
+                    // Move to the DLQ instead of delivering, then try the next message
+                    if let Some(moved) = dead_letter_if_exhausted(
+                        &tx,
+                        &id,
+                        i64::from(new_receive_count),
+                        max_receive_count,
+                        dlq_arn.as_deref(),
+                        &processed_at,
+                    )? {
+                        dead_lettered.push(moved);
+                        continue;
+                    }
+
+                    // Set visibility timeout and increment receive count
+                    let timeout = visibility_deadline(timeout_seconds, jitter_seconds);
+                    let receipt_token = new_receipt_token();
+                    tx.execute(
+                        "UPDATE messages SET visibility_timeout = ?1, receive_count = ?2, status = 'processing', processed_at = ?3, receipt_token = ?5, first_received_at = COALESCE(first_received_at, ?3) WHERE id = ?4",
+                        [&timeout, &new_receive_count.to_string(), &processed_at, &id, &receipt_token],
+                    )?;
+
+                    let receipt_handle = issue_receipt_handle(
+                        &id,
+                        &receipt_token,
+                        sequence_number,
+                        sequence_in_receipt_handles,
+                    );
+                    let first_received_at = first_received_at.unwrap_or_else(|| processed_at.clone());
+                    break Some((
+                        (
+                            id,
+                            body,
+                            created_at,
+                            attributes,
+                            receipt_handle,
+                            new_receive_count.max(0) as u32,
+                            first_received_at,
+                            timeout,
+                        ),
+                        first_receive,
+                    ));
+                };
+
+                drop(stmt);
+                tx.commit()?;
+                let received = received.map(|(message, first_receive)| {
+                    if first_receive {
+                        observe_first_receive(&metrics, &message.2);
+                    }
+                    message
+                });
+                Ok((received, dead_lettered))
+            })
+            .await
+    }
+
+    // Hide an in-flight message for `timeout_seconds` from now, or make it visible again at 0.
+    // The new deadline may not be more than `ceiling_seconds` after the message was received.
+    // A handle for a message of another queue is treated as unknown
+    pub async fn change_message_visibility(
+        &self,
+        queue_name: &str,
+        receipt_handle: &str,
+        timeout_seconds: u32,
+        ceiling_seconds: u32,
+    ) -> Result<VisibilityChange> {
+        let queue_name = queue_name.to_string();
+        let receipt_handle = receipt_handle.to_string();
+
+        self.connection
+            .call(move |conn| {
+                let Some((status, processed_at)) = conn
+                    .query_row(
+                        "SELECT status, processed_at FROM messages WHERE id = ?1 AND queue_name = ?2",
+                        [crate::message::receipt_message_id(&receipt_handle), &queue_name],
+                        |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)),
+                    )
+                    .optional()?
+                else {
+                    return Ok(VisibilityChange::NotFound);
+                };
+
+                let message_id = match resolve_receipt_handle(conn, &receipt_handle)? {
+                    ReceiptHandleStatus::Current(message_id) => message_id,
+                    ReceiptHandleStatus::Stale => return Ok(VisibilityChange::StaleHandle),
+                    ReceiptHandleStatus::Unknown => return Ok(VisibilityChange::NotFound),
+                };
+
+                if status != "processing" {
+                    return Ok(VisibilityChange::NotInFlight);
+                }
+
+                if timeout_seconds == 0 {
+                    conn.execute(
+                        "UPDATE messages SET status = 'active', visibility_timeout = NULL WHERE id = ?1",
+                        [&message_id],
+                    )?;
+                    return Ok(VisibilityChange::Changed);
+                }
+
+                let now = Utc::now();
+                let received_at = processed_at
+                    .and_then(|p| chrono::DateTime::parse_from_rfc3339(&p).ok())
+                    .map(|p| p.with_timezone(&Utc))
+                    .unwrap_or(now);
+                let deadline = now + chrono::Duration::seconds(timeout_seconds as i64);
+                if deadline > received_at + chrono::Duration::seconds(ceiling_seconds as i64) {
+                    return Ok(VisibilityChange::ExceedsCeiling);
+                }
 
-                Ok(changes > 0)
+                conn.execute(
+                    "UPDATE messages SET visibility_timeout = ?2 WHERE id = ?1",
+                    [&message_id, &deadline.to_rfc3339()],
+                )?;
+                Ok(VisibilityChange::Changed)
             })
             .await
     }
 
-    pub async fn send_message(
+    // Append one delivery log row per (message_id, receipt_handle)
+    pub async fn log_deliveries(
         &self,
-        queue_name: &str,
-        message_id: &str,
-        body: &str,
-        attributes: Option<&str>,
-        deduplication_id: Option<&str>,
+        deliveries: Vec<(String, String)>,
+        consumer_id: Option<String>,
     ) -> Result<()> {
-        let queue_name = queue_name.to_string();
-        let message_id = message_id.to_string();
-        let body = body.to_string();
-        let created_at = Utc::now().to_rfc3339();
-        let attributes = attributes.map(|s| s.to_string());
-        let deduplication_id = deduplication_id.map(|s| s.to_string());
-
-        // Check for duplicate deduplication_id within the last 5 minutes
-        if let Some(ref dedup_id) = deduplication_id {
-            let five_minutes_ago = (Utc::now() - chrono::Duration::minutes(5)).to_rfc3339();
-            let queue_name_check = queue_name.clone();
-            let dedup_id_check = dedup_id.clone();
+        let delivered_at = Utc::now().to_rfc3339();
 
-            let duplicate_exists = self.connection
-                .call(move |conn| {
-                    let mut stmt = conn.prepare(
-                        "SELECT COUNT(*) FROM messages WHERE queue_name = ?1 AND deduplication_id = ?2 AND created_at > ?3"
+        self.connection
+            .call(move |conn| {
+                let tx = conn.unchecked_transaction()?;
+                {
+                    let mut stmt = tx.prepare(
+                        "INSERT INTO message_delivery_log (message_id, delivered_at, receipt_handle, consumer_id) VALUES (?1, ?2, ?3, ?4)",
                     )?;
-                    let count: i64 = stmt.query_row([&queue_name_check, &dedup_id_check, &five_minutes_ago], |row| {
-                        row.get(0)
-                    })?;
-                    Ok(count > 0)
-                })
-                .await?;
+                    for (message_id, receipt_handle) in &deliveries {
+                        stmt.execute(rusqlite::params![
+                            message_id,
+                            delivered_at,
+                            receipt_handle,
+                            consumer_id
+                        ])?;
+                    }
+                }
+                tx.commit()?;
+                Ok(())
+            })
+            .await
+    }
 
-            if duplicate_exists {
-                return Ok(()); // Silently ignore duplicate
-            }
-        }
+    pub async fn get_message_deliveries(&self, message_id: &str) -> Result<Vec<DeliveryRecord>> {
+        let message_id = message_id.to_string();
 
         self.connection
             .call(move |conn| {
-                conn.execute(
-                    "INSERT INTO messages (id, queue_name, body, created_at, attributes, deduplication_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                    [
-                        &Some(message_id),
-                        &Some(queue_name),
-                        &Some(body),
-                        &Some(created_at),
-                        &attributes,
-                        &deduplication_id
-                    ],
+                let mut stmt = conn.prepare(
+                    "SELECT delivered_at, receipt_handle, consumer_id FROM message_delivery_log WHERE message_id = ?1 ORDER BY id ASC",
                 )?;
-                Ok(())
+                let deliveries = stmt
+                    .query_map([&message_id], |row| {
+                        Ok(DeliveryRecord {
+                            delivered_at: row.get(0)?,
+                            receipt_handle: row.get(1)?,
+                            consumer_id: row.get(2)?,
+                        })
+                    })?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                Ok(deliveries)
             })
             .await
     }
 
-    pub async fn receive_message(
-        &self,
-        queue_name: &str,
-    ) -> Result<Option<(String, String, String, Option<String>)>> {
-        let queue_name = queue_name.to_string();
-        let processed_at = Utc::now().to_rfc3339();
+    // None when no queue holds a message with this id; deleted messages don't count
+    pub async fn get_message_receive_count(&self, message_id: &str) -> Result<Option<i64>> {
+        let message_id = message_id.to_string();
 
         self.connection
             .call(move |conn| {
-                // Check if this is a FIFO queue to determine ordering
-                let queue_config_result: Option<(bool,)> = conn.prepare(
-                    "SELECT is_fifo FROM queue_config WHERE name = ?1"
-                )?.query_row([&queue_name], |row| {
-                    Ok((row.get::<_, i32>(0)? != 0,))
-                }).optional()?;
-
-                let is_fifo = queue_config_result.map(|(fifo,)| fifo).unwrap_or(false);
+                Ok(conn
+                    .query_row(
+                        "SELECT COALESCE(receive_count, 0) FROM messages WHERE id = ?1 AND status != 'deleted'",
+                        [&message_id],
+                        |row| row.get(0),
+                    )
+                    .optional()?)
+            })
+            .await
+    }
 
-                let mut stmt = if is_fifo {
-                    // For FIFO queues, order by sequence_number for strict FIFO ordering
-                    conn.prepare(
-                        r#"
-                        SELECT id, body, created_at, attributes
-                        FROM messages
-                        WHERE queue_name = ?1
-                        AND status = 'active'
-                        AND (visibility_timeout IS NULL OR visibility_timeout < datetime('now'))
-                        AND (delay_until IS NULL OR delay_until < datetime('now'))
-                        ORDER BY sequence_number ASC
-                        LIMIT 1
-                        "#,
-                    )?
-                } else {
-                    // For standard queues, order by created_at
-                    conn.prepare(
-                        r#"
-                        SELECT id, body, created_at, attributes
-                        FROM messages
-                        WHERE queue_name = ?1
-                        AND status = 'active'
-                        AND (visibility_timeout IS NULL OR visibility_timeout < datetime('now'))
-                        AND (delay_until IS NULL OR delay_until < datetime('now'))
-                        ORDER BY created_at ASC
-                        LIMIT 1
-                        "#,
-                    )?
-                };
+    pub async fn delete_message(&self, receipt_handle: &str) -> Result<DeleteOutcome> {
+        let receipt_handle = receipt_handle.to_string();
+        let deleted_at = Utc::now().to_rfc3339();
 
-                let mut rows = stmt.query_map([&queue_name], |row| {
-                    Ok((
-                        row.get::<_, String>(0)?,
-                        row.get::<_, String>(1)?,
-                        row.get::<_, String>(2)?,
-                        row.get::<_, Option<String>>(3)?,
-                    ))
-                })?;
+        self.connection
+            .call(move |conn| Ok(delete_in_order(conn, &receipt_handle, &deleted_at)?))
+            .await
+    }
 
-                if let Some(row) = rows.next() {
-                    let (id, body, created_at, attributes) = row?;
-
-                    // Get current receive count and queue configuration
-                    let current_receive_count: i32 = conn.prepare(
-                        "SELECT receive_count FROM messages WHERE id = ?1"
-                    )?.query_row([&id], |row| row.get(0))?;
-
-                    // Check for DLQ configuration
-                    let queue_config = conn.prepare(
-                        "SELECT max_receive_count, dead_letter_target_arn FROM queue_config WHERE name = ?1"
-                    )?.query_row([&queue_name], |row| {
-                        Ok((row.get::<_, Option<i32>>(0)?, row.get::<_, Option<String>>(1)?))
-                    }).optional()?;
-
-                    let new_receive_count = current_receive_count + 1;
-
-                    // Check if message should be moved to DLQ
-                    if let Some((Some(max_receive_count), Some(_dlq_arn))) = queue_config
-                        && new_receive_count > max_receive_count {
-                            // Move to DLQ instead of returning the message
-                            let _reason = format!("Message exceeded max receive count of {}", max_receive_count);
-
-                            // Get message details for DLQ move
-                            let _message_details = conn.prepare(
-                                "SELECT queue_name, body, created_at, attributes FROM messages WHERE id = ?1"
-                            )?.query_row([&id], |row| {
-                                Ok((
-                                    row.get::<_, String>(0)?,
-                                    row.get::<_, String>(1)?,
-                                    row.get::<_, String>(2)?,
-                                    row.get::<_, Option<String>>(3)?,
-                                ))
-                            })?;
-
-                            // This will be handled by a separate call - for now mark as failed and let DLQ processing handle it
-                            conn.execute(
-                                "UPDATE messages SET status = 'dlq_pending', receive_count = ?2 WHERE id = ?1",
-                                [&id, &new_receive_count.to_string()],
-                            )?;
-
-                            // Return None to indicate message was moved to DLQ processing
-                            return Ok(None);
-                        }
+    // Return an in-flight message to the queue immediately.
+    // Returns the queue name and updated nack count, or None if the message isn't in flight.
+    pub async fn nack_message(&self, message_id: &str) -> Result<Option<(String, u32)>> {
+        let message_id = message_id.to_string();
 
-                    // Set visibility timeout (30 seconds from now) and increment receive count
-                    let timeout = (Utc::now() + chrono::Duration::seconds(30)).to_rfc3339();
-                    conn.execute(
-                        "UPDATE messages SET visibility_timeout = ?1, receive_count = ?2, status = 'processing', processed_at = ?3 WHERE id = ?4",
-                        [&timeout, &new_receive_count.to_string(), &processed_at, &id],
-                    )?;
+        self.connection
+            .call(move |conn| {
+                let changes = conn.execute(
+                    "UPDATE messages SET status = 'active', visibility_timeout = NULL, nack_count = COALESCE(nack_count, 0) + 1 WHERE id = ?1 AND status = 'processing'",
+                    [&message_id],
+                )?;
 
-                    Ok(Some((id, body, created_at, attributes)))
-                } else {
-                    Ok(None)
+                if changes == 0 {
+                    return Ok(None);
                 }
+
+                let result = conn.query_row(
+                    "SELECT queue_name, nack_count FROM messages WHERE id = ?1",
+                    [&message_id],
+                    |row| Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?)),
+                )?;
+                Ok(Some(result))
             })
             .await
     }
 
-    pub async fn delete_message(&self, message_id: &str) -> Result<bool> {
+    pub async fn get_message_queue_name(&self, message_id: &str) -> Result<Option<String>> {
         let message_id = message_id.to_string();
-        let deleted_at = Utc::now().to_rfc3339();
 
         self.connection
             .call(move |conn| {
-                let changes = conn.execute(
-                    "UPDATE messages SET status = 'deleted', deleted_at = ?2 WHERE id = ?1",
-                    [&message_id, &deleted_at],
-                )?;
-                Ok(changes > 0)
+                let queue_name = conn
+                    .query_row(
+                        "SELECT queue_name FROM messages WHERE id = ?1",
+                        [&message_id],
+                        |row| row.get::<_, String>(0),
+                    )
+                    .optional()?;
+                Ok(queue_name)
             })
             .await
     }
 
+    #[cfg_attr(not(feature = "ui"), allow(dead_code))]
     pub async fn restore_message(&self, message_id: &str) -> Result<bool> {
         let message_id = message_id.to_string();
 
@@ -510,6 +1714,146 @@ impl Database {
             .await
     }
 
+    // Queues with their tags, sorted by queue then key, in one joined query.
+    // Only queues carrying every (key, value) pair in tag_filter are returned.
+    pub async fn list_queues_with_tags(
+        &self,
+        tag_filter: &[(String, String)],
+    ) -> Result<Vec<QueueWithTags>> {
+        let tag_filter = tag_filter.to_vec();
+
+        self.connection
+            .call(move |conn| {
+                let mut sql = String::from(
+                    "SELECT q.name, q.created_at, t.tag_key, t.tag_value FROM queues q",
+                );
+                let mut params = Vec::with_capacity(tag_filter.len() * 2);
+                for (i, (key, value)) in tag_filter.into_iter().enumerate() {
+                    sql.push_str(&format!(
+                        " JOIN queue_tags f{i} ON f{i}.queue_name = q.name AND f{i}.tag_key = ? AND f{i}.tag_value = ?"
+                    ));
+                    params.push(key);
+                    params.push(value);
+                }
+                sql.push_str(
+                    " LEFT JOIN queue_tags t ON t.queue_name = q.name ORDER BY q.name, t.tag_key",
+                );
+
+                let mut stmt = conn.prepare(&sql)?;
+                let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, Option<String>>(3)?,
+                    ))
+                })?;
+
+                let mut queues: Vec<QueueWithTags> = Vec::new();
+                for row in rows {
+                    let (name, created_at, key, value) = row?;
+                    if queues.last().is_none_or(|(last, _, _)| *last != name) {
+                        queues.push((name, created_at, Vec::new()));
+                    }
+                    if let (Some(key), Some(value), Some((_, _, tags))) =
+                        (key, value, queues.last_mut())
+                    {
+                        tags.push((key, value));
+                    }
+                }
+                Ok(queues)
+            })
+            .await
+    }
+
+    // Add or overwrite tags. Returns false if the queue doesn't exist.
+    pub async fn tag_queue(&self, queue_name: &str, tags: Vec<(String, String)>) -> Result<bool> {
+        let queue_name = queue_name.to_string();
+
+        self.connection
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                let exists = tx
+                    .query_row("SELECT 1 FROM queues WHERE name = ?1", [&queue_name], |_| {
+                        Ok(())
+                    })
+                    .optional()?
+                    .is_some();
+                if !exists {
+                    return Ok(false);
+                }
+
+                for (key, value) in tags {
+                    tx.execute(
+                        "INSERT INTO queue_tags (queue_name, tag_key, tag_value) VALUES (?1, ?2, ?3)
+                         ON CONFLICT(queue_name, tag_key) DO UPDATE SET tag_value = excluded.tag_value",
+                        [&queue_name, &key, &value],
+                    )?;
+                }
+                tx.commit()?;
+                Ok(true)
+            })
+            .await
+    }
+
+    // Remove tags by key. Returns false if the queue doesn't exist.
+    pub async fn untag_queue(&self, queue_name: &str, keys: Vec<String>) -> Result<bool> {
+        let queue_name = queue_name.to_string();
+
+        self.connection
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                let exists = tx
+                    .query_row(
+                        "SELECT 1 FROM queues WHERE name = ?1",
+                        [&queue_name],
+                        |_| Ok(()),
+                    )
+                    .optional()?
+                    .is_some();
+                if !exists {
+                    return Ok(false);
+                }
+
+                for key in keys {
+                    tx.execute(
+                        "DELETE FROM queue_tags WHERE queue_name = ?1 AND tag_key = ?2",
+                        [&queue_name, &key],
+                    )?;
+                }
+                tx.commit()?;
+                Ok(true)
+            })
+            .await
+    }
+
+    // Tags sorted by key, or None if the queue doesn't exist
+    pub async fn list_queue_tags(&self, queue_name: &str) -> Result<Option<Vec<(String, String)>>> {
+        let queue_name = queue_name.to_string();
+
+        self.connection
+            .call(move |conn| {
+                let exists = conn
+                    .query_row("SELECT 1 FROM queues WHERE name = ?1", [&queue_name], |_| {
+                        Ok(())
+                    })
+                    .optional()?
+                    .is_some();
+                if !exists {
+                    return Ok(None);
+                }
+
+                let mut stmt = conn.prepare(
+                    "SELECT tag_key, tag_value FROM queue_tags WHERE queue_name = ?1 ORDER BY tag_key",
+                )?;
+                let tags = stmt
+                    .query_map([&queue_name], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                Ok(Some(tags))
+            })
+            .await
+    }
+
     #[allow(dead_code)]
     pub async fn get_queue_messages(
         &self,
@@ -554,32 +1898,76 @@ impl Database {
             .await
     }
 
-    pub async fn get_all_queue_messages(
+    // Stream every message in a queue, oldest first, with at most EXPORT_CHANNEL_CAPACITY
+    // rows in memory. Rows are read on a separate read-only connection, so a slow consumer
+    // holds up neither the main connection nor other requests.
+    pub fn stream_all_queue_messages(
         &self,
         queue_name: &str,
-    ) -> Result<
-        Vec<(
-            String,
-            String,
-            String,
-            Option<String>,
-            u32,
-            Option<String>,
-            Option<String>,
-            String,
-            Option<String>,
-            Option<String>,
-        )>,
-    > {
+    ) -> tokio::sync::mpsc::Receiver<rusqlite::Result<ExportedMessage>> {
+        let (sender, receiver) = tokio::sync::mpsc::channel(EXPORT_CHANNEL_CAPACITY);
+        let path = self.path.clone();
+        let queue_name = queue_name.to_string();
+
+        tokio::task::spawn_blocking(move || {
+            let result = (|| -> rusqlite::Result<()> {
+                let conn = rusqlite::Connection::open_with_flags(
+                    &path,
+                    rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+                )?;
+                conn.busy_timeout(std::time::Duration::from_secs(5))?;
+                let mut stmt = conn.prepare(
+                    "SELECT id, body, created_at, visibility_timeout, receive_count, attributes, deduplication_id, status, processed_at, deleted_at FROM messages WHERE queue_name = ?1 ORDER BY created_at ASC",
+                )?;
+                let rows = stmt.query_map([&queue_name], |row| {
+                    Ok(ExportedMessage {
+                        id: row.get(0)?,
+                        body: row.get(1)?,
+                        created_at: row.get(2)?,
+                        visibility_timeout: row.get(3)?,
+                        receive_count: row.get(4)?,
+                        attributes: row.get(5)?,
+                        deduplication_id: row.get(6)?,
+                        status: row.get(7)?,
+                        processed_at: row.get(8)?,
+                        deleted_at: row.get(9)?,
+                    })
+                })?;
+
+                for row in rows {
+                    // The receiver is gone, e.g. the client disconnected mid-export
+                    if sender.blocking_send(row).is_err() {
+                        break;
+                    }
+                }
+                Ok(())
+            })();
+
+            if let Err(e) = result {
+                let _ = sender.blocking_send(Err(e));
+            }
+        });
+
+        receiver
+    }
+
+    // Up to limit messages of a queue in any status, oldest first, skipping the first offset
+    #[cfg_attr(not(feature = "ui"), allow(dead_code))]
+    pub async fn get_queue_messages_page(
+        &self,
+        queue_name: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<QueueMessageRow>> {
         let queue_name = queue_name.to_string();
 
         self.connection
             .call(move |conn| {
                 let mut stmt = conn.prepare(
-                    "SELECT id, body, created_at, visibility_timeout, receive_count, attributes, deduplication_id, status, processed_at, deleted_at FROM messages WHERE queue_name = ?1 ORDER BY created_at ASC"
+                    "SELECT id, body, created_at, visibility_timeout, receive_count, attributes, deduplication_id, status, processed_at, deleted_at FROM messages WHERE queue_name = ?1 ORDER BY created_at ASC LIMIT ?2 OFFSET ?3"
                 )?;
 
-                let rows = stmt.query_map([&queue_name], |row| {
+                let rows = stmt.query_map(rusqlite::params![queue_name, limit as i64, offset as i64], |row| {
                     Ok((
                         row.get::<_, String>(0)?,         // id
                         row.get::<_, String>(1)?,         // body
@@ -618,22 +2006,35 @@ impl Database {
                     return Ok(None);
                 }
 
-                // Get message counts - only count active messages
-                let mut stmt = conn.prepare(
-                    "SELECT COUNT(*) FROM messages WHERE queue_name = ?1 AND status = 'active'"
+                // Active messages are visible unless a send delay still hides them
+                let (visible_messages, delayed_messages): (i64, i64) = conn.query_row(
+                    r#"
+                    SELECT
+                        COUNT(*) FILTER (WHERE delay_until IS NULL OR julianday(delay_until) <= julianday('now')),
+                        COUNT(*) FILTER (WHERE julianday(delay_until) > julianday('now'))
+                    FROM messages WHERE queue_name = ?1 AND status = 'active'
+                    "#,
+                    [&queue_name],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
                 )?;
-                let total_active_messages: i64 = stmt.query_row([&queue_name], |row| row.get(0))?;
 
-                let mut stmt = conn.prepare(
-                    "SELECT COUNT(*) FROM messages WHERE queue_name = ?1 AND status = 'active' AND (visibility_timeout IS NULL OR visibility_timeout < datetime('now'))"
+                let in_flight_messages: i64 = conn.query_row(
+                    "SELECT COUNT(*) FROM messages WHERE queue_name = ?1 AND status = 'processing'",
+                    [&queue_name],
+                    |row| row.get(0),
                 )?;
-                let visible_messages: i64 = stmt.query_row([&queue_name], |row| row.get(0))?;
 
-                let in_flight_messages = total_active_messages - visible_messages;
+                let dead_lettered_messages: i64 = conn.query_row(
+                    "SELECT COUNT(*) FROM dead_letter_messages WHERE original_queue_name = ?1",
+                    [&queue_name],
+                    |row| row.get(0),
+                )?;
 
                 Ok(Some(QueueAttributes {
                     approximate_number_of_messages: visible_messages as u32,
                     approximate_number_of_messages_not_visible: in_flight_messages as u32,
+                    approximate_number_of_messages_delayed: delayed_messages as u32,
+                    dead_lettered_messages: dead_lettered_messages as u32,
                     created_timestamp: queue_exists.unwrap(),
                 }))
             })
@@ -658,6 +2059,9 @@ impl Database {
         let delay_seconds = config.delay_seconds as i32;
         let wait_time = config.receive_message_wait_time_seconds as i32;
         let dlq_arn = config.dead_letter_target_arn.clone();
+        let count_receives = config.count_receives;
+        let ack_watermark = config.ack_watermark;
+        let dedup_window = config.deduplication_window_seconds as i64;
 
         self.connection
             .call(move |conn| {
@@ -666,8 +2070,9 @@ impl Database {
                     INSERT OR REPLACE INTO queue_config
                     (name, is_fifo, content_based_deduplication, visibility_timeout_seconds,
                      message_retention_period_seconds, max_receive_count, dead_letter_target_arn,
-                     delay_seconds, receive_message_wait_time_seconds)
-                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                     delay_seconds, receive_message_wait_time_seconds, count_receives,
+                     ack_watermark, deduplication_window_seconds)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
                     "#,
                     rusqlite::params![
                         config_name,
@@ -678,7 +2083,10 @@ impl Database {
                         max_receive_count,
                         dlq_arn,
                         delay_seconds,
-                        wait_time
+                        wait_time,
+                        count_receives as i32,
+                        ack_watermark as i32,
+                        dedup_window
                     ],
                 )?;
                 Ok(())
@@ -691,6 +2099,7 @@ impl Database {
         queue_name: &str,
     ) -> Result<Option<crate::config::QueueConfig>> {
         let queue_name = queue_name.to_string();
+        let default_timeout_seconds = self.visibility_timeout_seconds;
 
         self.connection
             .call(move |conn| {
@@ -698,8 +2107,10 @@ impl Database {
                     r#"
                     SELECT name, is_fifo, content_based_deduplication, visibility_timeout_seconds,
                            message_retention_period_seconds, max_receive_count, dead_letter_target_arn,
-                           delay_seconds, receive_message_wait_time_seconds
-                    FROM queue_config WHERE name = ?1
+                           delay_seconds, receive_message_wait_time_seconds, count_receives,
+                           ack_watermark, deduplication_window_seconds
+                    FROM queue_config
+                    WHERE name = ?1 AND name IN (SELECT name FROM queues)
                     "#,
                 )?;
 
@@ -711,12 +2122,19 @@ impl Database {
                         name: row.get::<_, String>(0)?,
                         is_fifo: row.get::<_, i32>(1)? != 0,
                         content_based_deduplication: row.get::<_, i32>(2)? != 0,
-                        visibility_timeout_seconds: row.get::<_, i32>(3)? as u32,
+                        visibility_timeout_seconds: row
+                            .get::<_, Option<i32>>(3)?
+                            .map_or(default_timeout_seconds, |t| t as u32),
                         message_retention_period_seconds: row.get::<_, i32>(4)? as u32,
                         max_receive_count: max_receive_count.map(|v| v as u32),
                         dead_letter_target_arn,
                         delay_seconds: row.get::<_, i32>(7)? as u32,
                         receive_message_wait_time_seconds: row.get::<_, i32>(8)? as u32,
+                        count_receives: row.get::<_, Option<i32>>(9)?.unwrap_or(1) != 0,
+                        ack_watermark: row.get::<_, Option<i32>>(10)?.unwrap_or(0) != 0,
+                        deduplication_window_seconds: row
+                            .get::<_, Option<i64>>(11)?
+                            .map_or_else(crate::config::default_deduplication_window_seconds, |s| s as u32),
                     })
                 }).optional()?;
 
@@ -725,194 +2143,293 @@ impl Database {
             .await
     }
 
-    #[allow(dead_code)]
+    // Move a message into the dead_letter_messages table.
+    // Returns the source queue and DLQ names, or None if nothing was moved.
     pub async fn move_message_to_dlq(
         &self,
         message_id: &str,
         failure_reason: &str,
-    ) -> Result<bool> {
+    ) -> Result<Option<(String, String)>> {
         let message_id = message_id.to_string();
         let failure_reason = failure_reason.to_string();
         let moved_at = Utc::now().to_rfc3339();
 
+        self.connection
+            .call(move |conn| Ok(move_to_dlq(conn, &message_id, &failure_reason, &moved_at)?))
+            .await
+    }
+
+    pub async fn get_dlq_messages(
+        &self,
+        dlq_name: &str,
+    ) -> Result<Vec<(String, String, String, String, Option<String>)>> {
+        let dlq_name = dlq_name.to_string();
+
+        self.connection
+            .call(move |conn| {
+                let mut stmt = conn.prepare(
+                    r#"
+                    SELECT id, original_body, moved_at, failure_reason, original_attributes
+                    FROM dead_letter_messages
+                    WHERE dlq_name = ?1
+                    ORDER BY moved_at DESC
+                    "#,
+                )?;
+
+                let rows = stmt.query_map([&dlq_name], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,         // id
+                        row.get::<_, String>(1)?,         // original_body
+                        row.get::<_, String>(2)?,         // moved_at
+                        row.get::<_, String>(3)?,         // failure_reason
+                        row.get::<_, Option<String>>(4)?, // original_attributes
+                    ))
+                })?;
+
+                let mut messages = Vec::new();
+                for row in rows {
+                    messages.push(row?);
+                }
+
+                Ok(messages)
+            })
+            .await
+    }
+
+    // Queues whose redrive policy targets the given DLQ
+    // Delete the messages in a queue that match every condition in the filter.
+    // The filter must already be validated. Returns the number of deleted rows.
+    pub async fn purge_filtered(&self, queue_name: &str, filter: &PurgeFilter) -> Result<u32> {
+        // The queue name is inlined so attribute indexes, which are partial on the queue, apply
+        let mut sql = format!(
+            "DELETE FROM messages WHERE queue_name = {}",
+            sql_string_literal(queue_name)
+        );
+        let mut params = Vec::new();
+
+        if let Some(status) = &filter.status {
+            sql.push_str(" AND COALESCE(status, 'active') = ?");
+            params.push(status.clone());
+        }
+
+        if let Some(body_contains) = &filter.body_contains {
+            sql.push_str(" AND instr(body, ?) > 0");
+            params.push(body_contains.clone());
+        }
+
+        if let Some(name) = &filter.attribute_name {
+            match &filter.attribute_value {
+                Some(value) => {
+                    sql.push_str(&format!(" AND {} = ?", attribute_value_expr(name)));
+                    params.push(value.clone());
+                }
+                None => {
+                    sql.push_str(" AND json_type(attributes, ?) IS NOT NULL");
+                    params.push(format!("$.\"{}\"", name));
+                }
+            }
+        }
+
+        self.connection
+            .call(move |conn| {
+                let deleted = conn.execute(&sql, rusqlite::params_from_iter(params))?;
+                Ok(deleted as u32)
+            })
+            .await
+    }
+
+    // Expression index over one attribute's string value, partial on the queue, so
+    // attribute-filtered queries on that queue avoid a full scan. Requires JSON1.
+    pub async fn create_attribute_index(
+        &self,
+        queue_name: &str,
+        attribute_name: &str,
+    ) -> Result<String> {
+        if !is_valid_attribute_name(attribute_name) {
+            return Err(tokio_rusqlite::Error::Rusqlite(
+                rusqlite::Error::InvalidParameterName(attribute_name.to_string()),
+            ));
+        }
+
+        let index_name = format!("idx_attr__{}__{}", queue_name, attribute_name);
+        let sql = format!(
+            "CREATE INDEX IF NOT EXISTS \"{}\" ON messages({}) WHERE queue_name = {}",
+            index_name.replace('"', "\"\""),
+            attribute_value_expr(attribute_name),
+            sql_string_literal(queue_name)
+        );
+
         self.connection
             .call(move |conn| {
-                // First, get the message details and queue configuration
-                let message_result = conn.prepare(
-                    "SELECT queue_name, body, created_at, attributes, receive_count FROM messages WHERE id = ?1 AND status != 'deleted'"
-                )?.query_row([&message_id], |row| {
-                    Ok((
-                        row.get::<_, String>(0)?,  // queue_name
-                        row.get::<_, String>(1)?,  // body
-                        row.get::<_, String>(2)?,  // created_at
-                        row.get::<_, Option<String>>(3)?,  // attributes
-                        row.get::<_, i32>(4)?      // receive_count
-                    ))
-                });
-
-                if let Ok((queue_name, body, created_at, attributes, receive_count)) = message_result {
-                    // Get DLQ configuration from queue_config
-                    if let Some(dlq_arn) = conn.prepare(
-                        "SELECT dead_letter_target_arn FROM queue_config WHERE name = ?1"
-                    )?.query_row([&queue_name], |row| {
-                        row.get::<_, Option<String>>(0)
-                    }).optional()? {
-                        if let Some(dlq_name) = dlq_arn {
-                            // Extract DLQ name from ARN (simplified - assume it's just the queue name for now)
-                            let dlq_queue_name = dlq_name.split('/').next_back().unwrap_or(&dlq_name);
-
-                            // Create JSON representation of original message data
-                            let original_message_data = serde_json::json!({
-                                "messageId": message_id,
-                                "body": body,
-                                "attributes": attributes,
-                                "createdAt": created_at,
-                                "receiveCount": receive_count
-                            }).to_string();
-
-                            // Insert into dead_letter_messages table
-                            conn.execute(
-                                r#"
-                                INSERT INTO dead_letter_messages
-                                (id, original_queue_name, dlq_name, failure_reason, moved_at,
-                                 original_message_data, original_body, original_attributes,
-                                 receive_count, original_created_at)
-                                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
-                                "#,
-                                [
-                                    &message_id,
-                                    &queue_name,
-                                    &dlq_queue_name.to_string(),
-                                    &failure_reason,
-                                    &moved_at,
-                                    &original_message_data,
-                                    &body,
-                                    &attributes.unwrap_or_else(|| "".to_string()),
-                                    &receive_count.to_string(),
-                                    &created_at,
-                                ]
-                            )?;
-
-                            // Remove original message from messages table
-                            conn.execute(
-                                "DELETE FROM messages WHERE id = ?1",
-                                [&message_id]
-                            )?;
-
-                            Ok(true)
-                        } else {
-                            // No DLQ configured for this queue
-                            Ok(false)
-                        }
-                    } else {
-                        // No queue configuration found
-                        Ok(false)
-                    }
-                } else {
-                    // Message not found or already deleted
-                    Ok(false)
-                }
+                conn.execute(&sql, [])?;
+                Ok(index_name)
             })
             .await
     }
 
-    pub async fn get_dlq_messages(
+    // Copy messages that were already received or deleted back into the queue as new
+    // active messages. Originals are left untouched. Returns (original id, new id) pairs.
+    pub async fn replay_messages(
         &self,
-        dlq_name: &str,
-    ) -> Result<Vec<(String, String, String, String, Option<String>)>> {
-        let dlq_name = dlq_name.to_string();
+        queue_name: &str,
+        from: &str,
+        to: &str,
+        id_generator: IdGenerator,
+    ) -> Result<Vec<(String, String)>> {
+        let queue_name = queue_name.to_string();
+        let from = from.to_string();
+        let to = to.to_string();
+        let created_at = Utc::now().to_rfc3339();
 
         self.connection
             .call(move |conn| {
-                let mut stmt = conn.prepare(
-                    r#"
-                    SELECT id, original_body, moved_at, failure_reason, original_attributes
-                    FROM dead_letter_messages
-                    WHERE dlq_name = ?1
-                    ORDER BY moved_at DESC
-                    "#,
-                )?;
+                let tx = conn.unchecked_transaction()?;
 
-                let rows = stmt.query_map([&dlq_name], |row| {
-                    Ok((
-                        row.get::<_, String>(0)?,         // id
-                        row.get::<_, String>(1)?,         // original_body
-                        row.get::<_, String>(2)?,         // moved_at
-                        row.get::<_, String>(3)?,         // failure_reason
-                        row.get::<_, Option<String>>(4)?, // original_attributes
-                    ))
-                })?;
+                let originals = {
+                    let mut stmt = tx.prepare(
+                        r#"
+                        SELECT id, body, attributes, message_group_id
+                        FROM messages
+                        WHERE queue_name = ?1
+                        AND status IN ('processing', 'deleted')
+                        AND julianday(COALESCE(processed_at, created_at)) BETWEEN julianday(?2) AND julianday(?3)
+                        ORDER BY COALESCE(sequence_number, 0), created_at ASC
+                        "#,
+                    )?;
+                    let rows = stmt.query_map([&queue_name, &from, &to], |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, String>(1)?,
+                            row.get::<_, Option<String>>(2)?,
+                            row.get::<_, Option<String>>(3)?,
+                        ))
+                    })?;
+                    rows.collect::<std::result::Result<Vec<_>, _>>()?
+                };
 
-                let mut messages = Vec::new();
-                for row in rows {
-                    messages.push(row?);
+                let is_fifo = tx
+                    .query_row(
+                        "SELECT is_fifo FROM queue_config WHERE name = ?1",
+                        [&queue_name],
+                        |row| row.get::<_, i32>(0),
+                    )
+                    .optional()?
+                    .is_some_and(|fifo| fifo != 0);
+
+                let mut replayed = Vec::with_capacity(originals.len());
+                for (original_id, body, attributes, message_group_id) in originals {
+                    let new_id = id_generator.generate(&queue_name);
+                    let sequence_number: Option<i64> = if is_fifo {
+                        Some(tx.query_row(
+                            "SELECT COALESCE(MAX(sequence_number), 0) + 1 FROM messages WHERE queue_name = ?1",
+                            [&queue_name],
+                            |row| row.get(0),
+                        )?)
+                    } else {
+                        None
+                    };
+
+                    tx.execute(
+                        "INSERT INTO messages (id, queue_name, body, created_at, attributes, status, receive_count, message_group_id, sequence_number) VALUES (?1, ?2, ?3, ?4, ?5, 'active', 0, ?6, ?7)",
+                        rusqlite::params![
+                            new_id,
+                            queue_name,
+                            body,
+                            created_at,
+                            attributes,
+                            message_group_id,
+                            sequence_number
+                        ],
+                    )?;
+                    replayed.push((original_id, new_id));
                 }
 
-                Ok(messages)
+                tx.commit()?;
+                Ok(replayed)
             })
             .await
     }
 
+    pub async fn list_dlq_source_queues(&self, dlq_name: &str) -> Result<Vec<String>> {
+        let dlq_name = dlq_name.to_string();
+
+        self.connection
+            .call(move |conn| Ok(dlq_source_queues(conn, &dlq_name)?))
+            .await
+    }
+
+    // Move up to `max_messages` DLQ messages, oldest first, back into a queue with new ids:
+    // `destination` when given, otherwise the queue each message was dead-lettered from.
+    // Messages whose original queue no longer exists stay in the DLQ.
+    // Returns the queue each moved message went to.
     pub async fn redrive_dlq_messages(
         &self,
         dlq_name: &str,
-        source_queue: &str,
+        destination: Option<&str>,
         max_messages: Option<u32>,
-    ) -> Result<u32> {
+        id_generator: IdGenerator,
+    ) -> Result<Vec<String>> {
         let dlq_name = dlq_name.to_string();
-        let source_queue = source_queue.to_string();
+        let destination = destination.map(str::to_string);
         let limit = max_messages.unwrap_or(10); // AWS default
 
         self.connection
             .call(move |conn| {
+                let tx = conn.unchecked_transaction()?;
+
                 // Get messages from DLQ to redrive
-                let mut stmt = conn.prepare(
+                let mut stmt = tx.prepare(
                     r#"
-                    SELECT id, original_body, original_attributes, original_created_at
+                    SELECT id, original_queue_name, original_body, original_attributes
                     FROM dead_letter_messages
-                    WHERE dlq_name = ?1 AND original_queue_name = ?2
+                    WHERE dlq_name = ?1
+                    AND (?2 IS NOT NULL OR original_queue_name IN (SELECT name FROM queues))
+                    ORDER BY moved_at ASC
                     LIMIT ?3
                     "#
                 )?;
 
-                let rows = stmt.query_map([&dlq_name, &source_queue, &limit.to_string()], |row| {
+                let rows = stmt.query_map(rusqlite::params![dlq_name, destination, limit], |row| {
                     Ok((
                         row.get::<_, String>(0)?,        // id
-                        row.get::<_, String>(1)?,        // original_body
-                        row.get::<_, Option<String>>(2)?, // original_attributes
-                        row.get::<_, String>(3)?,        // original_created_at
+                        row.get::<_, String>(1)?,        // original_queue_name
+                        row.get::<_, String>(2)?,        // original_body
+                        row.get::<_, Option<String>>(3)?, // original_attributes
                     ))
                 })?;
 
-                let mut redriven_count = 0;
+                let mut redriven_to = Vec::new();
                 let now = chrono::Utc::now().to_rfc3339();
 
                 for row in rows {
-                    let (message_id, body, attributes, _created_at) = row?;
+                    let (message_id, original_queue, body, attributes) = row?;
+                    let queue_name = destination.clone().unwrap_or(original_queue);
 
-                    // Insert message back into original queue with new ID and timestamp
-                    let new_message_id = uuid::Uuid::new_v4().to_string();
-                    conn.execute(
+                    // Insert message back into the queue with new ID and timestamp
+                    let new_message_id = id_generator.generate(&queue_name);
+                    tx.execute(
                         "INSERT INTO messages (id, queue_name, body, created_at, attributes, status, receive_count) VALUES (?1, ?2, ?3, ?4, ?5, 'active', 0)",
-                        [
-                            &new_message_id,
-                            &source_queue,
-                            &body,
-                            &now,
-                            &attributes.unwrap_or_else(|| "".to_string()),
+                        rusqlite::params![
+                            new_message_id,
+                            queue_name,
+                            body,
+                            now,
+                            attributes.filter(|attributes| !attributes.is_empty()),
                         ],
                     )?;
 
                     // Remove from DLQ
-                    conn.execute(
+                    tx.execute(
                         "DELETE FROM dead_letter_messages WHERE id = ?1",
                         [&message_id]
                     )?;
 
-                    redriven_count += 1;
+                    redriven_to.push(queue_name);
                 }
 
-                Ok(redriven_count)
+                drop(stmt);
+                tx.commit()?;
+                Ok(redriven_to)
             })
             .await
     }
@@ -947,7 +2464,7 @@ impl Database {
     pub async fn send_message_with_delay_and_group(
         &self,
         params: SendMessageParams<'_>,
-    ) -> Result<()> {
+    ) -> Result<String> {
         // Check if this is a FIFO queue and get configuration
         let queue_config = self.get_queue_config(params.queue_name).await?;
         let queue_name = params.queue_name.to_string();
@@ -961,21 +2478,19 @@ impl Database {
 
         let is_fifo = queue_config.as_ref().map(|c| c.is_fifo).unwrap_or(false);
 
-        // For FIFO queues, ensure MessageGroupId is provided
-        let message_group_id = if is_fifo && message_group_id.is_none() {
-            // Use a default message group ID if none provided for backwards compatibility
-            Some("default".to_string())
-        } else {
-            message_group_id
-        };
+        // Like AWS, FIFO sends must name their group
+        if is_fifo && message_group_id.is_none() {
+            return Err(tokio_rusqlite::Error::Other(Box::new(
+                MissingMessageGroupId,
+            )));
+        }
 
         // For FIFO queues, handle content-based deduplication if enabled
         let effective_dedup_id = if is_fifo {
             match (deduplication_id.clone(), queue_config.as_ref()) {
                 (Some(id), _) => Some(id), // Explicit deduplication ID provided
                 (None, Some(config)) if config.content_based_deduplication => {
-                    // Generate SHA-256 hash of message body for content-based deduplication
-                    Some(format!("{:x}", md5::compute(body.as_bytes()))) // Using MD5 for simplicity
+                    Some(content_dedup_id(&body))
                 }
                 _ => None,
             }
@@ -983,27 +2498,11 @@ impl Database {
             deduplication_id.clone()
         };
 
-        // Check for duplicate deduplication_id within the last 5 minutes
-        if let Some(ref dedup_id) = effective_dedup_id {
-            let five_minutes_ago = (Utc::now() - chrono::Duration::minutes(5)).to_rfc3339();
-            let queue_name_check = queue_name.clone();
-            let dedup_id_check = dedup_id.clone();
-
-            let duplicate_exists = self.connection
-                .call(move |conn| {
-                    let mut stmt = conn.prepare(
-                        "SELECT COUNT(*) FROM messages WHERE queue_name = ?1 AND deduplication_id = ?2 AND created_at > ?3"
-                    )?;
-                    let count: i64 = stmt.query_row([&queue_name_check, &dedup_id_check, &five_minutes_ago], |row| {
-                        row.get(0)
-                    })?;
-                    Ok(count > 0)
-                })
-                .await?;
-
-            if duplicate_exists {
-                return Ok(()); // Silently ignore duplicate
-            }
+        // A duplicate within the deduplication window returns the original message id
+        if let Some(ref dedup_id) = effective_dedup_id
+            && let Some(original_id) = self.find_duplicate(&queue_name, dedup_id).await?
+        {
+            return Ok(original_id);
         }
 
         self.connection
@@ -1034,7 +2533,7 @@ impl Database {
                         &message_group_id.as_ref()
                     ],
                 )?;
-                Ok(())
+                Ok(message_id)
             })
             .await
     }
@@ -1047,63 +2546,103 @@ impl Database {
     ) -> Result<()> {
         let queue_name = queue_name.to_string();
 
-        // Parse common SQS attributes
-        let visibility_timeout = attributes
-            .get("VisibilityTimeout")
-            .and_then(|v| v.parse::<i32>().ok())
-            .unwrap_or(30);
-        let message_retention_period = attributes
-            .get("MessageRetentionPeriod")
-            .and_then(|v| v.parse::<i32>().ok())
-            .unwrap_or(1209600);
-        let delay_seconds = attributes
-            .get("DelaySeconds")
-            .and_then(|v| v.parse::<i32>().ok())
-            .unwrap_or(0);
-        let receive_message_wait_time = attributes
-            .get("ReceiveMessageWaitTimeSeconds")
-            .and_then(|v| v.parse::<i32>().ok())
-            .unwrap_or(0);
+        // Only the given attributes change; the rest of the row, including the FIFO
+        // settings chosen at creation, is kept. Values that don't parse are ignored.
+        let mut columns: Vec<(&str, rusqlite::types::Value)> = Vec::new();
+        let seconds = |name: &str| {
+            attributes
+                .get(name)
+                .and_then(|v| v.parse::<i64>().ok())
+                .map(rusqlite::types::Value::Integer)
+        };
+        for (attribute, column) in [
+            ("VisibilityTimeout", "visibility_timeout_seconds"),
+            ("MessageRetentionPeriod", "message_retention_period_seconds"),
+            ("DelaySeconds", "delay_seconds"),
+            (
+                "ReceiveMessageWaitTimeSeconds",
+                "receive_message_wait_time_seconds",
+            ),
+            // QLite extension: tune how long deduplication ids suppress resends
+            ("DeduplicationWindowSeconds", "deduplication_window_seconds"),
+        ] {
+            if let Some(value) = seconds(attribute) {
+                columns.push((column, value));
+            }
+        }
 
-        // Parse RedrivePolicy JSON
-        let (max_receive_count, dead_letter_target_arn) =
-            if let Some(redrive_policy) = attributes.get("RedrivePolicy") {
-                // Parse JSON: {"deadLetterTargetArn":"arn:aws:sqs:region:account:queue-name","maxReceiveCount":3}
-                if let Ok(policy) = serde_json::from_str::<serde_json::Value>(redrive_policy) {
-                    let max_count = policy
-                        .get("maxReceiveCount")
-                        .and_then(|v| v.as_i64())
-                        .map(|v| v as i32);
-                    let dlq_arn = policy
-                        .get("deadLetterTargetArn")
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string());
-                    (max_count, dlq_arn)
-                } else {
-                    (None, None)
-                }
-            } else {
-                (None, None)
-            };
+        // QLite extension: disable receive counting for repeatable delivery
+        if let Some(v) = attributes.get("CountReceives") {
+            let count_receives = !v.eq_ignore_ascii_case("false");
+            columns.push(("count_receives", (count_receives as i64).into()));
+        }
+
+        // QLite extension: require deletes to follow sequence order within a group
+        if let Some(v) = attributes.get("AckWatermark") {
+            let ack_watermark = v.eq_ignore_ascii_case("true");
+            columns.push(("ack_watermark", (ack_watermark as i64).into()));
+        }
+
+        // Parse RedrivePolicy JSON; one that doesn't parse removes the policy
+        if let Some(redrive_policy) = attributes.get("RedrivePolicy") {
+            // Parse JSON: {"deadLetterTargetArn":"arn:aws:sqs:region:account:queue-name","maxReceiveCount":3}
+            let policy = serde_json::from_str::<serde_json::Value>(redrive_policy).ok();
+            let max_count = policy
+                .as_ref()
+                .and_then(|p| p.get("maxReceiveCount"))
+                .and_then(|v| v.as_i64());
+            let dlq_arn = policy
+                .as_ref()
+                .and_then(|p| p.get("deadLetterTargetArn"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+            columns.push(("max_receive_count", max_count.into()));
+            columns.push(("dead_letter_target_arn", dlq_arn.into()));
+        }
 
         self.connection
             .call(move |conn| {
+                // A queue without a config row gets one with the defaults QueueService
+                // reports for it. Its timeout is left NULL when unset so receives use
+                // the server's default.
+                let is_fifo = queue_name.ends_with(".fifo");
+                let mut insert_columns = vec!["name", "is_fifo", "content_based_deduplication"];
+                let mut values: Vec<rusqlite::types::Value> = vec![
+                    queue_name.into(),
+                    (is_fifo as i64).into(),
+                    (is_fifo as i64).into(),
+                ];
+                if !columns
+                    .iter()
+                    .any(|(column, _)| *column == "visibility_timeout_seconds")
+                {
+                    insert_columns.push("visibility_timeout_seconds");
+                    values.push(rusqlite::types::Value::Null);
+                }
+                let updates: Vec<String> = columns
+                    .iter()
+                    .map(|(column, _)| format!("{column} = excluded.{column}"))
+                    .collect();
+                for (column, value) in columns {
+                    insert_columns.push(column);
+                    values.push(value);
+                }
+
+                let placeholders: Vec<String> =
+                    (1..=values.len()).map(|i| format!("?{i}")).collect();
+                let on_conflict = if updates.is_empty() {
+                    "DO NOTHING".to_string()
+                } else {
+                    format!("DO UPDATE SET {}", updates.join(", "))
+                };
                 conn.execute(
-                    r#"
-                    INSERT OR REPLACE INTO queue_config
-                    (name, visibility_timeout_seconds, message_retention_period_seconds, delay_seconds,
-                     receive_message_wait_time_seconds, max_receive_count, dead_letter_target_arn)
-                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
-                    "#,
-                    rusqlite::params![
-                        queue_name,
-                        visibility_timeout,
-                        message_retention_period,
-                        delay_seconds,
-                        receive_message_wait_time,
-                        max_receive_count,
-                        dead_letter_target_arn
-                    ],
+                    &format!(
+                        "INSERT INTO queue_config ({}) VALUES ({}) ON CONFLICT(name) {}",
+                        insert_columns.join(", "),
+                        placeholders.join(", "),
+                        on_conflict
+                    ),
+                    rusqlite::params_from_iter(values),
                 )?;
                 Ok(())
             })
@@ -1111,36 +2650,69 @@ impl Database {
     }
 
     // Batch operations for Phase 2
+    // With atomic set, any failed entry rolls back the whole batch. Each result is the
+    // stored message id, or the original message's id for a duplicate, as in a single send.
     pub async fn send_messages_batch(
         &self,
-        messages: Vec<DelayedMessageTuple>, // (queue_name, message_id, body, attributes, deduplication_id, delay_until)
-    ) -> Result<Vec<std::result::Result<(), String>>> {
+        messages: Vec<DelayedMessageTuple>, // (queue_name, message_id, body, attributes, deduplication_id, delay_until, message_group_id)
+        atomic: bool,
+    ) -> Result<Vec<std::result::Result<String, String>>> {
         let created_at = Utc::now().to_rfc3339();
         let mut results = Vec::new();
 
         self.connection
             .call(move |conn| {
                 let tx = conn.unchecked_transaction()?;
+                let mut fifo_settings_by_queue = HashMap::new();
 
-                for (queue_name, message_id, body, attributes, deduplication_id, delay_until) in messages {
+                for (queue_name, message_id, body, attributes, deduplication_id, delay_until, message_group_id) in messages {
                     let result = (|| {
-                        // Check for duplicate deduplication_id within the last 5 minutes if provided
+                        let (is_fifo, content_based_dedup) = match fifo_settings_by_queue.get(&queue_name) {
+                            Some(settings) => *settings,
+                            None => {
+                                let settings = fifo_settings(&tx, &queue_name)?;
+                                fifo_settings_by_queue.insert(queue_name.clone(), settings);
+                                settings
+                            }
+                        };
+
+                        // Same rules as a single FIFO send: content-based dedup and a required group
+                        if is_fifo && message_group_id.is_none() {
+                            return Ok(Err(MissingMessageGroupId.to_string()));
+                        }
+                        let deduplication_id = match deduplication_id {
+                            None if is_fifo && content_based_dedup => Some(content_dedup_id(&body)),
+                            id => id,
+                        };
+
+                        // A duplicate deduplication_id within the queue's deduplication window isn't stored again.
+                        // Earlier entries of this batch count, since they're in the same transaction.
                         if let Some(ref dedup_id) = deduplication_id {
-                            let five_minutes_ago = (Utc::now() - chrono::Duration::minutes(5)).to_rfc3339();
-                            let mut stmt = tx.prepare_cached(
-                                "SELECT COUNT(*) FROM messages WHERE queue_name = ?1 AND deduplication_id = ?2 AND created_at > ?3"
-                            )?;
-                            let count: i64 = stmt.query_row([&queue_name, dedup_id, &five_minutes_ago], |row| {
-                                row.get(0)
-                            })?;
-
-                            if count > 0 {
-                                return Ok(()); // Silently ignore duplicate
+                            let mut stmt = tx.prepare_cached(DUPLICATE_MESSAGE_QUERY)?;
+                            let original_id = stmt
+                                .query_row([&queue_name, dedup_id], |row| {
+                                    row.get::<_, String>(0)
+                                })
+                                .optional()?;
+
+                            if let Some(original_id) = original_id {
+                                return Ok(Ok(original_id));
                             }
                         }
 
+                        // Entries share created_at, so FIFO order comes from the sequence number.
+                        // Earlier entries are visible here since they're in the same transaction.
+                        let sequence_number = if is_fifo {
+                            let seq_num: i64 = tx.prepare_cached(
+                                "SELECT COALESCE(MAX(sequence_number), 0) + 1 FROM messages WHERE queue_name = ?1"
+                            )?.query_row([&queue_name], |row| row.get(0))?;
+                            Some(seq_num.to_string())
+                        } else {
+                            None
+                        };
+
                         tx.execute(
-                            "INSERT INTO messages (id, queue_name, body, created_at, attributes, deduplication_id, delay_until) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                            "INSERT INTO messages (id, queue_name, body, created_at, attributes, deduplication_id, delay_until, message_group_id, sequence_number) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
                             [
                                 &Some(message_id.clone()),
                                 &Some(queue_name.clone()),
@@ -1148,16 +2720,22 @@ impl Database {
                                 &Some(created_at.clone()),
                                 &attributes,
                                 &deduplication_id,
-                                &delay_until
+                                &delay_until,
+                                &message_group_id,
+                                &sequence_number
                             ],
                         )?;
-                        Ok(())
+                        Ok(Ok(message_id))
                     })();
 
-                    results.push(result.map_err(|e: rusqlite::Error| e.to_string()));
+                    results.push(result.map_err(|e: rusqlite::Error| e.to_string()).and_then(|stored| stored));
                 }
 
-                tx.commit()?;
+                if atomic && results.iter().any(|r| r.is_err()) {
+                    tx.rollback()?;
+                } else {
+                    tx.commit()?;
+                }
                 Ok(results)
             })
             .await
@@ -1165,8 +2743,8 @@ impl Database {
 
     pub async fn delete_messages_batch(
         &self,
-        message_ids: Vec<String>,
-    ) -> Result<Vec<std::result::Result<bool, String>>> {
+        receipt_handles: Vec<String>,
+    ) -> Result<Vec<std::result::Result<DeleteOutcome, String>>> {
         let deleted_at = Utc::now().to_rfc3339();
         let mut results = Vec::new();
 
@@ -1174,16 +2752,11 @@ impl Database {
             .call(move |conn| {
                 let tx = conn.unchecked_transaction()?;
 
-                for message_id in message_ids {
-                    let result = (|| {
-                        let changes = tx.execute(
-                            "UPDATE messages SET status = 'deleted', deleted_at = ?2 WHERE id = ?1",
-                            [&message_id, &deleted_at],
-                        )?;
-                        Ok(changes > 0)
-                    })();
-
-                    results.push(result.map_err(|e: rusqlite::Error| e.to_string()));
+                // Entries are applied in order, so a batch deleting a whole group
+                // front-to-back satisfies the ack watermark
+                for receipt_handle in receipt_handles {
+                    let result = delete_in_order(&tx, &receipt_handle, &deleted_at);
+                    results.push(result.map_err(|e| e.to_string()));
                 }
 
                 tx.commit()?;
@@ -1192,58 +2765,160 @@ impl Database {
             .await
     }
 
+    // Like receive_message, messages past the queue's maxReceiveCount are moved to its DLQ
+    // instead of being returned, and others are fetched in their place
     pub async fn receive_messages_batch(
         &self,
         queue_name: &str,
         max_messages: u32,
-    ) -> Result<Vec<(String, String, String, Option<String>)>> {
+    ) -> Result<(Vec<ReceivedRow>, Vec<DeadLettered>)> {
         let queue_name = queue_name.to_string();
         let processed_at = Utc::now().to_rfc3339();
         let max_messages = max_messages.min(10) as i64; // AWS SQS limit
+        let timeout_seconds = self.visibility_timeout_seconds as i64;
+        let jitter_seconds = self.visibility_jitter_seconds;
+        let strict_standard_order = self.strict_standard_order;
+        let sequence_in_receipt_handles = self.sequence_in_receipt_handles;
+        let metrics = self.metrics.clone();
 
         self.connection
             .call(move |conn| {
                 let tx = conn.unchecked_transaction()?;
 
-                let mut stmt = tx.prepare(
+                let (count_receives, queue_timeout_seconds, is_fifo, max_receive_count, dlq_arn) = tx
+                    .query_row(
+                        "SELECT count_receives, visibility_timeout_seconds, is_fifo, max_receive_count, dead_letter_target_arn FROM queue_config WHERE name = ?1",
+                        [&queue_name],
+                        |row| {
+                            Ok((
+                                row.get::<_, Option<i32>>(0)?,
+                                row.get::<_, Option<i64>>(1)?,
+                                row.get::<_, Option<i32>>(2)?.unwrap_or(0) != 0,
+                                row.get::<_, Option<i32>>(3)?,
+                                row.get::<_, Option<String>>(4)?,
+                            ))
+                        },
+                    )
+                    .optional()?
+                    .unwrap_or((None, None, false, None, None));
+                let count_receives = count_receives.unwrap_or(1);
+                let timeout_seconds = queue_timeout_seconds.unwrap_or(timeout_seconds);
+
+                // Batches from FIFO queues keep their historical created_at order
+                let order_by = if is_fifo {
+                    "created_at ASC"
+                } else {
+                    receive_order(false, strict_standard_order)
+                };
+                let mut stmt = tx.prepare(&format!(
                     r#"
-                    SELECT id, body, created_at, attributes
+                    SELECT id, body, created_at, attributes, processed_at IS NULL, receive_count, first_received_at, sequence_number
                     FROM messages
                     WHERE queue_name = ?1
-                    AND status = 'active'
-                    AND (visibility_timeout IS NULL OR visibility_timeout < datetime('now'))
-                    AND (delay_until IS NULL OR delay_until < datetime('now'))
-                    ORDER BY created_at ASC
+                    AND (status = 'active' OR (status = 'processing' AND visibility_timeout IS NOT NULL))
+                    AND (visibility_timeout IS NULL OR julianday(visibility_timeout) <= julianday('now'))
+                    AND (delay_until IS NULL OR julianday(delay_until) <= julianday('now'))
+                    {}
+                    ORDER BY {}
                     LIMIT ?2
                     "#,
-                )?;
-
-                let rows = stmt.query_map([&queue_name, &max_messages.to_string()], |row| {
-                    Ok((
-                        row.get::<_, String>(0)?,
-                        row.get::<_, String>(1)?,
-                        row.get::<_, String>(2)?,
-                        row.get::<_, Option<String>>(3)?,
-                    ))
-                })?;
-
-                let mut messages = Vec::new();
-                for row in rows {
-                    let (id, body, created_at, attributes) = row?;
+                    fifo_group_filter(is_fifo, "?3"),
+                    order_by
+                ))?;
+
+                let mut messages: Vec<ReceivedRow> = Vec::new();
+                let mut dead_lettered = Vec::new();
+                let mut first_receives = Vec::new();
+                // Dead-lettered messages leave gaps in the batch, so query again until it is
+                // full or a query moves nothing to the DLQ
+                loop {
+                    let remaining = (max_messages - messages.len() as i64).to_string();
+                    let delivered_ids = serde_json::to_string(
+                        &messages.iter().map(|m| m.0.as_str()).collect::<Vec<_>>(),
+                    )
+                    .unwrap_or_else(|_| "[]".to_string());
+                    // Only FIFO queries reference the delivered ids
+                    let query_params = if is_fifo {
+                        vec![&queue_name, &remaining, &delivered_ids]
+                    } else {
+                        vec![&queue_name, &remaining]
+                    };
+
+                    // Read the whole page before marking any of it in flight, so the group filter
+                    // doesn't hide later messages of a group this batch is already delivering
+                    let rows = stmt
+                        .query_map(rusqlite::params_from_iter(&query_params), |row| {
+                            Ok((
+                                row.get::<_, String>(0)?,
+                                row.get::<_, String>(1)?,
+                                row.get::<_, String>(2)?,
+                                row.get::<_, Option<String>>(3)?,
+                                row.get::<_, bool>(4)?,
+                                row.get::<_, Option<i64>>(5)?.unwrap_or(0),
+                                row.get::<_, Option<String>>(6)?,
+                                row.get::<_, Option<i64>>(7)?,
+                            ))
+                        })?
+                        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+                    let dead_lettered_before = dead_lettered.len();
+                    for (id, body, created_at, attributes, first_receive, receive_count, first_received_at, sequence_number) in rows {
+                    let receive_count = receive_count + i64::from(count_receives != 0);
+                    if let Some(moved) = dead_letter_if_exhausted(
+                        &tx,
+                        &id,
+                        receive_count,
+                        max_receive_count,
+                        dlq_arn.as_deref(),
+                        &processed_at,
+                    )? {
+                        dead_lettered.push(moved);
+                        continue;
+                    }
 
-                    // Set visibility timeout (30 seconds from now) and mark as processing
-                    let timeout = (Utc::now() + chrono::Duration::seconds(30)).to_rfc3339();
+                    // Set visibility timeout and mark as processing
+                    let timeout = visibility_deadline(timeout_seconds, jitter_seconds);
+                    let receipt_token = new_receipt_token();
                     tx.execute(
-                        "UPDATE messages SET visibility_timeout = ?1, receive_count = receive_count + 1, status = 'processing', processed_at = ?3 WHERE id = ?2",
-                        [&timeout, &id, &processed_at],
+                        "UPDATE messages SET visibility_timeout = ?1, receive_count = receive_count + (?4 != 0), status = 'processing', processed_at = ?3, receipt_token = ?5, first_received_at = COALESCE(first_received_at, ?3) WHERE id = ?2",
+                        rusqlite::params![timeout, id, processed_at, count_receives, receipt_token],
                     )?;
+                    if first_receive {
+                        first_receives.push(created_at.clone());
+                    }
 
-                    messages.push((id, body, created_at, attributes));
+                    let receipt_handle = issue_receipt_handle(
+                        &id,
+                        &receipt_token,
+                        sequence_number,
+                        sequence_in_receipt_handles,
+                    );
+                    let first_received_at = first_received_at.unwrap_or_else(|| processed_at.clone());
+                    messages.push((
+                        id,
+                        body,
+                        created_at,
+                        attributes,
+                        receipt_handle,
+                        receive_count.max(0) as u32,
+                        first_received_at,
+                        timeout,
+                    ));
+                    }
+
+                    if dead_lettered.len() == dead_lettered_before
+                        || messages.len() as i64 >= max_messages
+                    {
+                        break;
+                    }
                 }
 
                 drop(stmt); // Explicitly drop the statement before committing
                 tx.commit()?;
-                Ok(messages)
+                for created_at in &first_receives {
+                    observe_first_receive(&metrics, created_at);
+                }
+                Ok((messages, dead_lettered))
             })
             .await
     }
@@ -1256,13 +2931,12 @@ impl Database {
             crate::config::RetentionMode::KeepForever => {
                 // In KeepForever mode, just clean up visibility timeouts for processing messages
                 // that have timed out and should be available again
-                let now = Utc::now().to_rfc3339();
-
                 self.connection
                     .call(move |conn| {
                         let changes = conn.execute(
-                            "UPDATE messages SET status = 'active', visibility_timeout = NULL WHERE status = 'processing' AND visibility_timeout < ?1",
-                            [&now],
+                            "UPDATE messages SET status = 'active', visibility_timeout = NULL
+                             WHERE status = 'processing' AND julianday(visibility_timeout) < julianday('now')",
+                            [],
                         )?;
                         Ok(changes as u32)
                     })
@@ -1277,8 +2951,9 @@ impl Database {
 
                 self.connection
                     .call(move |conn| {
-                        let mut stmt =
-                            conn.prepare("DELETE FROM messages WHERE created_at < ?1")?;
+                        let mut stmt = conn.prepare(
+                            "DELETE FROM messages WHERE julianday(created_at) < julianday(?1)",
+                        )?;
                         let deleted = stmt.execute([cutoff_str])?;
                         Ok(deleted as u32)
                     })
@@ -1286,15 +2961,289 @@ impl Database {
             }
         }
     }
+
+    // Hard-delete messages consumers deleted before the cutoff
+    pub async fn prune_deleted_messages(&self, deleted_before: &str) -> Result<u32> {
+        let deleted_before = deleted_before.to_string();
+
+        self.connection
+            .call(move |conn| {
+                let pruned = conn.execute(
+                    "DELETE FROM messages WHERE status = 'deleted' AND julianday(deleted_at) < julianday(?1)",
+                    [&deleted_before],
+                )?;
+                Ok(pruned as u32)
+            })
+            .await
+    }
+
+    // Delete dead-letter entries moved to their DLQ before the cutoff
+    pub async fn cleanup_dead_letter_messages(&self, moved_before: &str) -> Result<u32> {
+        let moved_before = moved_before.to_string();
+
+        self.connection
+            .call(move |conn| {
+                let deleted = conn.execute(
+                    "DELETE FROM dead_letter_messages WHERE julianday(moved_at) < julianday(?1)",
+                    [&moved_before],
+                )?;
+                Ok(deleted as u32)
+            })
+            .await
+    }
+
+    // How long in-flight messages have been held since they were received
+    pub async fn get_inflight_age_stats(&self) -> Result<Vec<InflightAgeStats>> {
+        self.connection
+            .call(|conn| {
+                let mut stmt = conn.prepare(
+                    r#"
+                    SELECT queue_name, COUNT(*), MAX(age), SUM(age)
+                    FROM (
+                        SELECT queue_name,
+                               MAX(0, (julianday('now') - julianday(processed_at)) * 86400) AS age
+                        FROM messages
+                        WHERE status = 'processing' AND processed_at IS NOT NULL
+                    )
+                    GROUP BY queue_name
+                    ORDER BY queue_name
+                    "#,
+                )?;
+                let rows = stmt.query_map([], |row| {
+                    Ok(InflightAgeStats {
+                        queue_name: row.get(0)?,
+                        count: row.get::<_, i64>(1)? as u32,
+                        max_age_seconds: row.get::<_, Option<f64>>(2)?.unwrap_or(0.0),
+                        sum_age_seconds: row.get::<_, Option<f64>>(3)?.unwrap_or(0.0),
+                    })
+                })?;
+
+                let mut stats = Vec::new();
+                for row in rows {
+                    stats.push(row?);
+                }
+                Ok(stats)
+            })
+            .await
+    }
+
+    // Maintained message counts per (queue_name, status)
+    pub async fn get_queue_counters(&self) -> Result<Vec<(String, String, u32)>> {
+        self.connection
+            .call(|conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT queue_name, status, count FROM queue_counters WHERE count != 0 ORDER BY queue_name, status",
+                )?;
+                let rows = stmt.query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, i64>(2)?.max(0) as u32,
+                    ))
+                })?;
+
+                let mut counters = Vec::new();
+                for row in rows {
+                    counters.push(row?);
+                }
+                Ok(counters)
+            })
+            .await
+    }
+
+    // Recompute counters with COUNT(*) and correct any drifted rows.
+    // Returns the number of (queue, status) rows that had to be corrected.
+    pub async fn reconcile_queue_counters(&self) -> Result<u32> {
+        self.connection
+            .call(|conn| {
+                let tx = conn.unchecked_transaction()?;
+
+                let mut actual: HashMap<(String, String), i64> = HashMap::new();
+                {
+                    let mut stmt = tx.prepare(
+                        "SELECT queue_name, COALESCE(status, 'active'), COUNT(*) FROM messages GROUP BY queue_name, COALESCE(status, 'active')",
+                    )?;
+                    let rows = stmt.query_map([], |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, String>(1)?,
+                            row.get::<_, i64>(2)?,
+                        ))
+                    })?;
+                    for row in rows {
+                        let (queue_name, status, count) = row?;
+                        actual.insert((queue_name, status), count);
+                    }
+                }
+
+                let mut stored: HashMap<(String, String), i64> = HashMap::new();
+                {
+                    let mut stmt =
+                        tx.prepare("SELECT queue_name, status, count FROM queue_counters")?;
+                    let rows = stmt.query_map([], |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, String>(1)?,
+                            row.get::<_, i64>(2)?,
+                        ))
+                    })?;
+                    for row in rows {
+                        let (queue_name, status, count) = row?;
+                        stored.insert((queue_name, status), count);
+                    }
+                }
+
+                let mut corrected = 0u32;
+                let keys: HashSet<(String, String)> =
+                    actual.keys().chain(stored.keys()).cloned().collect();
+
+                for (queue_name, status) in keys {
+                    let key = (queue_name, status);
+                    let actual_count = actual.get(&key).copied().unwrap_or(0);
+                    let stored_count = stored.get(&key).copied().unwrap_or(0);
+                    let (queue_name, status) = key;
+
+                    if actual_count != stored_count {
+                        warn!(
+                            "Queue counter drift for '{}' ({}): stored {}, actual {}",
+                            queue_name, status, stored_count, actual_count
+                        );
+                        corrected += 1;
+                    }
+
+                    if actual_count == 0 {
+                        // Drop rows for statuses/queues that no longer have messages
+                        tx.execute(
+                            "DELETE FROM queue_counters WHERE queue_name = ?1 AND status = ?2",
+                            [&queue_name, &status],
+                        )?;
+                    } else if actual_count != stored_count {
+                        tx.execute(
+                            "INSERT INTO queue_counters (queue_name, status, count) VALUES (?1, ?2, ?3) ON CONFLICT(queue_name, status) DO UPDATE SET count = excluded.count",
+                            rusqlite::params![queue_name, status, actual_count],
+                        )?;
+                    }
+                }
+
+                tx.commit()?;
+                Ok(corrected)
+            })
+            .await
+    }
+}
+
+// Resolve a queue name from a DLQ target, which may be a plain name, a queue URL
+// (http://host/queue), or an AWS ARN (arn:aws:sqs:region:account:queue)
+pub fn dlq_name_from_arn(arn: &str) -> &str {
+    arn.rsplit(['/', ':']).next().unwrap_or(arn)
 }
 
 #[derive(Debug, Clone)]
 pub struct QueueAttributes {
     pub approximate_number_of_messages: u32,
     pub approximate_number_of_messages_not_visible: u32,
+    pub approximate_number_of_messages_delayed: u32,
+    // Messages from this queue that were moved to its dead letter queue
+    pub dead_lettered_messages: u32,
     pub created_timestamp: String,
 }
 
+// A single receive recorded in message_delivery_log
+#[derive(Debug, Clone)]
+pub struct DeliveryRecord {
+    pub delivered_at: String,
+    pub receipt_handle: String,
+    pub consumer_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisibilityChange {
+    Changed,
+    NotFound,
+    NotInFlight,
+    // The new deadline would be past the maximum extension from the message's receive
+    ExceedsCeiling,
+    // The message was received again after this receipt handle was issued
+    StaleHandle,
+}
+
+// A FIFO send without a MessageGroupId. Single sends fail with it as a
+// tokio_rusqlite::Error::Other; batch entries fail with its message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingMessageGroupId;
+
+impl std::fmt::Display for MissingMessageGroupId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("The request must contain the parameter MessageGroupId.")
+    }
+}
+
+impl std::error::Error for MissingMessageGroupId {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueueDeletion {
+    Deleted,
+    NotFound,
+    // Other queues dead-letter into this one and force wasn't set; nothing was deleted
+    DeadLetterTargetInUse { source_queues: Vec<String> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeleteOutcome {
+    Deleted,
+    NotFound,
+    // The queue has ack_watermark enabled and earlier messages in the group are still pending
+    OutOfOrder { pending: u32 },
+    // The message was received again after this receipt handle was issued
+    StaleHandle,
+}
+
+// Outcome of a WAL checkpoint; frame counts are -1 when the database isn't in WAL mode
+#[derive(Debug, Clone)]
+pub struct WalCheckpoint {
+    pub busy: bool,
+    pub log_frames: i64,
+    pub checkpointed_frames: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct StorageStats {
+    pub size_bytes: u64,
+    pub page_count: i64,
+    pub freelist_count: i64,
+}
+
+// Rows buffered between an export's reader thread and its consumer
+pub const EXPORT_CHANNEL_CAPACITY: usize = 256;
+
+// Default number of messages a listing loads, so a deep queue can't be loaded into memory whole
+pub const DEFAULT_MESSAGE_LIST_LIMIT: usize = 1000;
+
+// One row of a queue export, in any status
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ExportedMessage {
+    pub id: String,
+    pub body: String,
+    pub created_at: String,
+    pub visibility_timeout: Option<String>,
+    pub receive_count: u32,
+    // Message attributes as stored, a JSON object
+    pub attributes: Option<String>,
+    pub deduplication_id: Option<String>,
+    pub status: String,
+    pub processed_at: Option<String>,
+    pub deleted_at: Option<String>,
+}
+
+// Age of messages currently in flight, per queue
+#[derive(Debug, Clone)]
+pub struct InflightAgeStats {
+    pub queue_name: String,
+    pub count: u32,
+    pub max_age_seconds: f64,
+    pub sum_age_seconds: f64,
+}
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct QueueMetric {
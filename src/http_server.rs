@@ -1,16 +1,37 @@
 use axum::{
     Router,
-    extract::{Path, Query, State},
+    extract::{DefaultBodyLimit, Path, Query, Request, State},
     http::{HeaderMap, StatusCode},
+    middleware::{self, Next},
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{delete, get, post},
 };
 use quick_xml::se::to_string as to_xml;
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, LazyLock},
+};
 use tower::ServiceBuilder;
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tower_http::{cors::CorsLayer, limit::RequestBodyLimitLayer, trace::TraceLayer};
+
+use crate::{
+    admin, api,
+    config::BatchMode,
+    database::{
+        DeleteOutcome, MissingMessageGroupId, QueueDeletion, VisibilityChange, dlq_name_from_arn,
+    },
+    message::{
+        MAX_BATCH_PAYLOAD_SIZE, MessageAttributeValue, ReceivedMessage, md5_of_message_attributes,
+        message_attributes_size, validate_message_attributes, validate_message_body,
+    },
+    queue_service::{PurgeOutcome, QueueCreation, QueueService},
+    sqs_types::*,
+};
 
-use crate::{message::MessageAttributeValue, queue_service::QueueService, sqs_types::*, ui};
+#[cfg(feature = "ui")]
+use crate::ui;
 
 pub struct AppState {
     pub queue_service: Arc<QueueService>,
@@ -22,22 +43,63 @@ pub fn create_router(
     base_url: String,
     enable_ui: bool,
 ) -> Router {
+    // Queue URLs are built as "{base_url}/{name}", so a trailing slash would double up
     let state = Arc::new(AppState {
         queue_service,
-        base_url,
+        base_url: base_url.trim_end_matches('/').to_string(),
     });
 
-    let mut router = Router::new()
+    // SQS API routes, the only ones subject to fault injection
+    let sqs_routes = Router::new()
         .route("/", post(handle_sqs_action))
         .route("/:queue_name", post(handle_queue_action))
+        .route(
+            "/:account_id/:queue_name",
+            post(handle_account_queue_action),
+        )
+        .route_layer(middleware::from_fn_with_state(state.clone(), inject_faults));
+
+    let router = Router::new()
+        .merge(sqs_routes)
         .route("/health", get(health_check))
         .route("/health/ready", get(readiness_check))
         .route("/health/live", get(liveness_check))
-        .route("/metrics", get(metrics_endpoint));
+        .route("/metrics", get(metrics_endpoint))
+        .route("/actions", get(list_actions))
+        .route("/admin/backup", post(admin::backup))
+        .route("/admin/checkpoint", post(admin::checkpoint))
+        .route("/api/queues", get(api::list_queues))
+        .route("/api/queue/:queue_name/config", get(api::get_queue_config))
+        .route("/api/queue/:queue_name/clone", post(api::clone_queue))
+        .route(
+            "/api/queue/:queue_name/purge-filtered",
+            post(api::purge_filtered),
+        )
+        .route(
+            "/api/queue/:queue_name/attribute-index",
+            post(api::create_attribute_index),
+        )
+        .route("/api/queue/:queue_name/replay", post(api::replay_messages))
+        .route("/api/queue/:queue_name/export", get(api::export_queue))
+        .route("/api/queue/:queue_name/lock", post(api::acquire_lock))
+        .route(
+            "/api/queue/:queue_name/lock/:token",
+            delete(api::release_lock),
+        )
+        .route("/api/message/:message_id/nack", post(api::nack_message))
+        .route(
+            "/api/message/:message_id/deliveries",
+            get(api::message_deliveries),
+        )
+        .route(
+            "/api/message/:message_id/receive-count",
+            get(api::message_receive_count),
+        );
 
     // Add UI routes if enabled
-    if enable_ui {
-        router = router
+    #[cfg(feature = "ui")]
+    let router = if enable_ui {
+        router
             .route("/ui", get(ui::dashboard))
             .route("/ui/queue/:queue_name", get(ui::queue_messages))
             .route("/ui/create-queue", post(ui::create_queue_ui))
@@ -62,14 +124,65 @@ pub fn create_router(
             .route(
                 "/api/ui/restore-message/:message_id",
                 post(ui::restore_message_json),
-            );
+            )
+    } else {
+        router
+    };
+
+    #[cfg(not(feature = "ui"))]
+    if enable_ui {
+        tracing::warn!("Web UI requested but qlite was built without the `ui` feature");
     }
 
-    router.with_state(state).layer(
-        ServiceBuilder::new()
-            .layer(TraceLayer::new_for_http())
-            .layer(CorsLayer::permissive()),
-    )
+    let max_request_body_bytes = state.queue_service.max_request_body_bytes();
+    router
+        .with_state(state)
+        // RequestBodyLimitLayer enforces the configured limit instead of axum's fixed 2 MB
+        .layer(RequestBodyLimitLayer::new(max_request_body_bytes))
+        .layer(DefaultBodyLimit::disable())
+        .layer(
+            ServiceBuilder::new()
+                .layer(TraceLayer::new_for_http())
+                .layer(CorsLayer::permissive())
+                .layer(middleware::map_response(request_too_large)),
+        )
+}
+
+// Oversized bodies are rejected by the body limit with a plain-text 413; answer with the
+// SQS error instead so clients see RequestTooLarge
+async fn request_too_large(response: Response) -> Response {
+    let is_xml = response
+        .headers()
+        .get("Content-Type")
+        .is_some_and(|content_type| content_type == "application/xml");
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE && !is_xml {
+        return error_response(
+            "RequestTooLarge",
+            "The request body exceeds the maximum allowed size",
+        );
+    }
+    response
+}
+
+// Requests carrying this header are never failed by fault injection
+pub const NO_FAULT_HEADER: &str = "x-qlite-no-fault";
+
+// Testing aid (`fault_injection_rate`): fail a random share of SQS requests with the
+// retryable errors real SQS returns, so clients can exercise their retry and backoff
+async fn inject_faults(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let rate = state.queue_service.fault_injection_rate();
+    if rate > 0.0 && !request.headers().contains_key(NO_FAULT_HEADER) && fastrand::f64() < rate {
+        return if fastrand::bool() {
+            error_response("ServiceUnavailable", "Injected fault: service unavailable")
+        } else {
+            error_response("Throttling", "Injected fault: rate exceeded")
+        };
+    }
+    next.run(request).await
 }
 
 async fn handle_sqs_action(
@@ -78,115 +191,25 @@ async fn handle_sqs_action(
     headers: HeaderMap,
     body: String,
 ) -> Response {
-    let content_type = headers
-        .get("content-type")
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("");
-
-    // Determine the action from either query parameter or X-Amz-Target header
-    let action = if let Some(action) = query.get("Action") {
-        // Form-encoded request
-        action.clone()
-    } else if let Some(target) = headers.get("x-amz-target").and_then(|v| v.to_str().ok()) {
-        // AWS CLI/SDK JSON request - extract action from X-Amz-Target
-        if let Some(action) = target.strip_prefix("AmazonSQS.") {
-            action.to_string()
-        } else {
-            return error_response("InvalidAction", "Invalid X-Amz-Target header");
-        }
-    } else {
-        return error_response(
-            "MissingAction",
-            "Action parameter or X-Amz-Target header is required",
-        );
-    };
+    dispatch_action(state, None, &query, &headers, &body).await
+}
 
-    // Parse parameters based on content type
-    let params = if content_type.contains("application/x-amz-json") {
-        // Parse JSON body for AWS CLI/SDK requests
-        parse_json_params(&body).unwrap_or_default()
-    } else {
-        // Parse form-encoded body for traditional requests
-        parse_form_params(&body).unwrap_or_default()
-    };
+// Queue names never contain '/' (FIFO names only add a ".fifo" suffix), so the last
+// path segment is the name for every URL shape clients send: `<base>/<name>`,
+// `<base>/<account-id>/<name>` as returned by AWS, a trailing slash, or a bare name.
+fn queue_name_from_url(queue_url: &str) -> &str {
+    let path = queue_url.split(['?', '#']).next().unwrap_or("");
+    path.trim_end_matches('/').rsplit('/').next().unwrap_or("")
+}
 
-    match action.as_str() {
-        "ListQueues" => handle_list_queues(state).await,
-        "CreateQueue" => {
-            if let Some(queue_name) = params.get("QueueName") {
-                handle_create_queue_with_attributes(state, queue_name, &params).await
-            } else {
-                error_response("MissingParameter", "QueueName parameter is required")
-            }
-        }
-        "GetQueueUrl" => {
-            if let Some(queue_name) = params.get("QueueName") {
-                handle_get_queue_url(state, queue_name).await
-            } else {
-                error_response("MissingParameter", "QueueName parameter is required")
-            }
-        }
-        "SendMessageBatch" => {
-            // Extract queue name from batch entries or use a parameter
-            handle_send_message_batch(state, &params).await
-        }
-        "DeleteMessageBatch" => handle_delete_message_batch(state, &params).await,
-        "SetQueueAttributes" => {
-            if let Some(queue_url) = params.get("QueueUrl").cloned() {
-                // Extract queue name from URL (assuming format like http://localhost:3000/queue-name)
-                let queue_name = queue_url.split('/').next_back().unwrap_or("");
-                handle_set_queue_attributes(state, queue_name, params).await
-            } else {
-                error_response("MissingParameter", "QueueUrl parameter is required")
-            }
-        }
-        "GetQueueAttributes" => {
-            if let Some(queue_url) = params.get("QueueUrl").cloned() {
-                // Extract queue name from URL (assuming format like http://localhost:3000/queue-name)
-                let queue_name = queue_url.split('/').next_back().unwrap_or("");
-                handle_get_queue_attributes(state, queue_name).await
-            } else {
-                error_response("MissingParameter", "QueueUrl parameter is required")
-            }
-        }
-        "SendMessage" => {
-            if let Some(queue_url) = params.get("QueueUrl").cloned() {
-                // Extract queue name from URL (assuming format like http://localhost:3000/queue-name)
-                let queue_name = queue_url.split('/').next_back().unwrap_or("");
-                handle_send_message_enhanced(state, queue_name, params).await
-            } else {
-                error_response("MissingParameter", "QueueUrl parameter is required")
-            }
-        }
-        "ReceiveMessage" => {
-            if let Some(queue_url) = params.get("QueueUrl").cloned() {
-                // Extract queue name from URL (assuming format like http://localhost:3000/queue-name)
-                let queue_name = queue_url.split('/').next_back().unwrap_or("");
-                handle_receive_message_enhanced(state, queue_name, params).await
-            } else {
-                error_response("MissingParameter", "QueueUrl parameter is required")
-            }
-        }
-        "DeleteMessage" => {
-            if let Some(queue_url) = params.get("QueueUrl").cloned() {
-                // Extract queue name from URL (assuming format like http://localhost:3000/queue-name)
-                let queue_name = queue_url.split('/').next_back().unwrap_or("");
-                handle_delete_message(state, queue_name, params).await
-            } else {
-                error_response("MissingParameter", "QueueUrl parameter is required")
-            }
-        }
-        "DeleteQueue" => {
-            if let Some(queue_url) = params.get("QueueUrl").cloned() {
-                // Extract queue name from URL (assuming format like http://localhost:3000/queue-name)
-                let queue_name = queue_url.split('/').next_back().unwrap_or("");
-                handle_delete_queue(state, queue_name).await
-            } else {
-                error_response("MissingParameter", "QueueUrl parameter is required")
-            }
-        }
-        _ => error_response("InvalidAction", &format!("Unknown action: {}", action)),
-    }
+async fn handle_account_queue_action(
+    state: State<Arc<AppState>>,
+    Path((_account_id, queue_name)): Path<(String, String)>,
+    query: Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    handle_queue_action(state, Path(queue_name), query, headers, body).await
 }
 
 async fn handle_queue_action(
@@ -195,6 +218,17 @@ async fn handle_queue_action(
     Query(query): Query<HashMap<String, String>>,
     headers: HeaderMap,
     body: String,
+) -> Response {
+    dispatch_action(state, Some(queue_name), &query, &headers, &body).await
+}
+
+// Shared by POST / and POST /<queue-name>, so both accept exactly the same actions
+async fn dispatch_action(
+    state: Arc<AppState>,
+    path_queue_name: Option<String>,
+    query: &HashMap<String, String>,
+    headers: &HeaderMap,
+    body: &str,
 ) -> Response {
     let content_type = headers
         .get("content-type")
@@ -203,8 +237,10 @@ async fn handle_queue_action(
 
     // Determine the action from either query parameter or X-Amz-Target header
     let action = if let Some(action) = query.get("Action") {
+        // Form-encoded request
         action.clone()
     } else if let Some(target) = headers.get("x-amz-target").and_then(|v| v.to_str().ok()) {
+        // AWS CLI/SDK JSON request - extract action from X-Amz-Target
         if let Some(action) = target.strip_prefix("AmazonSQS.") {
             action.to_string()
         } else {
@@ -219,27 +255,312 @@ async fn handle_queue_action(
 
     // Parse parameters based on content type
     let params = if content_type.contains("application/x-amz-json") {
-        parse_json_params(&body).unwrap_or_default()
+        // Parse JSON body for AWS CLI/SDK requests
+        parse_json_params(body).unwrap_or_default()
     } else {
-        parse_form_params(&body).unwrap_or_default()
+        // Parse form-encoded body for traditional requests
+        parse_form_params(body).unwrap_or_default()
     };
 
-    match action.as_str() {
-        "SendMessage" => handle_send_message_enhanced(state, &queue_name, params).await,
-        "ReceiveMessage" => handle_receive_message_enhanced(state, &queue_name, params).await,
-        "DeleteMessage" => handle_delete_message(state, &queue_name, params).await,
-        "GetQueueAttributes" => handle_get_queue_attributes(state, &queue_name).await,
-        "SetQueueAttributes" => handle_set_queue_attributes(state, &queue_name, params).await,
-        "SendMessageBatch" => handle_send_message_batch_for_queue(state, &queue_name, params).await,
-        "ReceiveMessageBatch" => handle_receive_message_batch(state, &queue_name, params).await,
-        "DeleteMessageBatch" => {
-            handle_delete_message_batch_for_queue(state, &queue_name, params).await
+    let context = ActionContext {
+        state,
+        action,
+        path_queue_name,
+        params,
+    };
+    ACTION_REGISTRY.dispatch(context).await
+}
+
+// Everything an action handler needs, whichever endpoint the request arrived on
+struct ActionContext {
+    state: Arc<AppState>,
+    action: String,
+    // Set for POST /<queue-name>; a QueueUrl parameter still takes precedence
+    path_queue_name: Option<String>,
+    params: HashMap<String, String>,
+}
+
+impl ActionContext {
+    // The QueueUrl parameter if present, so a request means the same on either route
+    fn queue_name(&self) -> Result<String, MissingParameter> {
+        match (self.params.get("QueueUrl"), &self.path_queue_name) {
+            (Some(queue_url), _) => Ok(queue_name_from_url(queue_url).to_string()),
+            (None, Some(queue_name)) => Ok(queue_name.clone()),
+            (None, None) => Err(MissingParameter("QueueUrl")),
+        }
+    }
+
+    fn param(&self, name: &'static str) -> Result<String, MissingParameter> {
+        self.params.get(name).cloned().ok_or(MissingParameter(name))
+    }
+}
+
+// A required parameter was absent; dispatch turns it into an SQS MissingParameter error
+struct MissingParameter(&'static str);
+
+type ActionResult = Result<Response, MissingParameter>;
+type ActionHandler =
+    Box<dyn Fn(ActionContext) -> Pin<Box<dyn Future<Output = ActionResult> + Send>> + Send + Sync>;
+
+struct ActionSpec {
+    name: &'static str,
+    // Part of the AWS SQS API, as opposed to a QLite-only extension
+    aws: bool,
+    // None for AWS actions QLite doesn't implement yet
+    handler: Option<ActionHandler>,
+}
+
+// Maps action names to handlers. Every AWS SQS action is listed, implemented or not,
+// so unimplemented ones can be told apart from typos and reported by GET /actions.
+struct ActionRegistry {
+    actions: Vec<ActionSpec>,
+}
+
+impl ActionRegistry {
+    fn register<F, Fut>(&mut self, name: &'static str, handler: F) -> &mut Self
+    where
+        F: Fn(ActionContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ActionResult> + Send + 'static,
+    {
+        self.push(
+            name,
+            true,
+            Some(Box::new(move |ctx| Box::pin(handler(ctx)))),
+        )
+    }
+
+    fn extension<F, Fut>(&mut self, name: &'static str, handler: F) -> &mut Self
+    where
+        F: Fn(ActionContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ActionResult> + Send + 'static,
+    {
+        self.push(
+            name,
+            false,
+            Some(Box::new(move |ctx| Box::pin(handler(ctx)))),
+        )
+    }
+
+    fn unimplemented(&mut self, name: &'static str) -> &mut Self {
+        self.push(name, true, None)
+    }
+
+    fn push(&mut self, name: &'static str, aws: bool, handler: Option<ActionHandler>) -> &mut Self {
+        self.actions.push(ActionSpec { name, aws, handler });
+        self
+    }
+
+    fn get(&self, name: &str) -> Option<&ActionSpec> {
+        self.actions.iter().find(|a| a.name == name)
+    }
+
+    fn supported(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.actions
+            .iter()
+            .filter(|a| a.handler.is_some())
+            .map(|a| a.name)
+    }
+
+    async fn dispatch(&self, ctx: ActionContext) -> Response {
+        match self.get(&ctx.action) {
+            Some(ActionSpec {
+                handler: Some(handler),
+                ..
+            }) => handler(ctx).await.unwrap_or_else(|MissingParameter(name)| {
+                error_response(
+                    "MissingParameter",
+                    &format!("{} parameter is required", name),
+                )
+            }),
+            // Real SQS actions QLite doesn't handle get UnsupportedOperation so clients can
+            // tell "not implemented" apart from a misspelled action name
+            Some(_) => error_response(
+                "UnsupportedOperation",
+                &format!(
+                    "{} is not supported by QLite. Supported actions: {}",
+                    ctx.action,
+                    self.supported().collect::<Vec<_>>().join(", ")
+                ),
+            ),
+            None => error_response("InvalidAction", &format!("Unknown action: {}", ctx.action)),
         }
-        _ => error_response("InvalidAction", &format!("Unknown action: {}", action)),
     }
 }
 
-async fn handle_list_queues(state: Arc<AppState>) -> Response {
+static ACTION_REGISTRY: LazyLock<ActionRegistry> = LazyLock::new(|| {
+    let mut registry = ActionRegistry {
+        actions: Vec::new(),
+    };
+    registry
+        .unimplemented("AddPermission")
+        .unimplemented("CancelMessageMoveTask")
+        .register("ChangeMessageVisibility", |ctx| async move {
+            let queue_name = ctx.queue_name()?;
+            let receipt_handle = ctx.param("ReceiptHandle")?;
+            let visibility_timeout = ctx.param("VisibilityTimeout")?;
+            Ok(handle_change_message_visibility(
+                ctx.state,
+                &queue_name,
+                &receipt_handle,
+                &visibility_timeout,
+            )
+            .await)
+        })
+        .unimplemented("ChangeMessageVisibilityBatch")
+        .register("CreateQueue", |ctx| async move {
+            let queue_name = ctx.param("QueueName")?;
+            Ok(handle_create_queue(ctx.state, &queue_name, &ctx.params).await)
+        })
+        .register("DeleteMessage", |ctx| async move {
+            let queue_name = ctx.queue_name()?;
+            Ok(handle_delete_message(ctx.state, &queue_name, ctx.params).await)
+        })
+        .register("DeleteMessageBatch", |ctx| async move {
+            // Without a queue path, the batch handler reports a bad QueueUrl per batch
+            Ok(match ctx.path_queue_name {
+                Some(queue_name) if !ctx.params.contains_key("QueueUrl") => {
+                    handle_delete_message_batch_for_queue(ctx.state, &queue_name, ctx.params).await
+                }
+                _ => handle_delete_message_batch(ctx.state, &ctx.params).await,
+            })
+        })
+        .register("DeleteQueue", |ctx| async move {
+            let queue_name = ctx.queue_name()?;
+            Ok(handle_delete_queue(ctx.state, &queue_name, &ctx.params).await)
+        })
+        .register("GetQueueAttributes", |ctx| async move {
+            let queue_name = ctx.queue_name()?;
+            Ok(handle_get_queue_attributes(ctx.state, &queue_name).await)
+        })
+        .register("GetQueueUrl", |ctx| async move {
+            let queue_name = ctx.param("QueueName")?;
+            Ok(handle_get_queue_url(ctx.state, &queue_name).await)
+        })
+        .register("ListDeadLetterSourceQueues", |ctx| async move {
+            let queue_name = ctx.queue_name()?;
+            Ok(handle_list_dead_letter_source_queues(ctx.state, &queue_name).await)
+        })
+        .unimplemented("ListMessageMoveTasks")
+        .register("ListQueueTags", queue_tags_action)
+        .register("ListQueues", |ctx| async move {
+            Ok(handle_list_queues(ctx.state, &ctx.params).await)
+        })
+        .register("PurgeQueue", |ctx| async move {
+            let queue_name = ctx.queue_name()?;
+            Ok(handle_purge_queue(ctx.state, &queue_name).await)
+        })
+        .register("ReceiveMessage", |ctx| async move {
+            simulate_latency(&ctx.state).await;
+            let queue_name = ctx.queue_name()?;
+            Ok(handle_receive_message_enhanced(ctx.state, &queue_name, ctx.params).await)
+        })
+        .extension("ReceiveMessageBatch", |ctx| async move {
+            simulate_latency(&ctx.state).await;
+            let queue_name = ctx.queue_name()?;
+            Ok(handle_receive_message_batch(ctx.state, &queue_name, ctx.params).await)
+        })
+        .unimplemented("RemovePermission")
+        .register("SendMessage", |ctx| async move {
+            simulate_latency(&ctx.state).await;
+            let queue_name = ctx.queue_name()?;
+            Ok(handle_send_message_enhanced(ctx.state, &queue_name, ctx.params).await)
+        })
+        .register("SendMessageBatch", |ctx| async move {
+            simulate_latency(&ctx.state).await;
+            // Without a queue path, the batch handler reports a bad QueueUrl per batch
+            Ok(match ctx.path_queue_name {
+                Some(queue_name) if !ctx.params.contains_key("QueueUrl") => {
+                    handle_send_message_batch_for_queue(ctx.state, &queue_name, ctx.params).await
+                }
+                _ => handle_send_message_batch(ctx.state, &ctx.params).await,
+            })
+        })
+        .register("SetQueueAttributes", |ctx| async move {
+            let queue_name = ctx.queue_name()?;
+            Ok(handle_set_queue_attributes(ctx.state, &queue_name, ctx.params).await)
+        })
+        .register("StartMessageMoveTask", |ctx| async move {
+            let source_arn = ctx.param("SourceArn")?;
+            Ok(handle_start_message_move_task(ctx.state, &source_arn, &ctx.params).await)
+        })
+        .register("TagQueue", queue_tags_action)
+        .register("UntagQueue", queue_tags_action);
+    registry
+});
+
+// Testing aid (`simulate_latency_ms`) for exercising client timeouts locally
+async fn simulate_latency(state: &AppState) {
+    let latency = state.queue_service.simulated_latency();
+    if !latency.is_zero() {
+        tokio::time::sleep(latency).await;
+    }
+}
+
+// GET /actions - what this build supports, for discovery and capability probes
+async fn list_actions() -> Response {
+    let actions: Vec<_> = ACTION_REGISTRY
+        .actions
+        .iter()
+        .map(|a| {
+            let implemented = a.handler.is_some();
+            let endpoints: &[&str] = if implemented {
+                &["/", "/{queue_name}"]
+            } else {
+                &[]
+            };
+            serde_json::json!({
+                "name": a.name,
+                "implemented": implemented,
+                "extension": !a.aws,
+                "endpoints": endpoints,
+            })
+        })
+        .collect();
+
+    (
+        StatusCode::OK,
+        [("Content-Type", "application/json")],
+        serde_json::json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "actions": actions,
+        })
+        .to_string(),
+    )
+        .into_response()
+}
+
+async fn handle_list_queues(state: Arc<AppState>, params: &HashMap<String, String>) -> Response {
+    // IncludeTags is a QLite extension; AWS needs a ListQueueTags call per queue
+    let include_tags = params
+        .get("IncludeTags")
+        .is_some_and(|v| v.eq_ignore_ascii_case("true"));
+
+    if include_tags {
+        return match state.queue_service.list_queues_with_tags(&[]).await {
+            Ok(queues) => {
+                let mut queue_urls = Vec::with_capacity(queues.len());
+                let mut queue_tags = Vec::with_capacity(queues.len());
+                for (name, _, tags) in queues {
+                    let queue_url = format!("{}/{}", state.base_url, name);
+                    queue_urls.push(queue_url.clone());
+                    queue_tags.push(QueueTagSet {
+                        queue_url,
+                        tags: to_queue_tags(tags),
+                    });
+                }
+
+                let response = ListQueuesResponse {
+                    list_queues_result: ListQueuesResult {
+                        queue_urls,
+                        queue_tags: Some(queue_tags),
+                    },
+                };
+                xml_response(response)
+            }
+            Err(_) => error_response("InternalError", "Failed to list queues"),
+        };
+    }
+
     match state.queue_service.list_queues().await {
         Ok(queues) => {
             let queue_urls: Vec<String> = queues
@@ -248,7 +569,10 @@ async fn handle_list_queues(state: Arc<AppState>) -> Response {
                 .collect();
 
             let response = ListQueuesResponse {
-                list_queues_result: ListQueuesResult { queue_urls },
+                list_queues_result: ListQueuesResult {
+                    queue_urls,
+                    queue_tags: None,
+                },
             };
 
             xml_response(response)
@@ -257,9 +581,122 @@ async fn handle_list_queues(state: Arc<AppState>) -> Response {
     }
 }
 
-async fn handle_create_queue(state: Arc<AppState>, queue_name: &str) -> Response {
-    match state.queue_service.create_queue(queue_name).await {
-        Ok(()) => {
+fn to_queue_tags(tags: Vec<(String, String)>) -> Vec<QueueTag> {
+    tags.into_iter()
+        .map(|(key, value)| QueueTag { key, value })
+        .collect()
+}
+
+// Tag.N.Key/Tag.N.Value pairs, as sent to TagQueue and CreateQueue
+fn parse_tags(params: &HashMap<String, String>) -> Vec<(String, String)> {
+    (1..)
+        .map_while(|i| {
+            let key = params.get(&format!("Tag.{}.Key", i))?;
+            let value = params.get(&format!("Tag.{}.Value", i))?;
+            Some((key.clone(), value.clone()))
+        })
+        .collect()
+}
+
+async fn queue_tags_action(ctx: ActionContext) -> ActionResult {
+    let queue_name = ctx.queue_name()?;
+    Ok(handle_queue_tags_action(ctx.state, &ctx.action, &queue_name, &ctx.params).await)
+}
+
+async fn handle_queue_tags_action(
+    state: Arc<AppState>,
+    action: &str,
+    queue_name: &str,
+    params: &HashMap<String, String>,
+) -> Response {
+    let result = match action {
+        "TagQueue" => {
+            let tags = parse_tags(params);
+            if tags.is_empty() {
+                return error_response("MissingParameter", "Tags parameter is required");
+            }
+            state
+                .queue_service
+                .tag_queue(queue_name, tags)
+                .await
+                .map(|found| {
+                    found.then(|| {
+                        xml_response(TagQueueResponse {
+                            tag_queue_result: TagQueueResult {},
+                        })
+                    })
+                })
+        }
+        "UntagQueue" => {
+            let keys: Vec<String> = (1..)
+                .map_while(|i| params.get(&format!("TagKey.{}", i)).cloned())
+                .collect();
+            if keys.is_empty() {
+                return error_response("MissingParameter", "TagKeys parameter is required");
+            }
+            state
+                .queue_service
+                .untag_queue(queue_name, keys)
+                .await
+                .map(|found| {
+                    found.then(|| {
+                        xml_response(UntagQueueResponse {
+                            untag_queue_result: UntagQueueResult {},
+                        })
+                    })
+                })
+        }
+        _ => state
+            .queue_service
+            .list_queue_tags(queue_name)
+            .await
+            .map(|tags| {
+                tags.map(|tags| {
+                    xml_response(ListQueueTagsResponse {
+                        list_queue_tags_result: ListQueueTagsResult {
+                            tags: to_queue_tags(tags),
+                        },
+                    })
+                })
+            }),
+    };
+
+    match result {
+        Ok(Some(response)) => response,
+        Ok(None) => error_response(
+            "AWS.SimpleQueueService.NonExistentQueue",
+            "The specified queue does not exist",
+        ),
+        Err(_) => error_response("InternalError", "Failed to update queue tags"),
+    }
+}
+
+async fn handle_create_queue(
+    state: Arc<AppState>,
+    queue_name: &str,
+    params: &HashMap<String, String>,
+) -> Response {
+    let attributes = parse_queue_attributes(params);
+    match state
+        .queue_service
+        .create_queue_with_attributes(queue_name, &attributes)
+        .await
+    {
+        Ok(creation @ (QueueCreation::Created | QueueCreation::AlreadyExists)) => {
+            let queue_name = &state.queue_service.normalize_queue_name(queue_name);
+            // Tags only apply to a queue this request created, like the attributes
+            let tags = parse_tags(params);
+            if matches!(creation, QueueCreation::Created)
+                && !tags.is_empty()
+                && state
+                    .queue_service
+                    .tag_queue(queue_name, tags)
+                    .await
+                    .is_err()
+            {
+                return error_response("InternalError", "Failed to tag queue");
+            }
+
             let response = CreateQueueResponse {
                 create_queue_result: CreateQueueResult {
                     queue_url: format!("{}/{}", state.base_url, queue_name),
@@ -267,20 +704,22 @@ async fn handle_create_queue(state: Arc<AppState>, queue_name: &str) -> Response
             };
             xml_response(response)
         }
+        Ok(QueueCreation::AttributeConflict { attribute }) => error_response(
+            "QueueAlreadyExists",
+            &format!(
+                "A queue already exists with the same name and a different value for attribute {}",
+                attribute
+            ),
+        ),
+        Ok(QueueCreation::InvalidAttributes { message }) => {
+            error_response("InvalidParameterValue", &message)
+        }
         Err(_) => error_response("InternalError", "Failed to create queue"),
     }
 }
 
-async fn handle_create_queue_with_attributes(
-    state: Arc<AppState>,
-    queue_name: &str,
-    _params: &HashMap<String, String>,
-) -> Response {
-    // For now, just create the queue normally - attributes support can be added later
-    handle_create_queue(state, queue_name).await
-}
-
 async fn handle_get_queue_url(state: Arc<AppState>, queue_name: &str) -> Response {
+    let queue_name = &state.queue_service.normalize_queue_name(queue_name);
     // Check if queue exists by trying to list it
     match state.queue_service.list_queues().await {
         Ok(queues) => {
@@ -302,25 +741,134 @@ async fn handle_get_queue_url(state: Arc<AppState>, queue_name: &str) -> Respons
     }
 }
 
-async fn handle_delete_queue(state: Arc<AppState>, queue_name: &str) -> Response {
-    match state.queue_service.delete_queue(queue_name).await {
-        Ok(true) => {
+// Force=true (a QLite extension) deletes a queue other queues still dead-letter into
+async fn handle_delete_queue(
+    state: Arc<AppState>,
+    queue_name: &str,
+    params: &HashMap<String, String>,
+) -> Response {
+    let force = params.get("Force").is_some_and(|force| force == "true");
+    match state.queue_service.delete_queue(queue_name, force).await {
+        Ok(QueueDeletion::Deleted) => {
             let response = DeleteQueueResponse {
                 delete_queue_result: DeleteQueueResult {},
             };
             xml_response(response)
         }
-        Ok(false) => error_response(
+        Ok(QueueDeletion::NotFound) => error_response(
             "AWS.SimpleQueueService.NonExistentQueue",
             "The specified queue does not exist",
         ),
+        Ok(QueueDeletion::DeadLetterTargetInUse { source_queues }) => error_response(
+            "ResourceInUse",
+            &format!(
+                "Queue {} is the dead-letter queue of {}. Remove their RedrivePolicy first, or pass Force=true to delete it anyway",
+                queue_name,
+                source_queues.join(", ")
+            ),
+        ),
         Err(_) => error_response("InternalError", "Failed to delete queue"),
     }
 }
 
+async fn handle_purge_queue(state: Arc<AppState>, queue_name: &str) -> Response {
+    match state.queue_service.purge_queue(queue_name).await {
+        Ok(PurgeOutcome::Purged { .. }) => xml_response(PurgeQueueResponse {
+            purge_queue_result: PurgeQueueResult {},
+        }),
+        Ok(PurgeOutcome::NotFound) => error_response(
+            "AWS.SimpleQueueService.NonExistentQueue",
+            "The specified queue does not exist",
+        ),
+        Ok(PurgeOutcome::InProgress) => error_response(
+            "PurgeQueueInProgress",
+            &format!(
+                "Only one PurgeQueue operation on {} is allowed every 1 second.",
+                queue_name
+            ),
+        ),
+        Err(_) => error_response("InternalError", "Failed to purge queue"),
+    }
+}
+
+async fn handle_list_dead_letter_source_queues(state: Arc<AppState>, dlq_name: &str) -> Response {
+    match state.queue_service.get_queue_attributes(dlq_name).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return error_response(
+                "AWS.SimpleQueueService.NonExistentQueue",
+                "The specified queue does not exist",
+            );
+        }
+        Err(_) => return error_response("InternalError", "Failed to check queue existence"),
+    }
+
+    match state.queue_service.list_dlq_source_queues(dlq_name).await {
+        Ok(source_queues) => {
+            let queue_urls = source_queues
+                .into_iter()
+                .map(|name| format!("{}/{}", state.base_url, name))
+                .collect();
+
+            let response = ListDeadLetterSourceQueuesResponse {
+                list_dead_letter_source_queues_result: ListDeadLetterSourceQueuesResult {
+                    queue_urls,
+                },
+            };
+            xml_response(response)
+        }
+        Err(_) => error_response("InternalError", "Failed to list dead letter source queues"),
+    }
+}
+
+async fn handle_start_message_move_task(
+    state: Arc<AppState>,
+    source_arn: &str,
+    params: &HashMap<String, String>,
+) -> Response {
+    let source = dlq_name_from_arn(source_arn);
+    let destination = params
+        .get("DestinationArn")
+        .map(|arn| dlq_name_from_arn(arn));
+
+    for queue_name in std::iter::once(source).chain(destination) {
+        match state.queue_service.get_queue_attributes(queue_name).await {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                return error_response(
+                    "AWS.SimpleQueueService.NonExistentQueue",
+                    &format!("The queue {} does not exist", queue_name),
+                );
+            }
+            Err(_) => return error_response("InternalError", "Failed to check queue existence"),
+        }
+    }
+
+    let max_per_second = match params.get("MaxNumberOfMessagesPerSecond") {
+        None => None,
+        Some(value) => match value.parse::<u32>() {
+            Ok(rate) if (1..=500).contains(&rate) => Some(rate),
+            _ => {
+                return error_response(
+                    "InvalidParameterValue",
+                    "MaxNumberOfMessagesPerSecond must be between 1 and 500",
+                );
+            }
+        },
+    };
+
+    let task_handle =
+        state
+            .queue_service
+            .start_message_move_task(source, destination, max_per_second);
+    xml_response(StartMessageMoveTaskResponse {
+        start_message_move_task_result: StartMessageMoveTaskResult { task_handle },
+    })
+}
+
 async fn handle_delete_message(
     state: Arc<AppState>,
-    _queue_name: &str,
+    queue_name: &str,
     params: HashMap<String, String>,
 ) -> Response {
     let receipt_handle = match params.get("ReceiptHandle") {
@@ -328,8 +876,23 @@ async fn handle_delete_message(
         None => return error_response("MissingParameter", "ReceiptHandle parameter is required"),
     };
 
-    match state.queue_service.delete_message(receipt_handle).await {
-        Ok(_) => {
+    match state
+        .queue_service
+        .delete_message_checked(queue_name, receipt_handle)
+        .await
+    {
+        Ok(DeleteOutcome::OutOfOrder { pending }) => {
+            error_response("MessageOutOfOrder", &out_of_order_message(pending))
+        }
+        Ok(DeleteOutcome::StaleHandle) => error_response(
+            "ReceiptHandleIsInvalid",
+            &stale_handle_message(receipt_handle),
+        ),
+        Ok(DeleteOutcome::NotFound) => error_response(
+            "ReceiptHandleIsInvalid",
+            &invalid_handle_message(receipt_handle),
+        ),
+        Ok(DeleteOutcome::Deleted) => {
             let response = DeleteMessageResponse {
                 delete_message_result: DeleteMessageResult {},
             };
@@ -339,6 +902,78 @@ async fn handle_delete_message(
     }
 }
 
+fn out_of_order_message(pending: u32) -> String {
+    format!(
+        "{} earlier message(s) in the message group have not been deleted yet",
+        pending
+    )
+}
+
+fn stale_handle_message(receipt_handle: &str) -> String {
+    format!(
+        "The receipt handle \"{}\" has expired: the message was received again after it was issued. Use the handle from the latest receive.",
+        receipt_handle
+    )
+}
+
+fn invalid_handle_message(receipt_handle: &str) -> String {
+    format!(
+        "The input receipt handle \"{}\" is not valid.",
+        receipt_handle
+    )
+}
+
+async fn handle_change_message_visibility(
+    state: Arc<AppState>,
+    queue_name: &str,
+    receipt_handle: &str,
+    visibility_timeout: &str,
+) -> Response {
+    let visibility_timeout = match visibility_timeout.parse::<u32>() {
+        Ok(timeout) if timeout <= 43_200 => timeout,
+        _ => {
+            return error_response(
+                "InvalidParameterValue",
+                &format!(
+                    "Value {} for parameter VisibilityTimeout is invalid. Reason: Must be between 0 and 43200, if provided.",
+                    visibility_timeout
+                ),
+            );
+        }
+    };
+
+    match state
+        .queue_service
+        .change_message_visibility(queue_name, receipt_handle, visibility_timeout)
+        .await
+    {
+        Ok(VisibilityChange::Changed) => xml_response(ChangeMessageVisibilityResponse {
+            change_message_visibility_result: ChangeMessageVisibilityResult {},
+        }),
+        Ok(VisibilityChange::NotFound) => error_response(
+            "ReceiptHandleIsInvalid",
+            &invalid_handle_message(receipt_handle),
+        ),
+        Ok(VisibilityChange::StaleHandle) => error_response(
+            "ReceiptHandleIsInvalid",
+            &stale_handle_message(receipt_handle),
+        ),
+        Ok(VisibilityChange::NotInFlight) => error_response(
+            "MessageNotInflight",
+            "The message referred to isn't in flight.",
+        ),
+        Ok(VisibilityChange::ExceedsCeiling) => error_response(
+            "InvalidParameterValue",
+            &format!(
+                "Value {} for parameter VisibilityTimeout is invalid. Reason: Total VisibilityTimeout for the message is beyond the limit [{} seconds]",
+                visibility_timeout,
+                state.queue_service.max_visibility_extension_seconds()
+            ),
+        ),
+        Err(_) => error_response("InternalError", "Failed to change message visibility"),
+    }
+}
+
 async fn handle_get_queue_attributes(state: Arc<AppState>, queue_name: &str) -> Response {
     match state.queue_service.get_queue_attributes(queue_name).await {
         Ok(Some(attrs)) => {
@@ -351,6 +986,10 @@ async fn handle_get_queue_attributes(state: Arc<AppState>, queue_name: &str) ->
                     name: "ApproximateNumberOfMessagesNotVisible".to_string(),
                     value: attrs.approximate_number_of_messages_not_visible.to_string(),
                 },
+                QueueAttribute {
+                    name: "ApproximateNumberOfMessagesDelayed".to_string(),
+                    value: attrs.approximate_number_of_messages_delayed.to_string(),
+                },
                 QueueAttribute {
                     name: "CreatedTimestamp".to_string(),
                     value: attrs.created_timestamp,
@@ -382,22 +1021,39 @@ async fn handle_send_message_enhanced(
         Some(body) => body,
         None => return error_response("MissingParameter", "MessageBody parameter is required"),
     };
+    if let Err(e) = validate_message_body(message_body) {
+        return error_response("InvalidMessageContents", &e);
+    }
 
     let message_attributes = parse_message_attributes(&params);
-    let deduplication_id = params.get("MessageDeduplicationId").cloned();
+    if let Some(attributes) = &message_attributes
+        && let Err(e) = validate_message_attributes(attributes)
+    {
+        return error_response("InvalidParameterValue", &e);
+    }
+    let md5_of_message_attributes = message_attributes
+        .as_ref()
+        .and_then(md5_of_message_attributes);
+    // ClientRequestToken gives standard queues idempotent sends, stored like a dedup id
+    let deduplication_id = params
+        .get("MessageDeduplicationId")
+        .or_else(|| params.get("ClientRequestToken"))
+        .cloned();
+    // Left unset so the queue's DelaySeconds applies
     let delay_seconds = params
         .get("DelaySeconds")
-        .and_then(|s| s.parse::<u32>().ok())
-        .unwrap_or(0);
+        .and_then(|s| s.parse::<u32>().ok());
+    let message_group_id = params.get("MessageGroupId").cloned();
 
     match state
         .queue_service
-        .send_message_enhanced(
+        .send_message_enhanced_with_group(
             queue_name,
             message_body,
             message_attributes,
             deduplication_id,
             delay_seconds,
+            message_group_id,
         )
         .await
     {
@@ -406,10 +1062,14 @@ async fn handle_send_message_enhanced(
                 send_message_result: SendMessageResult {
                     message_id,
                     md5_of_body: format!("{:x}", md5::compute(message_body)),
+                    md5_of_message_attributes,
                 },
             };
             xml_response(response)
         }
+        Err(tokio_rusqlite::Error::Other(err)) if err.is::<MissingMessageGroupId>() => {
+            error_response("MissingParameter", &err.to_string())
+        }
         Err(err) => {
             eprintln!("SendMessage error: {:?}", err);
             error_response("InternalError", "Failed to send message")
@@ -417,6 +1077,27 @@ async fn handle_send_message_enhanced(
     }
 }
 
+// Reject receives while another consumer holds the queue's exclusive lock.
+// The holder passes its lease token as the LockToken parameter (QLite extension).
+async fn check_receive_lock(
+    state: &AppState,
+    queue_name: &str,
+    params: &HashMap<String, String>,
+) -> Option<Response> {
+    let token = params.get("LockToken").map(String::as_str);
+    if state.queue_service.receive_allowed(queue_name, token).await {
+        None
+    } else {
+        Some(error_response(
+            "OverLimit",
+            "Queue is locked for exclusive receive by another consumer",
+        ))
+    }
+}
+
+// ReceiveMessage: long polls through receive_messages_with_minimum, which claims one
+// message per database call (Database::receive_next_message). Like the batch path, it
+// moves messages past maxReceiveCount to the DLQ instead of returning them.
 async fn handle_receive_message_enhanced(
     state: Arc<AppState>,
     queue_name: &str,
@@ -432,15 +1113,35 @@ async fn handle_receive_message_enhanced(
         .and_then(|s| s.parse::<u32>().ok())
         .unwrap_or(0);
 
+    if let Some(response) = check_receive_lock(&state, queue_name, &params).await {
+        return response;
+    }
+
+    // QLite extension: wait for at least this many messages (AWS behavior is 1)
+    let min_messages = params
+        .get("MinNumberOfMessages")
+        .and_then(|s| s.parse::<u32>().ok())
+        .unwrap_or(1);
+
+    // QLite extension: identifies the receiver in the delivery log
+    let consumer_id = params.get("ConsumerId").map(String::as_str);
+
     match state
         .queue_service
-        .receive_messages_enhanced(queue_name, max_messages, wait_time_seconds)
+        .receive_messages_as(
+            queue_name,
+            max_messages,
+            wait_time_seconds,
+            min_messages,
+            consumer_id,
+        )
         .await
     {
         Ok(messages) => {
             let sqs_messages: Vec<SqsMessage> = messages
                 .into_iter()
                 .map(|received_msg| {
+                    let system_attributes = create_basic_system_attributes(&received_msg);
                     let mut message_attributes = HashMap::new();
                     if let Some(attrs) = received_msg.attributes {
                         for (key, value) in attrs {
@@ -456,14 +1157,15 @@ async fn handle_receive_message_enhanced(
                     }
 
                     SqsMessage {
+                        attributes: system_attributes,
                         message_id: received_msg.id,
                         receipt_handle: received_msg.receipt_handle,
                         body: received_msg.body,
-                        attributes: create_basic_system_attributes(),
                         message_attributes,
                     }
                 })
                 .collect();
+            let sqs_messages = apply_attribute_selectors(sqs_messages, &params);
 
             let response = ReceiveMessageResponse {
                 receive_message_result: ReceiveMessageResult {
@@ -477,12 +1179,8 @@ async fn handle_receive_message_enhanced(
     }
 }
 
-async fn handle_set_queue_attributes(
-    state: Arc<AppState>,
-    queue_name: &str,
-    params: HashMap<String, String>,
-) -> Response {
-    // Parse queue attributes from parameters
+// Queue attributes given as Attribute.N.Name / Attribute.N.Value pairs
+fn parse_queue_attributes(params: &HashMap<String, String>) -> HashMap<String, String> {
     let mut attributes = HashMap::new();
 
     for (key, value) in params.iter() {
@@ -499,6 +1197,16 @@ async fn handle_set_queue_attributes(
         }
     }
 
+    attributes
+}
+
+async fn handle_set_queue_attributes(
+    state: Arc<AppState>,
+    queue_name: &str,
+    params: HashMap<String, String>,
+) -> Response {
+    let attributes = parse_queue_attributes(&params);
+
     match state
         .queue_service
         .set_queue_attributes(queue_name, attributes)
@@ -541,8 +1249,7 @@ async fn handle_send_message_batch(
         }
     };
 
-    // Extract queue name from URL (format: http://localhost:3000/queue-name)
-    let queue_name = queue_url.split('/').next_back().unwrap_or("");
+    let queue_name = queue_name_from_url(queue_url);
     if queue_name.is_empty() {
         let error_response = BatchResultErrorEntry {
             id: "1".to_string(),
@@ -569,9 +1276,22 @@ async fn handle_send_message_batch_for_queue(
     queue_name: &str,
     params: HashMap<String, String>,
 ) -> Response {
+    if let Some(too_many) = check_batch_entry_count(&state, &params, "SendMessageBatchRequestEntry")
+    {
+        return too_many;
+    }
+
+    // Entries without DelaySeconds get the queue's delay, as single sends do
+    let Ok(default_delay_seconds) = state.queue_service.default_delay_seconds(queue_name).await
+    else {
+        return error_response("InternalError", "Failed to load queue configuration");
+    };
+
     // Parse batch entries
     let mut entries = Vec::new();
     let mut entry_ids = Vec::new();
+    let mut rejected = Vec::new();
+    let mut payload_size = 0;
     let mut i = 1;
 
     loop {
@@ -579,14 +1299,16 @@ async fn handle_send_message_batch_for_queue(
         let body_key = format!("SendMessageBatchRequestEntry.{}.MessageBody", i);
         let delay_key = format!("SendMessageBatchRequestEntry.{}.DelaySeconds", i);
         let dedup_key = format!("SendMessageBatchRequestEntry.{}.MessageDeduplicationId", i);
+        let group_key = format!("SendMessageBatchRequestEntry.{}.MessageGroupId", i);
 
         if let (Some(id), Some(body)) = (params.get(&id_key), params.get(&body_key)) {
             let delay_seconds = params
                 .get(&delay_key)
                 .and_then(|s| s.parse::<u32>().ok())
-                .unwrap_or(0);
+                .unwrap_or(default_delay_seconds);
 
             let deduplication_id = params.get(&dedup_key).cloned();
+            let message_group_id = params.get(&group_key).cloned();
 
             // Parse message attributes if present
             let mut attributes = std::collections::HashMap::new();
@@ -600,6 +1322,10 @@ async fn handle_send_message_batch_for_queue(
                     "SendMessageBatchRequestEntry.{}.MessageAttribute.{}.Value.StringValue",
                     i, attr_index
                 );
+                let attr_type_key = format!(
+                    "SendMessageBatchRequestEntry.{}.MessageAttribute.{}.Value.DataType",
+                    i, attr_index
+                );
 
                 if let (Some(attr_name), Some(attr_value)) =
                     (params.get(&attr_name_key), params.get(&attr_value_key))
@@ -609,7 +1335,10 @@ async fn handle_send_message_batch_for_queue(
                         MessageAttributeValue {
                             string_value: Some(attr_value.clone()),
                             binary_value: None,
-                            data_type: "String".to_string(),
+                            data_type: params
+                                .get(&attr_type_key)
+                                .cloned()
+                                .unwrap_or_else(|| "String".to_string()),
                         },
                     );
                     attr_index += 1;
@@ -618,34 +1347,81 @@ async fn handle_send_message_batch_for_queue(
                 }
             }
 
+            payload_size += body.len() + message_attributes_size(&attributes);
+
             let attributes = if attributes.is_empty() {
                 None
             } else {
                 Some(attributes)
             };
-            let message_id = uuid::Uuid::new_v4().to_string();
-
-            entries.push((
-                queue_name.to_string(),
-                message_id.clone(),
-                body.clone(),
-                attributes,
-                deduplication_id,
-                delay_seconds,
-            ));
-
-            entry_ids.push((id.clone(), message_id, body.clone()));
-            i += 1;
 
-            if i > 10 {
-                // AWS limit
-                break;
+            if let Err(e) = validate_message_body(body) {
+                rejected.push(BatchResultErrorEntry {
+                    id: id.clone(),
+                    code: "InvalidMessageContents".to_string(),
+                    message: e,
+                    sender_fault: true,
+                });
+            } else if let Some(Err(e)) = attributes.as_ref().map(validate_message_attributes) {
+                rejected.push(BatchResultErrorEntry {
+                    id: id.clone(),
+                    code: "InvalidParameterValue".to_string(),
+                    message: e,
+                    sender_fault: true,
+                });
+            } else {
+                let message_id = state.queue_service.id_generator().generate(queue_name);
+                let attributes_md5 = attributes.as_ref().and_then(md5_of_message_attributes);
+
+                entries.push((
+                    queue_name.to_string(),
+                    message_id.clone(),
+                    body.clone(),
+                    attributes,
+                    deduplication_id,
+                    delay_seconds,
+                    message_group_id,
+                ));
+
+                entry_ids.push((id.clone(), body.clone(), attributes_md5));
             }
+            i += 1;
         } else {
             break;
         }
     }
 
+    // Checked across all entries before anything is stored, so the batch fails as a whole
+    if payload_size > MAX_BATCH_PAYLOAD_SIZE {
+        return error_response(
+            "BatchRequestTooLong",
+            &format!(
+                "Batch requests total {} bytes, exceeding the {} byte limit",
+                payload_size, MAX_BATCH_PAYLOAD_SIZE
+            ),
+        );
+    }
+
+    // In all-or-nothing mode one bad entry fails the request before anything is stored
+    if state.queue_service.batch_mode() == BatchMode::AllOrNothing
+        && let Some(entry) = rejected.first()
+    {
+        return error_response(
+            &entry.code,
+            &format!("Batch entry '{}': {}", entry.id, entry.message),
+        );
+    }
+
+    if entries.is_empty() && !rejected.is_empty() {
+        let response = SendMessageBatchResponse {
+            send_message_batch_result: SendMessageBatchResult {
+                successful: vec![],
+                failed: rejected,
+            },
+        };
+        return xml_response(response);
+    }
+
     if entries.is_empty() {
         let error_response = BatchResultErrorEntry {
             id: "1".to_string(),
@@ -665,27 +1441,43 @@ async fn handle_send_message_batch_for_queue(
 
     // Use the new batch service method
     match state.queue_service.send_messages_batch(entries).await {
+        Ok(results)
+            if state.queue_service.batch_mode() == BatchMode::AllOrNothing
+                && results.iter().any(|r| r.is_err()) =>
+        {
+            error_response(
+                "InternalError",
+                "Failed to send batch messages; nothing was stored",
+            )
+        }
         Ok(results) => {
             let mut successful = Vec::new();
-            let mut failed = Vec::new();
+            let mut failed = rejected;
 
             for (i, result) in results.into_iter().enumerate() {
-                let (entry_id, message_id, body) = &entry_ids[i];
+                let (entry_id, body, attributes_md5) = &entry_ids[i];
 
                 match result {
-                    Ok(_) => {
+                    Ok(message_id) => {
                         successful.push(SendMessageBatchResultEntry {
                             id: entry_id.clone(),
-                            message_id: message_id.clone(),
+                            message_id,
                             md5_of_body: format!("{:x}", md5::compute(body.as_bytes())),
+                            md5_of_message_attributes: attributes_md5.clone(),
                         });
                     }
                     Err(error) => {
+                        let sender_fault = error == MissingMessageGroupId.to_string();
                         failed.push(BatchResultErrorEntry {
                             id: entry_id.clone(),
-                            code: "InternalError".to_string(),
+                            code: if sender_fault {
+                                "MissingParameter"
+                            } else {
+                                "InternalError"
+                            }
+                            .to_string(),
                             message: error,
-                            sender_fault: false,
+                            sender_fault,
                         });
                     }
                 }
@@ -740,8 +1532,7 @@ async fn handle_delete_message_batch(
         }
     };
 
-    // Extract queue name from URL
-    let queue_name = queue_url.split('/').next_back().unwrap_or("");
+    let queue_name = queue_name_from_url(queue_url);
     if queue_name.is_empty() {
         let error_response = BatchResultErrorEntry {
             id: "1".to_string(),
@@ -763,11 +1554,46 @@ async fn handle_delete_message_batch(
     handle_delete_message_batch_for_queue(state, queue_name, params.clone()).await
 }
 
+// Batches are counted before any entry is parsed, so one over the limit fails as a whole
+// instead of silently dropping the entries past it
+fn check_batch_entry_count(
+    state: &AppState,
+    params: &HashMap<String, String>,
+    prefix: &str,
+) -> Option<Response> {
+    let count = params
+        .keys()
+        .filter(|key| {
+            key.strip_prefix(prefix)
+                .and_then(|rest| rest.strip_prefix('.'))
+                .and_then(|rest| rest.strip_suffix(".Id"))
+                .is_some_and(|index| index.parse::<u32>().is_ok())
+        })
+        .count();
+    let limit = state.queue_service.max_batch_entries();
+
+    (count > limit).then(|| {
+        error_response(
+            "TooManyEntriesInBatchRequest",
+            &format!(
+                "Maximum number of entries per request are {}. You have sent {}.",
+                limit, count
+            ),
+        )
+    })
+}
+
 async fn handle_delete_message_batch_for_queue(
     state: Arc<AppState>,
-    _queue_name: &str,
+    queue_name: &str,
     params: HashMap<String, String>,
 ) -> Response {
+    if let Some(too_many) =
+        check_batch_entry_count(&state, &params, "DeleteMessageBatchRequestEntry")
+    {
+        return too_many;
+    }
+
     let mut entries = Vec::new();
     let mut entry_ids = Vec::new();
     let mut i = 1;
@@ -779,13 +1605,8 @@ async fn handle_delete_message_batch_for_queue(
 
         if let (Some(id), Some(receipt_handle)) = (params.get(&id_key), params.get(&receipt_key)) {
             entries.push(receipt_handle.clone());
-            entry_ids.push(id.clone());
+            entry_ids.push((id.clone(), receipt_handle.clone()));
             i += 1;
-
-            if i > 10 {
-                // AWS limit
-                break;
-            }
         } else {
             break;
         }
@@ -809,25 +1630,45 @@ async fn handle_delete_message_batch_for_queue(
     }
 
     // Use the batch delete service method
-    match state.queue_service.delete_messages_batch(entries).await {
+    match state
+        .queue_service
+        .delete_messages_batch(queue_name, entries)
+        .await
+    {
         Ok(results) => {
             let mut successful = Vec::new();
             let mut failed = Vec::new();
 
             for (i, result) in results.into_iter().enumerate() {
-                let entry_id = &entry_ids[i];
+                let (entry_id, receipt_handle) = &entry_ids[i];
 
                 match result {
-                    Ok(true) => {
+                    Ok(DeleteOutcome::Deleted) => {
                         successful.push(DeleteMessageBatchResultEntry {
                             id: entry_id.clone(),
                         });
                     }
-                    Ok(false) => {
+                    Ok(DeleteOutcome::OutOfOrder { pending }) => {
+                        failed.push(BatchResultErrorEntry {
+                            id: entry_id.clone(),
+                            code: "MessageOutOfOrder".to_string(),
+                            message: out_of_order_message(pending),
+                            sender_fault: true,
+                        });
+                    }
+                    Ok(DeleteOutcome::StaleHandle) => {
                         failed.push(BatchResultErrorEntry {
                             id: entry_id.clone(),
                             code: "ReceiptHandleIsInvalid".to_string(),
-                            message: "The receipt handle provided is not valid".to_string(),
+                            message: stale_handle_message(receipt_handle),
+                            sender_fault: true,
+                        });
+                    }
+                    Ok(DeleteOutcome::NotFound) => {
+                        failed.push(BatchResultErrorEntry {
+                            id: entry_id.clone(),
+                            code: "ReceiptHandleIsInvalid".to_string(),
+                            message: invalid_handle_message(receipt_handle),
                             sender_fault: true,
                         });
                     }
@@ -866,6 +1707,8 @@ async fn handle_delete_message_batch_for_queue(
     }
 }
 
+// ReceiveMessageBatch (QLite extension): no long polling; claims up to
+// MaxNumberOfMessages in one database call (Database::receive_messages_batch)
 async fn handle_receive_message_batch(
     state: Arc<AppState>,
     queue_name: &str,
@@ -883,6 +1726,10 @@ async fn handle_receive_message_batch(
         .and_then(|s| s.parse::<u32>().ok())
         .unwrap_or(0);
 
+    if let Some(response) = check_receive_lock(&state, queue_name, &params).await {
+        return response;
+    }
+
     // For now, use the batch receive method (ignore wait_time_seconds until Phase 5)
     match state
         .queue_service
@@ -893,10 +1740,10 @@ async fn handle_receive_message_batch(
             let messages_xml: Vec<SqsMessage> = messages
                 .into_iter()
                 .map(|msg| SqsMessage {
+                    attributes: create_basic_system_attributes(&msg),
                     message_id: msg.id.clone(),
-                    receipt_handle: msg.id, // For now, receipt handle is the same as message ID
+                    receipt_handle: msg.receipt_handle,
                     body: msg.body,
-                    attributes: create_basic_system_attributes(),
                     message_attributes: msg
                         .attributes
                         .unwrap_or_default()
@@ -914,6 +1761,7 @@ async fn handle_receive_message_batch(
                         .collect(),
                 })
                 .collect();
+            let messages_xml = apply_attribute_selectors(messages_xml, &params);
 
             let response = ReceiveMessageResponse {
                 receive_message_result: ReceiveMessageResult {
@@ -957,6 +1805,21 @@ fn parse_json_params(body: &str) -> Result<HashMap<String, String>, ()> {
                                     let entry_num = i + 1;
                                     if let serde_json::Value::Object(entry_obj) = entry {
                                         for (entry_key, entry_value) in entry_obj {
+                                            if entry_key == "MessageAttributes"
+                                                && let serde_json::Value::Object(attributes) =
+                                                    entry_value
+                                            {
+                                                flatten_message_attributes(
+                                                    &mut params,
+                                                    &format!(
+                                                        "SendMessageBatchRequestEntry.{}.",
+                                                        entry_num
+                                                    ),
+                                                    attributes.clone(),
+                                                );
+                                                continue;
+                                            }
+
                                             // Determine prefix based on action type - we'll check headers context
                                             let param_key = format!(
                                                 "SendMessageBatchRequestEntry.{}.{}",
@@ -984,6 +1847,47 @@ fn parse_json_params(body: &str) -> Result<HashMap<String, String>, ()> {
                                 }
                             }
                         }
+                        // TagQueue sends a map (CreateQueue's is lowercase "tags") and
+                        // UntagQueue a list; flatten like the query protocol
+                        "Tags" | "tags" => {
+                            if let serde_json::Value::Object(tags) = value {
+                                for (i, (tag_key, tag_value)) in tags.into_iter().enumerate() {
+                                    params.insert(format!("Tag.{}.Key", i + 1), tag_key);
+                                    let tag_value = match tag_value {
+                                        serde_json::Value::String(s) => s,
+                                        other => other.to_string(),
+                                    };
+                                    params.insert(format!("Tag.{}.Value", i + 1), tag_value);
+                                }
+                            }
+                        }
+                        "MessageAttributes" => {
+                            if let serde_json::Value::Object(attributes) = value {
+                                flatten_message_attributes(&mut params, "", attributes);
+                            }
+                        }
+                        // Receive selectors arrive as arrays; index them like AttributeName.N
+                        "AttributeNames"
+                        | "MessageAttributeNames"
+                        | "MessageSystemAttributeNames" => {
+                            if let serde_json::Value::Array(names) = value {
+                                let prefix = key.trim_end_matches('s');
+                                for (i, name) in names.into_iter().enumerate() {
+                                    if let serde_json::Value::String(s) = name {
+                                        params.insert(format!("{}.{}", prefix, i + 1), s);
+                                    }
+                                }
+                            }
+                        }
+                        "TagKeys" => {
+                            if let serde_json::Value::Array(keys) = value {
+                                for (i, tag_key) in keys.into_iter().enumerate() {
+                                    if let serde_json::Value::String(s) = tag_key {
+                                        params.insert(format!("TagKey.{}", i + 1), s);
+                                    }
+                                }
+                            }
+                        }
                         _ => {
                             let value_str = match value {
                                 serde_json::Value::String(s) => s,
@@ -1002,15 +1906,50 @@ fn parse_json_params(body: &str) -> Result<HashMap<String, String>, ()> {
     }
 }
 
-fn create_basic_system_attributes() -> HashMap<String, String> {
+// Flatten a JSON-protocol MessageAttributes object, {"name": {"DataType": .., "StringValue": ..}},
+// into the indexed `MessageAttribute.N.*` params of the query protocol
+fn flatten_message_attributes(
+    params: &mut HashMap<String, String>,
+    prefix: &str,
+    attributes: serde_json::Map<String, serde_json::Value>,
+) {
+    for (i, (name, attribute)) in attributes.into_iter().enumerate() {
+        let key = format!("{}MessageAttribute.{}", prefix, i + 1);
+        params.insert(format!("{}.Name", key), name);
+        if let serde_json::Value::Object(fields) = attribute {
+            for (field, value) in fields {
+                if let serde_json::Value::String(value) = value {
+                    params.insert(format!("{}.Value.{}", key, field), value);
+                }
+            }
+        }
+    }
+}
+
+fn create_basic_system_attributes(message: &ReceivedMessage) -> HashMap<String, String> {
     let mut system_attrs = HashMap::new();
 
-    // SentTimestamp - when message was sent (use current time as approximation)
-    let sent_timestamp = chrono::Utc::now().timestamp_millis().to_string();
+    // SentTimestamp - when the message was originally sent, in epoch milliseconds
+    let sent_timestamp = message
+        .sent_timestamp
+        .unwrap_or_else(chrono::Utc::now)
+        .timestamp_millis()
+        .to_string();
     system_attrs.insert("SentTimestamp".to_string(), sent_timestamp);
 
-    // ApproximateReceiveCount - start with 1 (would be updated from database in real implementation)
-    system_attrs.insert("ApproximateReceiveCount".to_string(), "1".to_string());
+    // ApproximateReceiveCount - deliveries so far, including this one
+    system_attrs.insert(
+        "ApproximateReceiveCount".to_string(),
+        message.receive_count.to_string(),
+    );
+
+    // ApproximateFirstReceiveTimestamp - when the message was first received, in epoch milliseconds
+    if let Some(first_receive) = message.first_receive_timestamp {
+        system_attrs.insert(
+            "ApproximateFirstReceiveTimestamp".to_string(),
+            first_receive.timestamp_millis().to_string(),
+        );
+    }
 
     // SenderId - dummy value for compatibility
     system_attrs.insert("SenderId".to_string(), "AIDAIENQZJOLO23YVJ4VO".to_string());
@@ -1018,6 +1957,45 @@ fn create_basic_system_attributes() -> HashMap<String, String> {
     system_attrs
 }
 
+// Read an indexed selector list such as AttributeName.1, AttributeName.2, ...
+fn selector_names(params: &HashMap<String, String>, prefix: &str) -> Vec<String> {
+    (1..)
+        .map_while(|i| params.get(&format!("{}.{}", prefix, i)).cloned())
+        .collect()
+}
+
+// "All" and ".*" select everything; "prefix.*" selects names starting with "prefix."
+fn selector_matches(names: &[String], name: &str) -> bool {
+    names.iter().any(|selector| match selector.as_str() {
+        "All" | ".*" => true,
+        _ => match selector.strip_suffix('*') {
+            Some(prefix) if prefix.ends_with('.') => name.starts_with(prefix),
+            _ => selector == name,
+        },
+    })
+}
+
+// Drop attributes a receive didn't ask for. As in AWS, system and message attributes are
+// only returned when AttributeName.N / MessageAttributeName.N select them.
+fn apply_attribute_selectors(
+    mut messages: Vec<SqsMessage>,
+    params: &HashMap<String, String>,
+) -> Vec<SqsMessage> {
+    let mut system_names = selector_names(params, "AttributeName");
+    system_names.extend(selector_names(params, "MessageSystemAttributeName"));
+    let message_names = selector_names(params, "MessageAttributeName");
+
+    for message in &mut messages {
+        message
+            .attributes
+            .retain(|name, _| selector_matches(&system_names, name));
+        message
+            .message_attributes
+            .retain(|name, _| selector_matches(&message_names, name));
+    }
+    messages
+}
+
 fn parse_message_attributes(
     params: &HashMap<String, String>,
 ) -> Option<HashMap<String, MessageAttributeValue>> {
@@ -1066,7 +2044,10 @@ fn xml_response<T: serde::Serialize>(data: T) -> Response {
             )
                 .into_response()
         }
-        Err(_) => error_response("InternalError", "Failed to serialize response"),
+        Err(e) => {
+            tracing::error!("Failed to serialize XML response: {}", e);
+            error_response("InternalError", "Failed to serialize response")
+        }
     }
 }
 
@@ -1122,9 +2103,14 @@ fn get_aws_sqs_error_details(code: &str) -> (StatusCode, String) {
         "UnsupportedOperation" => (StatusCode::BAD_REQUEST, "Sender".to_string()),
         "InvalidIdFormat" => (StatusCode::BAD_REQUEST, "Sender".to_string()),
         "MissingAction" => (StatusCode::BAD_REQUEST, "Sender".to_string()),
+        "ReceiptHandleIsInvalid" => (StatusCode::BAD_REQUEST, "Sender".to_string()),
+        "MessageNotInflight" => (StatusCode::BAD_REQUEST, "Sender".to_string()),
+        "MessageOutOfOrder" => (StatusCode::BAD_REQUEST, "Sender".to_string()),
+        "ResourceInUse" => (StatusCode::BAD_REQUEST, "Sender".to_string()),
 
         // 403 Forbidden errors
         "AccessDenied" => (StatusCode::FORBIDDEN, "Sender".to_string()),
+        "OverLimit" => (StatusCode::FORBIDDEN, "Sender".to_string()),
         "InvalidSecurity" => (StatusCode::FORBIDDEN, "Sender".to_string()),
         "RequestExpired" => (StatusCode::FORBIDDEN, "Sender".to_string()),
 
@@ -1169,6 +2155,7 @@ async fn health_check(State(state): State<Arc<AppState>>) -> Response {
         "version": env!("CARGO_PKG_VERSION"),
         "checks": {
             "database": health_status.database_ok,
+            "schema_ready": health_status.schema_ready,
             "queues": health_status.queue_count,
             "retention_service": health_status.retention_active
         }
@@ -1189,19 +2176,38 @@ async fn health_check(State(state): State<Arc<AppState>>) -> Response {
 }
 
 async fn readiness_check(State(state): State<Arc<AppState>>) -> Response {
+    // Not ready until the schema and all indexes have been applied
+    if !state.queue_service.schema_ready() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [("Content-Type", "application/json")],
+            serde_json::json!({
+                "status": "not ready",
+                "reason": "schema not ready",
+                "schema_ready": false
+            })
+            .to_string(),
+        )
+            .into_response();
+    }
+
     // Check if the service is ready to handle requests
     match state.queue_service.list_queues().await {
         Ok(_) => (
             StatusCode::OK,
             [("Content-Type", "application/json")],
-            serde_json::json!({"status": "ready"}).to_string(),
+            serde_json::json!({"status": "ready", "schema_ready": true}).to_string(),
         )
             .into_response(),
         Err(_) => (
             StatusCode::SERVICE_UNAVAILABLE,
             [("Content-Type", "application/json")],
-            serde_json::json!({"status": "not ready", "reason": "database unavailable"})
-                .to_string(),
+            serde_json::json!({
+                "status": "not ready",
+                "reason": "database unavailable",
+                "schema_ready": true
+            })
+            .to_string(),
         )
             .into_response(),
     }
@@ -1224,7 +2230,7 @@ async fn liveness_check() -> Response {
 async fn metrics_endpoint(State(state): State<Arc<AppState>>) -> Response {
     let health_status = get_system_health(&state.queue_service).await;
 
-    let metrics = format!(
+    let mut metrics = format!(
         "# HELP qlite_queues_total Total number of queues\n\
          # TYPE qlite_queues_total gauge\n\
          qlite_queues_total {}\n\
@@ -1243,6 +2249,58 @@ async fn metrics_endpoint(State(state): State<Arc<AppState>>) -> Response {
         if health_status.retention_active { 1 } else { 0 }
     );
 
+    if let Ok(counters) = state.queue_service.get_queue_counters().await {
+        metrics.push_str(
+            "# HELP qlite_queue_messages Messages per queue and status (maintained counters)\n\
+             # TYPE qlite_queue_messages gauge\n",
+        );
+        for (queue_name, status, count) in counters {
+            metrics.push_str(&format!(
+                "qlite_queue_messages{{queue=\"{}\",status=\"{}\"}} {}\n",
+                queue_name, status, count
+            ));
+        }
+    }
+
+    if let Ok(stats) = state.queue_service.get_inflight_age_stats().await {
+        metrics.push_str(
+            "# HELP qlite_inflight_message_age_seconds Time since in-flight messages were received\n\
+             # TYPE qlite_inflight_message_age_seconds gauge\n",
+        );
+        for stat in stats {
+            metrics.push_str(&format!(
+                "qlite_inflight_message_age_seconds{{queue=\"{0}\",stat=\"max\"}} {1:.3}\n\
+                 qlite_inflight_message_age_seconds{{queue=\"{0}\",stat=\"sum\"}} {2:.3}\n\
+                 qlite_inflight_message_age_seconds{{queue=\"{0}\",stat=\"count\"}} {3}\n",
+                stat.queue_name, stat.max_age_seconds, stat.sum_age_seconds, stat.count
+            ));
+        }
+    }
+
+    metrics.push_str(&format!(
+        "# HELP qlite_wal_size_bytes Size of the SQLite write-ahead log file\n\
+         # TYPE qlite_wal_size_bytes gauge\n\
+         qlite_wal_size_bytes {}\n",
+        state.queue_service.wal_size_bytes()
+    ));
+
+    if let Ok(storage) = state.queue_service.storage_stats().await {
+        metrics.push_str(&format!(
+            "# HELP qlite_db_size_bytes Size of the SQLite database file\n\
+             # TYPE qlite_db_size_bytes gauge\n\
+             qlite_db_size_bytes {}\n\
+             # HELP qlite_db_page_count Pages in the SQLite database\n\
+             # TYPE qlite_db_page_count gauge\n\
+             qlite_db_page_count {}\n\
+             # HELP qlite_db_freelist_count Unused pages that VACUUM would reclaim\n\
+             # TYPE qlite_db_freelist_count gauge\n\
+             qlite_db_freelist_count {}\n",
+            storage.size_bytes, storage.page_count, storage.freelist_count
+        ));
+    }
+
+    metrics.push_str(&state.queue_service.metrics().render());
+
     (StatusCode::OK, [("Content-Type", "text/plain")], metrics).into_response()
 }
 
@@ -1250,12 +2308,14 @@ async fn metrics_endpoint(State(state): State<Arc<AppState>>) -> Response {
 struct SystemHealth {
     status: String,
     database_ok: bool,
+    schema_ready: bool,
     queue_count: usize,
     retention_active: bool,
 }
 
 async fn get_system_health(queue_service: &QueueService) -> SystemHealth {
     let database_ok = (queue_service.list_queues().await).is_ok();
+    let schema_ready = queue_service.schema_ready();
 
     let queue_count = match queue_service.list_queues().await {
         Ok(queues) => queues.len(),
@@ -1264,11 +2324,17 @@ async fn get_system_health(queue_service: &QueueService) -> SystemHealth {
 
     let retention_active = true; // Assume retention service is active if server is running
 
-    let status = if database_ok { "healthy" } else { "unhealthy" }.to_string();
+    let status = if database_ok && schema_ready {
+        "healthy"
+    } else {
+        "unhealthy"
+    }
+    .to_string();
 
     SystemHealth {
         status,
         database_ok,
+        schema_ready,
         queue_count,
         retention_active,
     }
@@ -1,17 +1,29 @@
+pub mod admin;
+pub mod alerts;
+pub mod api;
 pub mod config;
 pub mod database;
 pub mod http_server;
 pub mod message;
+pub mod metrics;
 pub mod queue_service;
 pub mod retention;
 pub mod sqs_types;
+#[cfg(feature = "ui")]
 pub mod ui;
+pub mod webhooks;
 
+pub use admin::*;
+pub use alerts::*;
+pub use api::*;
 pub use config::*;
 pub use database::*;
 pub use http_server::*;
 pub use message::*;
+pub use metrics::*;
 pub use queue_service::*;
 pub use retention::*;
 pub use sqs_types::*;
+#[cfg(feature = "ui")]
 pub use ui::*;
+pub use webhooks::*;
@@ -1,15 +1,21 @@
+mod admin;
+mod alerts;
+mod api;
 mod config;
 mod database;
 mod http_server;
 mod message;
+mod metrics;
 mod queue_service;
 mod retention;
 mod sqs_types;
+#[cfg(feature = "ui")]
 mod ui;
+mod webhooks;
 
-use clap::{Parser, Subcommand};
-use config::Config;
-use queue_service::QueueService;
+use clap::{Parser, Subcommand, ValueEnum};
+use config::{CliOverrides, Config, RetentionMode, RuntimeConfig, RuntimeFlavor};
+use queue_service::{QueueService, QueueStats};
 use retention::BackgroundServices;
 use std::sync::Arc;
 use tracing::info;
@@ -41,29 +47,161 @@ enum Commands {
         #[arg(short, long)]
         receipt_handle: String,
     },
+    // Message counts for one queue, or all queues
+    Stats {
+        queue: Option<String>,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        output: OutputFormat,
+    },
+    // Write the default configuration as a starting qlite.toml
+    InitConfig {
+        #[arg(default_value = "qlite.toml")]
+        path: String,
+        #[arg(long)]
+        force: bool,
+    },
+    // Flags override the environment and qlite.toml; unset flags leave them alone
     Server {
-        #[arg(short, long, default_value = "3000")]
-        port: u16,
-        #[arg(long, default_value = "http://localhost:3000")]
-        base_url: String,
-        #[arg(long, default_value = "false")]
+        #[arg(short, long)]
+        port: Option<u16>,
+        #[arg(long)]
+        host: Option<String>,
+        /// Defaults to http://localhost:<port>
+        #[arg(long)]
+        base_url: Option<String>,
+        #[arg(long)]
         enable_ui: bool,
+        #[arg(long)]
+        db_path: Option<String>,
+        /// keep-forever or delete
+        #[arg(long)]
+        retention_mode: Option<RetentionMode>,
+        /// Days to keep messages in delete mode
+        #[arg(long)]
+        retention_days: Option<u32>,
+        /// Seconds a received message stays hidden
+        #[arg(long)]
+        default_visibility_timeout: Option<u32>,
     },
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Table,
+    Json,
+}
+
+impl Commands {
+    fn cli_overrides(&self) -> CliOverrides {
+        match self {
+            Commands::Server {
+                port,
+                host,
+                base_url,
+                enable_ui,
+                db_path,
+                retention_mode,
+                retention_days,
+                default_visibility_timeout,
+            } => CliOverrides {
+                port: *port,
+                host: host.clone(),
+                base_url: base_url.clone(),
+                enable_ui: *enable_ui,
+                db_path: db_path.clone(),
+                retention_mode: *retention_mode,
+                retention_days: *retention_days,
+                default_visibility_timeout: *default_visibility_timeout,
+            },
+            _ => CliOverrides::default(),
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt::init();
 
     let cli = Cli::parse();
 
+    // Handled before loading config or opening the database, neither of which it needs
+    if let Commands::InitConfig { path, force } = &cli.command {
+        Config::write_template(path, *force)?;
+        println!("Wrote default configuration to {}", path);
+        return Ok(());
+    }
+
     // Load configuration with environment overrides and defaults
-    let config = Config::load_with_overrides().unwrap_or_else(|e| {
+    let mut config = Config::load_with_overrides().unwrap_or_else(|e| {
         println!("Warning: Failed to load config: {}. Using defaults.", e);
         Config::default()
     });
+    config.apply_cli_overrides(&cli.command.cli_overrides())?;
+
+    // The runtime is built by hand so its shape can come from config
+    let runtime = build_runtime(&config.runtime)?;
+    runtime.block_on(run(cli, config))
+}
 
-    let service = Arc::new(QueueService::new(&config.database.path).await?);
+fn format_stats_table(stats: &[QueueStats]) -> String {
+    let headers = ["QUEUE", "VISIBLE", "IN FLIGHT", "DELAYED", "DLQ"];
+    let rows: Vec<[String; 5]> = stats
+        .iter()
+        .map(|s| {
+            [
+                s.queue.clone(),
+                s.visible.to_string(),
+                s.in_flight.to_string(),
+                s.delayed.to_string(),
+                s.dead_lettered.to_string(),
+            ]
+        })
+        .collect();
+
+    let mut widths = headers.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    // Queue names are left-aligned and counts right-aligned
+    let format_row = |cells: [&str; 5]| {
+        let mut line = format!("{:<width$}", cells[0], width = widths[0]);
+        for (cell, width) in cells.iter().zip(widths).skip(1) {
+            line.push_str(&format!("  {:>width$}", cell, width = width));
+        }
+        line + "\n"
+    };
+
+    let mut table = format_row(headers);
+    for row in &rows {
+        table.push_str(&format_row(row.each_ref().map(String::as_str)));
+    }
+    table
+}
+
+fn build_runtime(config: &RuntimeConfig) -> std::io::Result<tokio::runtime::Runtime> {
+    let mut builder = match config.flavor {
+        RuntimeFlavor::MultiThread => {
+            let worker_threads = config.worker_threads();
+            info!(
+                "Starting multi-threaded runtime with {} workers",
+                worker_threads
+            );
+            let mut builder = tokio::runtime::Builder::new_multi_thread();
+            builder.worker_threads(worker_threads);
+            builder
+        }
+        RuntimeFlavor::CurrentThread => {
+            info!("Starting current-thread runtime");
+            tokio::runtime::Builder::new_current_thread()
+        }
+    };
+    builder.enable_all().build()
+}
+
+async fn run(cli: Cli, config: Config) -> Result<(), Box<dyn std::error::Error>> {
+    let service = Arc::new(QueueService::new_with_config(&config.database.path, &config).await?);
 
     match cli.command {
         Commands::CreateQueue { name } => {
@@ -94,17 +232,38 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("Message not found or already deleted");
             }
         }
-        Commands::Server {
-            port,
-            base_url,
-            enable_ui,
-        } => {
-            // Override config with CLI arguments
-            let mut server_config = config.clone();
-            server_config.server.port = port;
-            server_config.server.base_url = Some(base_url.clone());
-            server_config.server.enable_ui = enable_ui;
+        Commands::Stats { queue, output } => {
+            let stats = service.queue_stats(queue.as_deref()).await?;
+            if let Some(queue) = &queue
+                && stats.is_empty()
+            {
+                return Err(format!("Queue '{}' does not exist", queue).into());
+            }
+
+            match output {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+                OutputFormat::Table => print!("{}", format_stats_table(&stats)),
+            }
+        }
+        Commands::InitConfig { .. } => unreachable!("init-config is handled in main"),
+        Commands::Server { .. } => {
+            // CLI flags were already applied to config in main
+            let server_config = config;
+            let host = server_config.server.host.clone();
+            let port = server_config.server.port;
+            let base_url = server_config.effective_base_url();
+            let enable_ui = server_config.server.enable_ui;
 
+            info!(
+                host = %host,
+                port,
+                base_url = %base_url,
+                db_path = %server_config.database.path,
+                retention_mode = ?server_config.retention.mode,
+                retention_days = ?server_config.retention.delete_after_days,
+                visibility_timeout_seconds = server_config.queues.visibility_timeout_seconds,
+                "Effective server configuration"
+            );
             println!("Starting QLite SQS-compatible server on port {}", port);
             println!("Base URL: {}", base_url);
 
@@ -114,6 +273,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .start_retention_cleanup(Arc::clone(&service), server_config.clone())
                 .await?;
             info!("Background retention cleanup service started");
+            background_services
+                .start_counter_reconciliation(Arc::clone(&service), &server_config)
+                .await?;
+            info!("Background counter reconciliation service started");
+            if server_config.database.wal_checkpoint_interval_seconds > 0 {
+                background_services
+                    .start_wal_checkpoint(Arc::clone(&service), &server_config)
+                    .await?;
+                info!("Background WAL checkpoint service started");
+            }
 
             // Setup graceful shutdown
             let shutdown_signal = async {
@@ -124,10 +293,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             };
 
             let app = http_server::create_router(service, base_url, enable_ui);
-            let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
+            let listener = tokio::net::TcpListener::bind(format!("{}:{}", host, port)).await?;
 
-            println!("Server running at http://0.0.0.0:{}", port);
-            if enable_ui {
+            println!("Server running at http://{}:{}", host, port);
+            if enable_ui && cfg!(feature = "ui") {
                 println!("Web UI available at http://localhost:{}/ui", port);
             }
             println!("Press Ctrl+C to shutdown gracefully");
@@ -1,5 +1,10 @@
+use crate::config::MessageIdFormat;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use std::collections::HashMap;
 use uuid::Uuid;
 
@@ -28,10 +33,108 @@ pub struct MessageAttributeValue {
     pub data_type: String,
 }
 
+// Generates message IDs in the configured format
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IdGenerator {
+    format: MessageIdFormat,
+}
+
+impl IdGenerator {
+    pub fn new(format: MessageIdFormat) -> Self {
+        Self { format }
+    }
+
+    pub fn generate(&self, queue_name: &str) -> String {
+        match self.format {
+            MessageIdFormat::UuidV4 => Uuid::new_v4().to_string(),
+            MessageIdFormat::UuidV7 => Uuid::now_v7().to_string(),
+            MessageIdFormat::Prefixed => {
+                let prefix = queue_name.strip_suffix(".fifo").unwrap_or(queue_name);
+                format!("{}-{}", prefix, Uuid::new_v4())
+            }
+        }
+    }
+}
+
+// SQS limits on message attributes
+pub const MAX_MESSAGE_ATTRIBUTES: usize = 10;
+pub const MAX_ATTRIBUTE_NAME_LENGTH: usize = 256;
+pub const MAX_MESSAGE_ATTRIBUTES_SIZE: usize = 262_144;
+// Combined bodies and attributes of every entry in a SendMessageBatch
+pub const MAX_BATCH_PAYLOAD_SIZE: usize = 262_144;
+
+// Bodies are returned inside XML, which can't carry most control characters even when
+// escaped. SQS rejects the same characters at send time.
+pub fn validate_message_body(body: &str) -> Result<(), String> {
+    match body
+        .chars()
+        .find(|&c| !matches!(c, '\t' | '\n' | '\r' | ' '..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' | '\u{10000}'..))
+    {
+        Some(c) => Err(format!(
+            "Invalid binary character '#x{:X}' was found in the message body",
+            c as u32
+        )),
+        None => Ok(()),
+    }
+}
+
+// Reject attributes that real SQS would refuse, so clients don't come to rely on them
+pub fn validate_message_attributes(
+    attributes: &HashMap<String, MessageAttributeValue>,
+) -> Result<(), String> {
+    if attributes.len() > MAX_MESSAGE_ATTRIBUTES {
+        return Err(format!(
+            "Number of message attributes [{}] exceeds the allowed maximum [{}]",
+            attributes.len(),
+            MAX_MESSAGE_ATTRIBUTES
+        ));
+    }
+
+    for name in attributes.keys() {
+        if name.len() > MAX_ATTRIBUTE_NAME_LENGTH {
+            return Err(format!(
+                "Message attribute name '{}' exceeds {} characters",
+                name, MAX_ATTRIBUTE_NAME_LENGTH
+            ));
+        }
+
+        let lower = name.to_ascii_lowercase();
+        if lower.starts_with("aws.") || lower.starts_with("amazon.") {
+            return Err(format!(
+                "Message attribute name '{}' uses a reserved prefix",
+                name
+            ));
+        }
+    }
+
+    let total_size = message_attributes_size(attributes);
+    if total_size > MAX_MESSAGE_ATTRIBUTES_SIZE {
+        return Err(format!(
+            "Message attributes total {} bytes, exceeding the {} byte limit",
+            total_size, MAX_MESSAGE_ATTRIBUTES_SIZE
+        ));
+    }
+
+    Ok(())
+}
+
+// Size SQS charges attributes against the payload limit: names, types and values
+pub fn message_attributes_size(attributes: &HashMap<String, MessageAttributeValue>) -> usize {
+    attributes
+        .iter()
+        .map(|(name, value)| {
+            name.len()
+                + value.data_type.len()
+                + value.string_value.as_ref().map_or(0, String::len)
+                + value.binary_value.as_ref().map_or(0, String::len)
+        })
+        .sum()
+}
+
 impl Message {
-    pub fn new(queue_name: String, body: String) -> Self {
+    pub fn new(queue_name: String, body: String, id_generator: &IdGenerator) -> Self {
         Self {
-            id: Uuid::new_v4().to_string(),
+            id: id_generator.generate(&queue_name),
             queue_name,
             body,
             created_at: Utc::now(),
@@ -69,6 +172,7 @@ impl Message {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(dead_code)]
 pub struct Queue {
     pub name: String,
     pub created_at: DateTime<Utc>,
@@ -82,12 +186,166 @@ pub struct Queue {
 
 impl Queue {}
 
+// MD5OfMessageAttributes as the AWS SDKs verify it: attributes sorted by name, each
+// encoded as its length-prefixed name and data type, a transport byte (1 for string
+// values, 2 for binary) and the length-prefixed value. None when there are no attributes.
+pub fn md5_of_message_attributes(
+    attributes: &HashMap<String, MessageAttributeValue>,
+) -> Option<String> {
+    if attributes.is_empty() {
+        return None;
+    }
+
+    fn push_field(buffer: &mut Vec<u8>, field: &[u8]) {
+        buffer.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        buffer.extend_from_slice(field);
+    }
+
+    let mut names: Vec<&String> = attributes.keys().collect();
+    names.sort();
+
+    let mut buffer = Vec::new();
+    for name in names {
+        let attribute = &attributes[name];
+        push_field(&mut buffer, name.as_bytes());
+        push_field(&mut buffer, attribute.data_type.as_bytes());
+        if let Some(binary) = &attribute.binary_value {
+            // Binary values travel base64-encoded; the checksum covers the raw bytes
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(binary)
+                .unwrap_or_else(|_| binary.as_bytes().to_vec());
+            buffer.push(2);
+            push_field(&mut buffer, &bytes);
+        } else {
+            buffer.push(1);
+            push_field(
+                &mut buffer,
+                attribute.string_value.as_deref().unwrap_or("").as_bytes(),
+            );
+        }
+    }
+    Some(format!("{:x}", md5::compute(&buffer)))
+}
+
+// Receipt handles are "<message id>:<token>", with a new token issued on every receive.
+// With receipt_handles.include_sequence_number, FIFO handles end in ":<sequence number>".
+pub fn receipt_handle(message_id: &str, token: &str) -> String {
+    format!("{}:{}", message_id, token)
+}
+
+pub fn receipt_handle_with_sequence(message_id: &str, token: &str, sequence_number: i64) -> String {
+    format!("{}:{}:{}", message_id, token, sequence_number)
+}
+
+// The message a receipt handle refers to; a bare message id is accepted as its own handle
+pub fn receipt_message_id(receipt_handle: &str) -> &str {
+    receipt_handle
+        .split_once(':')
+        .map_or(receipt_handle, |(message_id, _)| message_id)
+}
+
+// The receive token in a plain receipt handle; None for a bare message id
+pub fn receipt_token(receipt_handle: &str) -> Option<&str> {
+    let (_, rest) = receipt_handle.split_once(':')?;
+    rest.split(':').next()
+}
+
+// The FIFO sequence number a receipt handle carries, plain or signed. Signed handles are
+// decoded without checking the signature, since this is for reading handles out of logs.
+#[allow(dead_code)]
+pub fn receipt_sequence_number(receipt_handle: &str) -> Option<i64> {
+    fn from_plain(handle: &str) -> Option<i64> {
+        let mut parts = handle.split(':');
+        let (_message_id, _token, sequence_number) = (parts.next()?, parts.next()?, parts.next()?);
+        sequence_number.parse().ok()
+    }
+
+    from_plain(receipt_handle).or_else(|| {
+        let (payload, _signature) = receipt_handle.split_once('.')?;
+        let payload = String::from_utf8(URL_SAFE_NO_PAD.decode(payload).ok()?).ok()?;
+        from_plain(payload.lines().next()?)
+    })
+}
+
+// Signs receipt handles as "<payload>.<signature>", both base64url. The payload holds the
+// plain "<message id>:<token>" handle, the queue name and an expiry in unix seconds, the
+// message's visibility deadline when it was received.
+#[derive(Clone)]
+pub struct ReceiptSigner {
+    key: Vec<u8>,
+}
+
+impl ReceiptSigner {
+    // Without a configured secret a random key is used, so handles don't survive a restart
+    pub fn new(secret: Option<&str>) -> Self {
+        let key = match secret {
+            Some(secret) => secret.as_bytes().to_vec(),
+            None => [Uuid::new_v4(), Uuid::new_v4()]
+                .iter()
+                .flat_map(|uuid| *uuid.as_bytes())
+                .collect(),
+        };
+        Self { key }
+    }
+
+    fn mac(&self) -> Hmac<Sha256> {
+        Hmac::new_from_slice(&self.key).expect("HMAC accepts keys of any length")
+    }
+
+    pub fn sign(
+        &self,
+        receipt_handle: &str,
+        queue_name: &str,
+        expires_at: DateTime<Utc>,
+    ) -> String {
+        let payload = format!(
+            "{}\n{}\n{}",
+            receipt_handle,
+            queue_name,
+            expires_at.timestamp()
+        );
+        let mut mac = self.mac();
+        mac.update(payload.as_bytes());
+        format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(&payload),
+            URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes())
+        )
+    }
+
+    // The plain handle inside a signed one; None if it's malformed, tampered with, expired or
+    // signed for another queue. Operator tools that don't go through a queue pass None.
+    pub fn verify(&self, signed: &str, queue_name: Option<&str>) -> Option<String> {
+        let (payload, signature) = signed.split_once('.')?;
+        let payload = URL_SAFE_NO_PAD.decode(payload).ok()?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+
+        let mut mac = self.mac();
+        mac.update(&payload);
+        mac.verify_slice(&signature).ok()?;
+
+        let payload = String::from_utf8(payload).ok()?;
+        let mut fields = payload.splitn(3, '\n');
+        let (receipt_handle, signed_queue_name, expires_at) =
+            (fields.next()?, fields.next()?, fields.next()?);
+        if queue_name.is_some_and(|queue_name| queue_name != signed_queue_name) {
+            return None;
+        }
+        let expires_at = DateTime::from_timestamp(expires_at.parse().ok()?, 0)?;
+        (expires_at > Utc::now()).then(|| receipt_handle.to_string())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ReceivedMessage {
     pub id: String,
     pub body: String,
     pub receipt_handle: String,
     pub attributes: Option<HashMap<String, MessageAttributeValue>>,
+    pub sent_timestamp: Option<DateTime<Utc>>,
+    // Deliveries so far, counting this one
+    pub receive_count: u32,
+    pub first_receive_timestamp: Option<DateTime<Utc>>,
 }
 
 impl ReceivedMessage {
@@ -101,6 +359,81 @@ impl ReceivedMessage {
             id,
             body,
             attributes,
+            sent_timestamp: None,
+            receive_count: 1,
+            first_receive_timestamp: None,
         }
     }
+
+    pub fn with_receipt_handle(mut self, receipt_handle: String) -> Self {
+        self.receipt_handle = receipt_handle;
+        self
+    }
+
+    // Set the original send time from the stored RFC3339 created_at value
+    pub fn with_sent_timestamp(mut self, created_at: &str) -> Self {
+        self.sent_timestamp = parse_timestamp(created_at);
+        self
+    }
+
+    // Set the delivery count and the RFC3339 time the message was first received
+    pub fn with_receive_stats(mut self, receive_count: u32, first_received_at: &str) -> Self {
+        self.receive_count = receive_count;
+        self.first_receive_timestamp = parse_timestamp(first_received_at);
+        self
+    }
+}
+
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string_attribute(data_type: &str, value: &str) -> MessageAttributeValue {
+        MessageAttributeValue {
+            string_value: Some(value.to_string()),
+            binary_value: None,
+            data_type: data_type.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_md5_of_message_attributes_matches_sqs() {
+        // The checksum SQS returns for this attribute
+        let attributes = HashMap::from([(
+            "SOME_Valid.attribute-Name".to_string(),
+            string_attribute("Number", "1493147359900"),
+        )]);
+        assert_eq!(
+            md5_of_message_attributes(&attributes).as_deref(),
+            Some("36655e7e9d7c0e8479fa3f3f42247ae7")
+        );
+
+        // Order-independent, and binary values are hashed as their decoded bytes
+        let attributes = HashMap::from([
+            (
+                "name".to_string(),
+                string_attribute("String.custom", "qlite"),
+            ),
+            (
+                "blob".to_string(),
+                MessageAttributeValue {
+                    string_value: None,
+                    binary_value: Some("AAEC".to_string()),
+                    data_type: "Binary".to_string(),
+                },
+            ),
+        ]);
+        assert_eq!(
+            md5_of_message_attributes(&attributes).as_deref(),
+            Some("0aeb06ac59b4505ed995b0726a27cbc0")
+        );
+
+        assert_eq!(md5_of_message_attributes(&HashMap::new()), None);
+    }
 }
@@ -0,0 +1,186 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Upper bounds (seconds) of the time-to-first-receive histogram buckets, excluding +Inf
+const FIRST_RECEIVE_BUCKETS: [f64; 11] = [
+    0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 300.0, 900.0, 3600.0, 86400.0,
+];
+
+// In-process counters exposed on the /metrics endpoint
+#[derive(Debug, Default)]
+pub struct Metrics {
+    counter_drift_corrected_total: AtomicU64,
+    nack_redeliveries_total: AtomicU64,
+    timeout_redeliveries_total: AtomicU64,
+    dlq_retention_deleted_total: AtomicU64,
+    consumer_deleted_total: AtomicU64,
+    retention_deleted_total: AtomicU64,
+    wal_checkpoints_total: AtomicU64,
+    stale_receipt_handle_total: AtomicU64,
+    // Unix timestamp of the last completed WAL checkpoint, 0 if none has run
+    last_wal_checkpoint_timestamp: AtomicU64,
+    // Non-cumulative per-bucket counts; the last slot is +Inf
+    first_receive_buckets: [AtomicU64; FIRST_RECEIVE_BUCKETS.len() + 1],
+    first_receive_sum_micros: AtomicU64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_counter_drift_corrected(&self, count: u64) {
+        self.counter_drift_corrected_total
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn counter_drift_corrected_total(&self) -> u64 {
+        self.counter_drift_corrected_total.load(Ordering::Relaxed)
+    }
+
+    pub fn inc_nack_redelivery(&self) {
+        self.nack_redeliveries_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn nack_redeliveries_total(&self) -> u64 {
+        self.nack_redeliveries_total.load(Ordering::Relaxed)
+    }
+
+    pub fn add_timeout_redeliveries(&self, count: u64) {
+        self.timeout_redeliveries_total
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn timeout_redeliveries_total(&self) -> u64 {
+        self.timeout_redeliveries_total.load(Ordering::Relaxed)
+    }
+
+    pub fn add_dlq_retention_deleted(&self, count: u64) {
+        self.dlq_retention_deleted_total
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn dlq_retention_deleted_total(&self) -> u64 {
+        self.dlq_retention_deleted_total.load(Ordering::Relaxed)
+    }
+
+    pub fn add_consumer_deleted(&self, count: u64) {
+        self.consumer_deleted_total
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn consumer_deleted_total(&self) -> u64 {
+        self.consumer_deleted_total.load(Ordering::Relaxed)
+    }
+
+    pub fn add_retention_deleted(&self, count: u64) {
+        self.retention_deleted_total
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn retention_deleted_total(&self) -> u64 {
+        self.retention_deleted_total.load(Ordering::Relaxed)
+    }
+
+    pub fn record_wal_checkpoint(&self, timestamp: u64) {
+        self.wal_checkpoints_total.fetch_add(1, Ordering::Relaxed);
+        self.last_wal_checkpoint_timestamp
+            .store(timestamp, Ordering::Relaxed);
+    }
+
+    pub fn wal_checkpoints_total(&self) -> u64 {
+        self.wal_checkpoints_total.load(Ordering::Relaxed)
+    }
+
+    pub fn last_wal_checkpoint_timestamp(&self) -> u64 {
+        self.last_wal_checkpoint_timestamp.load(Ordering::Relaxed)
+    }
+
+    pub fn inc_stale_receipt_handle(&self) {
+        self.stale_receipt_handle_total
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stale_receipt_handle_total(&self) -> u64 {
+        self.stale_receipt_handle_total.load(Ordering::Relaxed)
+    }
+
+    // Time between a message being sent and its first receive
+    pub fn observe_time_to_first_receive(&self, seconds: f64) {
+        let seconds = seconds.max(0.0);
+        let bucket = FIRST_RECEIVE_BUCKETS
+            .iter()
+            .position(|&bound| seconds <= bound)
+            .unwrap_or(FIRST_RECEIVE_BUCKETS.len());
+        self.first_receive_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.first_receive_sum_micros
+            .fetch_add((seconds * 1_000_000.0) as u64, Ordering::Relaxed);
+    }
+
+    fn render_first_receive_histogram(&self) -> String {
+        let mut output = String::from(
+            "# HELP qlite_time_to_first_receive_seconds Time messages waited before their first receive\n\
+             # TYPE qlite_time_to_first_receive_seconds histogram\n",
+        );
+
+        let mut cumulative = 0;
+        for (i, count) in self.first_receive_buckets.iter().enumerate() {
+            cumulative += count.load(Ordering::Relaxed);
+            let bound = FIRST_RECEIVE_BUCKETS
+                .get(i)
+                .map_or_else(|| "+Inf".to_string(), |bound| bound.to_string());
+            output.push_str(&format!(
+                "qlite_time_to_first_receive_seconds_bucket{{le=\"{}\"}} {}\n",
+                bound, cumulative
+            ));
+        }
+
+        let sum = self.first_receive_sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        output.push_str(&format!(
+            "qlite_time_to_first_receive_seconds_sum {:.6}\n\
+             qlite_time_to_first_receive_seconds_count {}\n",
+            sum, cumulative
+        ));
+        output
+    }
+
+    // Render all counters in Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let counters = format!(
+            "# HELP qlite_counter_drift_corrected_total Queue counter rows corrected by reconciliation\n\
+             # TYPE qlite_counter_drift_corrected_total counter\n\
+             qlite_counter_drift_corrected_total {}\n\
+             # HELP qlite_message_redeliveries_total Messages made visible again, by cause\n\
+             # TYPE qlite_message_redeliveries_total counter\n\
+             qlite_message_redeliveries_total{{reason=\"nack\"}} {}\n\
+             qlite_message_redeliveries_total{{reason=\"visibility_timeout\"}} {}\n\
+             # HELP qlite_dlq_retention_deleted_total Dead-lettered messages removed by DLQ retention\n\
+             # TYPE qlite_dlq_retention_deleted_total counter\n\
+             qlite_dlq_retention_deleted_total {}\n\
+             # HELP qlite_messages_consumer_deleted_total Messages deleted by consumers\n\
+             # TYPE qlite_messages_consumer_deleted_total counter\n\
+             qlite_messages_consumer_deleted_total {}\n\
+             # HELP qlite_messages_retention_deleted_total Messages removed by the retention period\n\
+             # TYPE qlite_messages_retention_deleted_total counter\n\
+             qlite_messages_retention_deleted_total {}\n\
+             # HELP qlite_wal_checkpoints_total Completed WAL checkpoints\n\
+             # TYPE qlite_wal_checkpoints_total counter\n\
+             qlite_wal_checkpoints_total {}\n\
+             # HELP qlite_wal_last_checkpoint_timestamp_seconds Unix time of the last completed WAL checkpoint\n\
+             # TYPE qlite_wal_last_checkpoint_timestamp_seconds gauge\n\
+             qlite_wal_last_checkpoint_timestamp_seconds {}\n\
+             # HELP qlite_stale_receipt_handle_total Deletes and visibility changes rejected for a superseded receipt handle\n\
+             # TYPE qlite_stale_receipt_handle_total counter\n\
+             qlite_stale_receipt_handle_total {}\n",
+            self.counter_drift_corrected_total(),
+            self.nack_redeliveries_total(),
+            self.timeout_redeliveries_total(),
+            self.dlq_retention_deleted_total(),
+            self.consumer_deleted_total(),
+            self.retention_deleted_total(),
+            self.wal_checkpoints_total(),
+            self.last_wal_checkpoint_timestamp(),
+            self.stale_receipt_handle_total(),
+        );
+        counters + &self.render_first_receive_histogram()
+    }
+}
@@ -1,12 +1,25 @@
-use crate::config::QueueConfig;
-use crate::database::{Database, DelayedMessageTuple, QueueAttributes, QueueMetric};
-use crate::message::{Message, MessageAttributeValue, ReceivedMessage};
+use crate::alerts::{AlertNotifier, DlqAlert};
+use crate::config::{
+    BatchMode, Config, QueueConfig, ReceiptHandleEncoding, RoutingRule, WebhookEvent,
+};
+use crate::database::{
+    Database, DeadLettered, DelayedMessageTuple, DeleteOutcome, DeliveryRecord, ExportedMessage,
+    InflightAgeStats, PurgeFilter, QueueAttributes, QueueDeletion, QueueMessageRow, QueueMetric,
+    QueueWithTags, StorageStats, VisibilityChange, WalCheckpoint,
+};
+use crate::message::{
+    IdGenerator, Message, MessageAttributeValue, ReceiptSigner, ReceivedMessage, receipt_message_id,
+};
+use crate::metrics::Metrics;
+use crate::webhooks::WebhookDispatcher;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::broadcast;
 use tokio_rusqlite::Result;
 
 // Type aliases to fix clippy warnings
+// (queue_name, message_id, body, attributes, deduplication_id, delay_seconds, message_group_id)
 type BatchSendEntry = (
     String,
     String,
@@ -14,25 +27,246 @@ type BatchSendEntry = (
     Option<HashMap<String, MessageAttributeValue>>,
     Option<String>,
     u32,
+    Option<String>,
 );
 type BatchSendResult = std::result::Result<String, String>;
 
+// Result of a negative acknowledgment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NackOutcome {
+    // Message is visible again and will be redelivered
+    Requeued { nack_count: u32 },
+    // Message reached the queue's max receive count and was moved to its DLQ
+    DeadLettered { nack_count: u32 },
+}
+
+// A queue can only be purged once within this window; sooner attempts are rejected
+const PURGE_COOLDOWN: Duration = Duration::from_secs(1);
+
+// Messages moved per step by a message move task without a rate limit
+const MESSAGE_MOVE_BATCH_SIZE: u32 = 500;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueueCreation {
+    Created,
+    // The queue exists and none of the requested attributes differ
+    AlreadyExists,
+    // The queue exists with a different value for this attribute
+    AttributeConflict { attribute: String },
+    // The attributes can't apply to a queue of this name, e.g. FifoQueue without .fifo
+    InvalidAttributes { message: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PurgeOutcome {
+    Purged { deleted: u32 },
+    NotFound,
+    // The queue was purged less than PURGE_COOLDOWN ago
+    InProgress,
+}
+
+// Message counts for one queue, as shown by `qlite stats`
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct QueueStats {
+    pub queue: String,
+    pub visible: u32,
+    pub in_flight: u32,
+    pub delayed: u32,
+    pub dead_lettered: u32,
+}
+
+// Advisory exclusive-consumer lease on a queue (QLite extension)
+#[derive(Debug, Clone)]
+struct ReceiveLock {
+    token: String,
+    expires_at: Instant,
+}
+
 pub struct QueueService {
     db: Database,
     // Notification system for long polling
     message_notifiers: Arc<tokio::sync::RwLock<HashMap<String, broadcast::Sender<()>>>>,
+    metrics: Arc<Metrics>,
+    receive_locks: Arc<tokio::sync::Mutex<HashMap<String, ReceiveLock>>>,
+    // When each queue was last purged, for PurgeQueueInProgress
+    last_purges: Arc<tokio::sync::Mutex<HashMap<String, Instant>>>,
+    alerts: AlertNotifier,
+    webhooks: WebhookDispatcher,
+    id_generator: IdGenerator,
+    batch_mode: BatchMode,
+    max_visibility_extension_seconds: u32,
+    max_batch_entries: usize,
+    case_insensitive_queue_names: bool,
+    default_message_delay_seconds: u32,
+    delivery_log: bool,
+    simulated_latency: Duration,
+    fault_injection_rate: f64,
+    admin_token: Option<String>,
+    max_request_body_bytes: usize,
+    ui_messages_per_page: usize,
+    routing_rules: Vec<RoutingRule>,
+    // Set when receipt_handles.encoding is Signed
+    receipt_signer: Option<ReceiptSigner>,
 }
 
 impl QueueService {
+    #[allow(dead_code)]
     pub async fn new(db_path: &str) -> Result<Self> {
-        let db = Database::new(db_path).await?;
-        Ok(Self {
+        Self::new_with_config(db_path, &Config::default()).await
+    }
+
+    pub async fn new_with_config(db_path: &str, config: &Config) -> Result<Self> {
+        if let Some(backup_path) = &config.database.restore_from {
+            if Database::restore_from_backup(backup_path, db_path)? {
+                tracing::info!("Restored database {} from backup {}", db_path, backup_path);
+            } else {
+                tracing::warn!(
+                    "Database {} already exists; not restoring from backup {}",
+                    db_path,
+                    backup_path
+                );
+            }
+        }
+
+        let metrics = Arc::new(Metrics::new());
+        let db = Database::new(db_path)
+            .await?
+            .with_visibility_timeout(config.queues.visibility_timeout_seconds)
+            .with_visibility_jitter(config.chaos.visibility_jitter)
+            .with_strict_standard_order(config.queues.standard_queue_strict_order)
+            .with_sequence_in_receipt_handles(config.receipt_handles.include_sequence_number)
+            .with_metrics(Arc::clone(&metrics));
+        let mut service = Self {
             db,
             message_notifiers: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
-        })
+            metrics,
+            receive_locks: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            last_purges: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            alerts: AlertNotifier::new(&config.alerts),
+            webhooks: WebhookDispatcher::new(&config.webhooks),
+            id_generator: IdGenerator::new(config.queues.message_id_format),
+            batch_mode: config.queues.batch_mode,
+            max_visibility_extension_seconds: config.queues.max_visibility_extension_seconds,
+            max_batch_entries: config.queues.max_batch_entries,
+            case_insensitive_queue_names: config.queues.case_insensitive_queue_names,
+            default_message_delay_seconds: config.queues.default_message_delay_seconds,
+            delivery_log: config.logging.delivery_log,
+            simulated_latency: Duration::from_millis(config.testing.simulate_latency_ms),
+            fault_injection_rate: config.testing.fault_injection_rate,
+            admin_token: config.server.admin_token.clone(),
+            max_request_body_bytes: config.server.max_request_body_bytes,
+            ui_messages_per_page: config.server.ui_messages_per_page,
+            routing_rules: config.routing.rules.clone(),
+            receipt_signer: (config.receipt_handles.encoding == ReceiptHandleEncoding::Signed)
+                .then(|| ReceiptSigner::new(config.receipt_handles.secret.as_deref())),
+        };
+
+        // Rules name queues like requests do
+        service.routing_rules = config
+            .routing
+            .rules
+            .iter()
+            .map(|rule| RoutingRule {
+                source_queue: service.normalize_queue_name(&rule.source_queue),
+                target_queue: service.normalize_queue_name(&rule.target_queue),
+                ..rule.clone()
+            })
+            .collect();
+
+        // Correct any counter drift left behind by crashes or manual edits
+        service.reconcile_counters().await?;
+
+        Ok(service)
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    pub fn schema_ready(&self) -> bool {
+        self.db.schema_ready()
+    }
+
+    pub fn id_generator(&self) -> IdGenerator {
+        self.id_generator
+    }
+
+    pub fn batch_mode(&self) -> BatchMode {
+        self.batch_mode
+    }
+
+    pub fn max_visibility_extension_seconds(&self) -> u32 {
+        self.max_visibility_extension_seconds
+    }
+
+    pub fn max_batch_entries(&self) -> usize {
+        self.max_batch_entries
+    }
+
+    // The stored spelling of a queue name. Every public method taking a queue name goes
+    // through this, so with queues.case_insensitive_queue_names all entry points agree.
+    pub fn normalize_queue_name(&self, queue_name: &str) -> String {
+        if self.case_insensitive_queue_names {
+            queue_name.to_lowercase()
+        } else {
+            queue_name.to_string()
+        }
+    }
+
+    // A RedrivePolicy attribute names its dead-letter queue inside the JSON value
+    fn normalize_attributes(
+        &self,
+        attributes: &HashMap<String, String>,
+    ) -> HashMap<String, String> {
+        let mut attributes = attributes.clone();
+        if self.case_insensitive_queue_names
+            && let Some(value) = attributes.get_mut("RedrivePolicy")
+            && let Ok(mut policy) = serde_json::from_str::<serde_json::Value>(value)
+            && let Some(target) = policy.get_mut("deadLetterTargetArn")
+            && let Some(arn) = target.as_str()
+        {
+            *target = serde_json::Value::String(arn.to_lowercase());
+            *value = policy.to_string();
+        }
+        attributes
+    }
+
+    fn normalize_config(&self, config: &QueueConfig) -> QueueConfig {
+        QueueConfig {
+            name: self.normalize_queue_name(&config.name),
+            dead_letter_target_arn: config
+                .dead_letter_target_arn
+                .as_deref()
+                .map(|arn| self.normalize_queue_name(arn)),
+            ..config.clone()
+        }
+    }
+
+    // Testing aid: extra delay applied to send and receive responses
+    pub fn simulated_latency(&self) -> Duration {
+        self.simulated_latency
+    }
+
+    // Testing aid: share of SQS requests to fail on purpose
+    pub fn fault_injection_rate(&self) -> f64 {
+        self.fault_injection_rate
+    }
+
+    pub fn admin_token(&self) -> Option<&str> {
+        self.admin_token.as_deref()
+    }
+
+    pub fn max_request_body_bytes(&self) -> usize {
+        self.max_request_body_bytes
+    }
+
+    // Consistent copy of the live database, taken without stopping the server
+    pub async fn backup_to(&self, path: &str) -> Result<u64> {
+        self.db.backup_to(path).await
     }
 
     pub async fn create_queue(&self, queue_name: &str) -> Result<()> {
+        let queue_name = &self.normalize_queue_name(queue_name);
         // Check if this is a FIFO queue based on naming convention
         let is_fifo = queue_name.ends_with(".fifo");
 
@@ -64,9 +298,45 @@ impl QueueService {
         Ok(())
     }
 
+    // CreateQueue semantics: creating an existing queue succeeds unless one of the given
+    // attributes differs from its current value
+    pub async fn create_queue_with_attributes(
+        &self,
+        queue_name: &str,
+        attributes: &HashMap<String, String>,
+    ) -> Result<QueueCreation> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        let attributes = &self.normalize_attributes(attributes);
+        if let Some(existing) = self.get_queue_config(queue_name).await? {
+            return Ok(match existing.conflicting_attribute(attributes) {
+                Some(attribute) => QueueCreation::AttributeConflict {
+                    attribute: attribute.to_string(),
+                },
+                None => QueueCreation::AlreadyExists,
+            });
+        }
+
+        let mut config = QueueConfig::new(queue_name.to_string(), queue_name.ends_with(".fifo"));
+        for (name, value) in attributes {
+            config.apply_attribute(name, value);
+        }
+        if let Err(crate::config::ConfigError::Validation(message)) = config.validate_name() {
+            return Ok(QueueCreation::InvalidAttributes { message });
+        }
+
+        self.create_queue(queue_name).await?;
+        if !attributes.is_empty() {
+            self.db.create_queue_with_config(&config).await?;
+        }
+
+        Ok(QueueCreation::Created)
+    }
+
     #[allow(dead_code)]
     pub async fn create_queue_with_config(&self, config: &QueueConfig) -> Result<()> {
-        self.db.create_queue_with_config(config).await
+        self.db
+            .create_queue_with_config(&self.normalize_config(config))
+            .await
     }
 
     pub async fn send_message(
@@ -76,36 +346,16 @@ impl QueueService {
         attributes: Option<HashMap<String, MessageAttributeValue>>,
         deduplication_id: Option<String>,
     ) -> Result<String> {
-        let mut message = Message::new(queue_name.to_string(), body.to_string());
-
-        if let Some(attrs) = attributes {
-            message = message.with_attributes(attrs);
-        }
-
-        if let Some(dedup_id) = deduplication_id {
-            message = message.with_deduplication_id(dedup_id);
-        }
-
-        let message_id = message.id.clone();
-        let attributes_json = message
-            .attributes
-            .as_ref()
-            .map(|attrs| serde_json::to_string(attrs).unwrap());
-
-        self.db
-            .send_message(
-                queue_name,
-                &message.id,
-                body,
-                attributes_json.as_deref(),
-                message.deduplication_id.as_deref(),
-            )
-            .await?;
-
-        // Notify any waiting long polling requests
-        self.notify_message_arrival(queue_name).await;
-
-        Ok(message_id)
+        // Sends to FIFO queues fail with MissingMessageGroupId; they need a group
+        self.send_message_enhanced_with_group(
+            queue_name,
+            body,
+            attributes,
+            deduplication_id,
+            None,
+            None,
+        )
+        .await
     }
 
     // Internal method to notify waiting long polling requests
@@ -137,8 +387,44 @@ impl QueueService {
     }
 
     pub async fn receive_message(&self, queue_name: &str) -> Result<Option<ReceivedMessage>> {
-        if let Some((id, body, _created_at, attributes_json)) =
-            self.db.receive_message(queue_name).await?
+        self.receive_message_as(queue_name, None).await
+    }
+
+    // consumer_id is the receiver's self-reported identity, kept in the delivery log
+    pub async fn receive_message_as(
+        &self,
+        queue_name: &str,
+        consumer_id: Option<&str>,
+    ) -> Result<Option<ReceivedMessage>> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        self.receive_next_message(queue_name, consumer_id, &[])
+            .await
+    }
+
+    // Messages in delivered_ids were already handed out by the same request, so they don't
+    // block the rest of their FIFO group
+    async fn receive_next_message(
+        &self,
+        queue_name: &str,
+        consumer_id: Option<&str>,
+        delivered_ids: &[String],
+    ) -> Result<Option<ReceivedMessage>> {
+        let (received, dead_lettered) = self
+            .db
+            .receive_next_message(queue_name, delivered_ids)
+            .await?;
+        self.notify_dead_lettered(dead_lettered);
+
+        if let Some((
+            id,
+            body,
+            created_at,
+            attributes_json,
+            receipt_handle,
+            receive_count,
+            first_received_at,
+            visibility_deadline,
+        )) = received
         {
             let attributes = if let Some(json) = attributes_json {
                 serde_json::from_str(&json).ok()
@@ -146,21 +432,266 @@ impl QueueService {
                 None
             };
 
-            Ok(Some(ReceivedMessage::new(id, body, attributes)))
+            self.webhooks
+                .dispatch(WebhookEvent::Received, queue_name, &id, None);
+
+            let message = ReceivedMessage::new(id, body, attributes)
+                .with_receipt_handle(self.issue_receipt_handle(
+                    queue_name,
+                    receipt_handle,
+                    &visibility_deadline,
+                ))
+                .with_sent_timestamp(&created_at)
+                .with_receive_stats(receive_count, &first_received_at);
+            self.log_deliveries(std::slice::from_ref(&message), consumer_id)
+                .await?;
+            Ok(Some(message))
         } else {
             Ok(None)
         }
     }
 
+    // A no-op unless logging.delivery_log is enabled
+    async fn log_deliveries(
+        &self,
+        messages: &[ReceivedMessage],
+        consumer_id: Option<&str>,
+    ) -> Result<()> {
+        if !self.delivery_log || messages.is_empty() {
+            return Ok(());
+        }
+
+        let deliveries = messages
+            .iter()
+            .map(|m| (m.id.clone(), m.receipt_handle.clone()))
+            .collect();
+        self.db
+            .log_deliveries(deliveries, consumer_id.map(str::to_string))
+            .await
+    }
+
+    pub async fn get_message_deliveries(&self, message_id: &str) -> Result<Vec<DeliveryRecord>> {
+        self.db.get_message_deliveries(message_id).await
+    }
+
+    pub async fn get_message_receive_count(&self, message_id: &str) -> Result<Option<i64>> {
+        self.db.get_message_receive_count(message_id).await
+    }
+
+    pub fn delivery_log_enabled(&self) -> bool {
+        self.delivery_log
+    }
+
+    // Grant an exclusive receive lease on a queue. Returns None while another
+    // consumer holds an unexpired lease.
+    pub async fn acquire_receive_lock(&self, queue_name: &str, lease: Duration) -> Option<String> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        let mut locks = self.receive_locks.lock().await;
+        let now = Instant::now();
+
+        if locks
+            .get(queue_name)
+            .is_some_and(|lock| lock.expires_at > now)
+        {
+            return None;
+        }
+
+        let token = uuid::Uuid::new_v4().to_string();
+        locks.insert(
+            queue_name.to_string(),
+            ReceiveLock {
+                token: token.clone(),
+                expires_at: now + lease,
+            },
+        );
+        Some(token)
+    }
+
+    // Release a lease early. Returns false if the token doesn't hold the queue's lock.
+    pub async fn release_receive_lock(&self, queue_name: &str, token: &str) -> bool {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        let mut locks = self.receive_locks.lock().await;
+        let held = locks
+            .get(queue_name)
+            .is_some_and(|lock| lock.token == token && lock.expires_at > Instant::now());
+
+        if held {
+            locks.remove(queue_name);
+        }
+        held
+    }
+
+    // Whether a receiver presenting this token (if any) may receive from the queue
+    pub async fn receive_allowed(&self, queue_name: &str, token: Option<&str>) -> bool {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        let locks = self.receive_locks.lock().await;
+        match locks.get(queue_name) {
+            Some(lock) if lock.expires_at > Instant::now() => token == Some(lock.token.as_str()),
+            _ => true,
+        }
+    }
+
+    // Signed handles expire at the visibility deadline of the receive that issued them
+    fn issue_receipt_handle(
+        &self,
+        queue_name: &str,
+        receipt_handle: String,
+        visibility_deadline: &str,
+    ) -> String {
+        match &self.receipt_signer {
+            Some(signer) => {
+                let expires_at = chrono::DateTime::parse_from_rfc3339(visibility_deadline)
+                    .map(|deadline| deadline.with_timezone(&chrono::Utc))
+                    .unwrap_or_else(|_| chrono::Utc::now());
+                signer.sign(&receipt_handle, queue_name, expires_at)
+            }
+            None => receipt_handle,
+        }
+    }
+
+    // The plain handle a client's handle stands for; None if a signed handle doesn't verify
+    // or was issued by another queue than the request's
+    fn resolve_receipt_handle(
+        &self,
+        queue_name: Option<&str>,
+        receipt_handle: &str,
+    ) -> Option<String> {
+        match &self.receipt_signer {
+            Some(signer) => signer.verify(receipt_handle, queue_name),
+            None => Some(receipt_handle.to_string()),
+        }
+    }
+
+    pub async fn change_message_visibility(
+        &self,
+        queue_name: &str,
+        receipt_handle: &str,
+        visibility_timeout_seconds: u32,
+    ) -> Result<VisibilityChange> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        let Some(receipt_handle) = self.resolve_receipt_handle(Some(queue_name), receipt_handle)
+        else {
+            return Ok(VisibilityChange::NotFound);
+        };
+        let change = self
+            .db
+            .change_message_visibility(
+                queue_name,
+                &receipt_handle,
+                visibility_timeout_seconds,
+                self.max_visibility_extension_seconds,
+            )
+            .await?;
+        if change == VisibilityChange::StaleHandle {
+            self.metrics.inc_stale_receipt_handle();
+        }
+        Ok(change)
+    }
+
+    // For operator tools: a signed handle is accepted whichever queue it was issued by
     pub async fn delete_message(&self, receipt_handle: &str) -> Result<bool> {
-        // For now, receipt_handle is the same as message ID
-        self.db.delete_message(receipt_handle).await
+        Ok(self.delete_resolved(None, receipt_handle).await? == DeleteOutcome::Deleted)
+    }
+
+    // Like delete_message, but for a request to queue_name, and reports why a message
+    // wasn't deleted
+    pub async fn delete_message_checked(
+        &self,
+        queue_name: &str,
+        receipt_handle: &str,
+    ) -> Result<DeleteOutcome> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        self.delete_resolved(Some(queue_name), receipt_handle).await
+    }
+
+    async fn delete_resolved(
+        &self,
+        queue_name: Option<&str>,
+        receipt_handle: &str,
+    ) -> Result<DeleteOutcome> {
+        let Some(receipt_handle) = self.resolve_receipt_handle(queue_name, receipt_handle) else {
+            return Ok(DeleteOutcome::NotFound);
+        };
+        let message_id = receipt_message_id(&receipt_handle);
+        let queue_name = self.queue_for_webhook(message_id).await?;
+        let outcome = self.db.delete_message(&receipt_handle).await?;
+        let deleted = outcome == DeleteOutcome::Deleted;
+        if deleted {
+            self.metrics.add_consumer_deleted(1);
+        }
+        if outcome == DeleteOutcome::StaleHandle {
+            self.metrics.inc_stale_receipt_handle();
+        }
+
+        if deleted && let Some(queue_name) = queue_name {
+            self.webhooks
+                .dispatch(WebhookEvent::Deleted, &queue_name, message_id, None);
+        }
+
+        Ok(outcome)
     }
 
-    pub async fn delete_queue(&self, queue_name: &str) -> Result<bool> {
-        self.db.delete_queue(queue_name).await
+    // Look up a message's queue only when someone subscribes to deletes
+    async fn queue_for_webhook(&self, message_id: &str) -> Result<Option<String>> {
+        if self.webhooks.has_subscribers(WebhookEvent::Deleted) {
+            self.db.get_message_queue_name(message_id).await
+        } else {
+            Ok(None)
+        }
     }
 
+    // Negative acknowledgment: make an in-flight message visible again right away.
+    // Nacks count towards max_receive_count so poison messages reach the DLQ sooner.
+    pub async fn nack_message(&self, message_id: &str) -> Result<Option<NackOutcome>> {
+        let Some((queue_name, nack_count)) = self.db.nack_message(message_id).await? else {
+            return Ok(None);
+        };
+
+        if let Some(config) = self.db.get_queue_config(&queue_name).await?
+            && let (Some(max_receive_count), Some(_)) =
+                (config.max_receive_count, config.dead_letter_target_arn)
+            && nack_count >= max_receive_count
+        {
+            let reason = format!(
+                "Message was nacked {} times (max receive count {})",
+                nack_count, max_receive_count
+            );
+            if self.dead_letter_message(message_id, &reason).await? {
+                return Ok(Some(NackOutcome::DeadLettered { nack_count }));
+            }
+        }
+
+        self.metrics.inc_nack_redelivery();
+        self.notify_message_arrival(&queue_name).await;
+
+        Ok(Some(NackOutcome::Requeued { nack_count }))
+    }
+
+    // `force` also deletes a queue that other queues use as their DLQ
+    pub async fn delete_queue(&self, queue_name: &str, force: bool) -> Result<QueueDeletion> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        self.db.delete_queue(queue_name, force).await
+    }
+
+    pub async fn purge_queue(&self, queue_name: &str) -> Result<PurgeOutcome> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        // Held across the delete so concurrent purges of one queue can't both run
+        let mut last_purges = self.last_purges.lock().await;
+        if last_purges
+            .get(queue_name)
+            .is_some_and(|purged_at| purged_at.elapsed() < PURGE_COOLDOWN)
+        {
+            return Ok(PurgeOutcome::InProgress);
+        }
+
+        let Some(deleted) = self.db.purge_queue(queue_name).await? else {
+            return Ok(PurgeOutcome::NotFound);
+        };
+        last_purges.insert(queue_name.to_string(), Instant::now());
+        Ok(PurgeOutcome::Purged { deleted })
+    }
+
+    #[cfg_attr(not(feature = "ui"), allow(dead_code))]
     pub async fn restore_message(&self, message_id: &str) -> Result<bool> {
         self.db.restore_message(message_id).await
     }
@@ -169,10 +700,61 @@ impl QueueService {
         self.db.list_queues().await
     }
 
+    pub async fn list_queues_with_tags(
+        &self,
+        tag_filter: &[(String, String)],
+    ) -> Result<Vec<QueueWithTags>> {
+        self.db.list_queues_with_tags(tag_filter).await
+    }
+
+    pub async fn tag_queue(&self, queue_name: &str, tags: Vec<(String, String)>) -> Result<bool> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        self.db.tag_queue(queue_name, tags).await
+    }
+
+    pub async fn untag_queue(&self, queue_name: &str, keys: Vec<String>) -> Result<bool> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        self.db.untag_queue(queue_name, keys).await
+    }
+
+    pub async fn list_queue_tags(&self, queue_name: &str) -> Result<Option<Vec<(String, String)>>> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        self.db.list_queue_tags(queue_name).await
+    }
+
     pub async fn get_queue_attributes(&self, queue_name: &str) -> Result<Option<QueueAttributes>> {
+        let queue_name = &self.normalize_queue_name(queue_name);
         self.db.get_queue_attributes(queue_name).await
     }
 
+    // Counts for one queue, or every queue when None; empty if the queue doesn't exist
+    pub async fn queue_stats(&self, queue_name: Option<&str>) -> Result<Vec<QueueStats>> {
+        let queue_name = queue_name.map(|name| self.normalize_queue_name(name));
+        let queue_names = match queue_name {
+            Some(name) => vec![name.to_string()],
+            None => self
+                .list_queues()
+                .await?
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect(),
+        };
+
+        let mut stats = Vec::with_capacity(queue_names.len());
+        for queue in queue_names {
+            if let Some(attrs) = self.get_queue_attributes(&queue).await? {
+                stats.push(QueueStats {
+                    queue,
+                    visible: attrs.approximate_number_of_messages,
+                    in_flight: attrs.approximate_number_of_messages_not_visible,
+                    delayed: attrs.approximate_number_of_messages_delayed,
+                    dead_lettered: attrs.dead_lettered_messages,
+                });
+            }
+        }
+        Ok(stats)
+    }
+
     #[allow(dead_code)]
     pub async fn get_queue_messages(
         &self,
@@ -188,94 +770,54 @@ impl QueueService {
             Option<String>,
         )>,
     > {
+        let queue_name = &self.normalize_queue_name(queue_name);
         self.db.get_queue_messages(queue_name).await
     }
 
-    pub async fn get_all_queue_messages(
+    pub fn stream_all_queue_messages(
         &self,
         queue_name: &str,
-    ) -> Result<
-        Vec<(
-            String,
-            String,
-            String,
-            Option<String>,
-            u32,
-            Option<String>,
-            Option<String>,
-            String,
-            Option<String>,
-            Option<String>,
-        )>,
-    > {
-        self.db.get_all_queue_messages(queue_name).await
+    ) -> tokio::sync::mpsc::Receiver<rusqlite::Result<ExportedMessage>> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        self.db.stream_all_queue_messages(queue_name)
     }
 
-    // DLQ-aware message processing
+    // The oldest limit messages of a queue, in any status; get_queue_messages_page goes further
     #[allow(dead_code)]
-    pub async fn receive_message_with_dlq(
+    pub async fn get_oldest_queue_messages(
         &self,
         queue_name: &str,
-    ) -> Result<Option<ReceivedMessage>> {
-        // Use a loop instead of recursion to handle DLQ processing
-        loop {
-            // Try to receive a message normally
-            if let Some((id, body, _created_at, attributes_json)) =
-                self.db.receive_message(queue_name).await?
-            {
-                let attributes = if let Some(json) = attributes_json {
-                    serde_json::from_str(&json).ok()
-                } else {
-                    None
-                };
+        limit: usize,
+    ) -> Result<Vec<QueueMessageRow>> {
+        self.get_queue_messages_page(queue_name, limit, 0).await
+    }
 
-                // Check if message should be moved to DLQ due to max receive count
-                if let Some(queue_config) = self.db.get_queue_config(queue_name).await? {
-                    // Get the current receive count from database by querying the messages again
-                    if let Some((_, _, _, _, receive_count, _, _)) =
-                        self.get_message_details(&id).await?
-                        && Some(receive_count) >= queue_config.max_receive_count
-                    {
-                        // Move to DLQ
-                        let reason = format!(
-                            "Message exceeded max receive count of {}",
-                            queue_config.max_receive_count.unwrap_or(0)
-                        );
-                        if self.db.move_message_to_dlq(&id, &reason).await? {
-                            // Message moved to DLQ, continue loop to get another message
-                            continue;
-                        }
-                    }
-                }
+    #[cfg_attr(not(feature = "ui"), allow(dead_code))]
+    pub async fn get_queue_messages_page(
+        &self,
+        queue_name: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<QueueMessageRow>> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        self.db
+            .get_queue_messages_page(queue_name, limit, offset)
+            .await
+    }
 
-                // Message is valid, return it
-                return Ok(Some(ReceivedMessage::new(id, body, attributes)));
-            } else {
-                // No messages available
-                return Ok(None);
-            }
-        }
+    #[cfg_attr(not(feature = "ui"), allow(dead_code))]
+    pub fn ui_messages_per_page(&self) -> usize {
+        self.ui_messages_per_page
     }
 
-    // Helper method to get message details
+    // Every receive path dead-letters messages past maxReceiveCount in the database
+    // transaction that would have delivered them, so this is receive_message
     #[allow(dead_code)]
-    async fn get_message_details(
+    pub async fn receive_message_with_dlq(
         &self,
-        _message_id: &str,
-    ) -> Result<
-        Option<(
-            String,
-            String,
-            String,
-            Option<String>,
-            u32,
-            Option<String>,
-            Option<String>,
-        )>,
-    > {
-        // This would need to be implemented in the database layer
-        // For now, return None to avoid compilation errors
-        Ok(None)
+        queue_name: &str,
+    ) -> Result<Option<ReceivedMessage>> {
+        self.receive_message(queue_name).await
     }
 
     // DLQ Management operations
@@ -285,9 +827,46 @@ impl QueueService {
         message_id: &str,
         failure_reason: &str,
     ) -> Result<bool> {
-        self.db
+        self.dead_letter_message(message_id, failure_reason).await
+    }
+
+    // Every DLQ move goes through here so alerts fire consistently
+    async fn dead_letter_message(&self, message_id: &str, failure_reason: &str) -> Result<bool> {
+        let Some((queue, dlq)) = self
+            .db
             .move_message_to_dlq(message_id, failure_reason)
-            .await
+            .await?
+        else {
+            return Ok(false);
+        };
+
+        self.notify_dead_lettered(vec![DeadLettered {
+            message_id: message_id.to_string(),
+            queue_name: queue,
+            dlq_name: dlq,
+            reason: failure_reason.to_string(),
+        }]);
+        Ok(true)
+    }
+
+    // Fire the DLQ webhook and alert for messages that were moved to a DLQ
+    fn notify_dead_lettered(&self, dead_lettered: Vec<DeadLettered>) {
+        for moved in dead_lettered {
+            self.webhooks.dispatch(
+                WebhookEvent::Dlq,
+                &moved.queue_name,
+                &moved.message_id,
+                Some(moved.reason.clone()),
+            );
+
+            self.alerts.dlq_message(DlqAlert {
+                queue: moved.queue_name,
+                dlq: moved.dlq_name,
+                message_id: moved.message_id,
+                reason: moved.reason,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            });
+        }
     }
 
     #[allow(dead_code)]
@@ -295,25 +874,150 @@ impl QueueService {
         &self,
         dlq_name: &str,
     ) -> Result<Vec<(String, String, String, String, Option<String>)>> {
+        let dlq_name = &self.normalize_queue_name(dlq_name);
         // Get messages from dead_letter_messages table for the specified DLQ
         self.db.get_dlq_messages(dlq_name).await
     }
 
+    pub async fn purge_filtered(&self, queue_name: &str, filter: &PurgeFilter) -> Result<u32> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        self.db.purge_filtered(queue_name, filter).await
+    }
+
+    pub async fn create_attribute_index(
+        &self,
+        queue_name: &str,
+        attribute_name: &str,
+    ) -> Result<String> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        self.db
+            .create_attribute_index(queue_name, attribute_name)
+            .await
+    }
+
+    // Re-deliver copies of processed messages whose receive/send time falls in [from, to]
+    pub async fn replay_messages(
+        &self,
+        queue_name: &str,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<(String, String)>> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        let replayed = self
+            .db
+            .replay_messages(
+                queue_name,
+                &from.to_rfc3339(),
+                &to.to_rfc3339(),
+                self.id_generator,
+            )
+            .await?;
+
+        if !replayed.is_empty() {
+            self.notify_message_arrival(queue_name).await;
+            for (_, new_id) in &replayed {
+                self.webhooks
+                    .dispatch(WebhookEvent::Sent, queue_name, new_id, None);
+            }
+        }
+
+        Ok(replayed)
+    }
+
+    pub async fn list_dlq_source_queues(&self, dlq_name: &str) -> Result<Vec<String>> {
+        let dlq_name = &self.normalize_queue_name(dlq_name);
+        self.db.list_dlq_source_queues(dlq_name).await
+    }
+
     #[allow(dead_code)]
     pub async fn redrive_dlq_messages(
         &self,
         dlq_name: &str,
-        source_queue: &str,
+        destination: Option<&str>,
         max_messages: Option<u32>,
-    ) -> Result<u32> {
-        // Move messages from DLQ back to source queue
+    ) -> Result<Vec<String>> {
+        let dlq_name = &self.normalize_queue_name(dlq_name);
+        let destination = destination.map(|name| self.normalize_queue_name(name));
+        // Move messages from DLQ back to the destination or their source queues
         self.db
-            .redrive_dlq_messages(dlq_name, source_queue, max_messages)
+            .redrive_dlq_messages(
+                dlq_name,
+                destination.as_deref(),
+                max_messages,
+                self.id_generator,
+            )
             .await
     }
 
+    // StartMessageMoveTask: drain a DLQ in the background into `destination`, or back to the
+    // queue each message came from. `max_per_second` paces the move; without it messages
+    // are moved as fast as possible. Returns the task handle.
+    pub fn start_message_move_task(
+        &self,
+        dlq_name: &str,
+        destination: Option<&str>,
+        max_per_second: Option<u32>,
+    ) -> String {
+        let dlq_name = &self.normalize_queue_name(dlq_name);
+        let task_handle = uuid::Uuid::new_v4().to_string();
+        let db = self.db.clone();
+        let notifiers = Arc::clone(&self.message_notifiers);
+        let id_generator = self.id_generator;
+        let dlq_name = dlq_name.to_string();
+        let destination = destination.map(|name| self.normalize_queue_name(name));
+        let handle = task_handle.clone();
+
+        tokio::spawn(async move {
+            let batch_size = max_per_second.unwrap_or(MESSAGE_MOVE_BATCH_SIZE);
+            let mut moved = 0;
+            loop {
+                let queues = match db
+                    .redrive_dlq_messages(
+                        &dlq_name,
+                        destination.as_deref(),
+                        Some(batch_size),
+                        id_generator,
+                    )
+                    .await
+                {
+                    Ok(queues) => queues,
+                    Err(e) => {
+                        tracing::error!("Message move task {} failed: {}", handle, e);
+                        break;
+                    }
+                };
+                moved += queues.len();
+
+                // Wake long polls on every queue that received messages
+                let notifiers = notifiers.read().await;
+                for queue in queues.iter().collect::<std::collections::HashSet<_>>() {
+                    if let Some(sender) = notifiers.get(queue) {
+                        let _ = sender.send(());
+                    }
+                }
+                drop(notifiers);
+
+                if queues.len() < batch_size as usize {
+                    break;
+                }
+                if max_per_second.is_some() {
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+            tracing::info!(
+                "Message move task {} moved {} messages out of {}",
+                handle,
+                moved,
+                dlq_name
+            );
+        });
+
+        task_handle
+    }
+
     #[allow(dead_code)]
     pub async fn purge_dlq(&self, dlq_name: &str) -> Result<u32> {
+        let dlq_name = &self.normalize_queue_name(dlq_name);
         // Delete all messages from DLQ
         self.db.purge_dlq(dlq_name).await
     }
@@ -329,15 +1033,121 @@ impl QueueService {
         &self,
         retention_config: &crate::config::RetentionConfig,
     ) -> Result<u32> {
-        self.db.cleanup_expired_messages(retention_config).await
+        let count = self.db.cleanup_expired_messages(retention_config).await?;
+
+        match retention_config.mode {
+            // In KeepForever mode the cleanup count is in-flight messages whose visibility expired
+            crate::config::RetentionMode::KeepForever => {
+                self.metrics.add_timeout_redeliveries(count as u64)
+            }
+            crate::config::RetentionMode::Delete => {
+                self.metrics.add_retention_deleted(count as u64)
+            }
+        }
+
+        Ok(count)
+    }
+
+    // Applies in both retention modes; a no-op unless prune_deleted_after_seconds is set
+    pub async fn prune_deleted_messages(
+        &self,
+        retention_config: &crate::config::RetentionConfig,
+    ) -> Result<u32> {
+        let Some(prune_after) = retention_config.prune_deleted_after_seconds else {
+            return Ok(0);
+        };
+
+        let cutoff = chrono::Utc::now() - chrono::Duration::seconds(prune_after as i64);
+        self.db.prune_deleted_messages(&cutoff.to_rfc3339()).await
+    }
+
+    // Applies in both retention modes; a no-op unless dlq_retention_seconds is set
+    pub async fn cleanup_dead_letter_messages(
+        &self,
+        retention_config: &crate::config::RetentionConfig,
+    ) -> Result<u32> {
+        let Some(retention_seconds) = retention_config.dlq_retention_seconds else {
+            return Ok(0);
+        };
+
+        let cutoff = chrono::Utc::now() - chrono::Duration::seconds(retention_seconds as i64);
+        let count = self
+            .db
+            .cleanup_dead_letter_messages(&cutoff.to_rfc3339())
+            .await?;
+        self.metrics.add_dlq_retention_deleted(count as u64);
+
+        Ok(count)
+    }
+
+    // Counter maintenance
+    pub async fn reconcile_counters(&self) -> Result<u32> {
+        let corrected = self.db.reconcile_queue_counters().await?;
+        self.metrics.add_counter_drift_corrected(corrected as u64);
+        Ok(corrected)
+    }
+
+    // Only a checkpoint that wasn't blocked by readers or writers counts as completed
+    pub async fn wal_checkpoint(&self) -> Result<WalCheckpoint> {
+        let checkpoint = self.db.wal_checkpoint().await?;
+        if !checkpoint.busy {
+            self.metrics
+                .record_wal_checkpoint(chrono::Utc::now().timestamp() as u64);
+        }
+        Ok(checkpoint)
+    }
+
+    pub fn wal_size_bytes(&self) -> u64 {
+        self.db.wal_size_bytes()
+    }
+
+    pub async fn storage_stats(&self) -> Result<StorageStats> {
+        self.db.storage_stats().await
+    }
+
+    pub async fn get_queue_counters(&self) -> Result<Vec<(String, String, u32)>> {
+        self.db.get_queue_counters().await
+    }
+
+    pub async fn get_inflight_age_stats(&self) -> Result<Vec<InflightAgeStats>> {
+        self.db.get_inflight_age_stats().await
     }
 
     // Background cleanup task for production performance
 
     // Enhanced queue configuration
-    #[allow(dead_code)]
     pub async fn get_queue_config(&self, queue_name: &str) -> Result<Option<QueueConfig>> {
-        self.db.get_queue_config(queue_name).await
+        let queue_name = &self.normalize_queue_name(queue_name);
+        if let Some(config) = self.db.get_queue_config(queue_name).await? {
+            return Ok(Some(config));
+        }
+
+        // Queues created without explicit configuration run with the defaults
+        if self.db.get_queue_attributes(queue_name).await?.is_some() {
+            let is_fifo = queue_name.ends_with(".fifo");
+            return Ok(Some(QueueConfig::new(queue_name.to_string(), is_fifo)));
+        }
+
+        Ok(None)
+    }
+
+    // Create a new queue with the same configuration as an existing one.
+    // Messages are not copied. Returns None when the source queue doesn't exist.
+    pub async fn clone_queue(
+        &self,
+        source_queue: &str,
+        new_name: &str,
+    ) -> Result<Option<QueueConfig>> {
+        let source_queue = &self.normalize_queue_name(source_queue);
+        let new_name = &self.normalize_queue_name(new_name);
+        let Some(mut config) = self.get_queue_config(source_queue).await? else {
+            return Ok(None);
+        };
+
+        config.name = new_name.to_string();
+        self.db.create_queue_with_config(&config).await?;
+
+        Ok(Some(config))
     }
 
     pub async fn set_queue_attributes(
@@ -345,28 +1155,27 @@ impl QueueService {
         queue_name: &str,
         attributes: HashMap<String, String>,
     ) -> Result<()> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        let attributes = self.normalize_attributes(&attributes);
         // Delegate to database layer for actual implementation
         self.db.set_queue_attributes(queue_name, &attributes).await
     }
 
-    pub async fn send_message_enhanced(
-        &self,
-        queue_name: &str,
-        body: &str,
-        attributes: Option<HashMap<String, MessageAttributeValue>>,
-        deduplication_id: Option<String>,
-        delay_seconds: u32,
-    ) -> Result<String> {
-        // For FIFO queues, MessageGroupId is required but we'll use a default for backwards compatibility
-        self.send_message_enhanced_with_group(
-            queue_name,
-            body,
-            attributes,
-            deduplication_id,
-            delay_seconds,
-            None,
-        )
-        .await
+    // The delay for a send without DelaySeconds: the queue's DelaySeconds, or
+    // queues.default_message_delay_seconds when the queue has none. The queue config can't
+    // tell DelaySeconds=0 from unset, so 0 falls back to the default too.
+    pub async fn default_delay_seconds(&self, queue_name: &str) -> Result<u32> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        let queue_delay = self
+            .db
+            .get_queue_config(queue_name)
+            .await?
+            .map_or(0, |config| config.delay_seconds);
+        Ok(if queue_delay > 0 {
+            queue_delay
+        } else {
+            self.default_message_delay_seconds
+        })
     }
 
     pub async fn send_message_enhanced_with_group(
@@ -375,10 +1184,17 @@ impl QueueService {
         body: &str,
         attributes: Option<HashMap<String, MessageAttributeValue>>,
         deduplication_id: Option<String>,
-        delay_seconds: u32,
+        delay_seconds: Option<u32>,
         message_group_id: Option<String>,
     ) -> Result<String> {
-        let mut message = Message::new(queue_name.to_string(), body.to_string());
+        let queue_name = &self.normalize_queue_name(queue_name);
+        let delay_seconds = match delay_seconds {
+            Some(delay_seconds) => delay_seconds,
+            None => self.default_delay_seconds(queue_name).await?,
+        };
+
+        let mut message =
+            Message::new(queue_name.to_string(), body.to_string(), &self.id_generator);
 
         if let Some(attrs) = attributes {
             message = message.with_attributes(attrs);
@@ -396,7 +1212,6 @@ impl QueueService {
             message = message.with_message_group_id(group_id);
         }
 
-        let message_id = message.id.clone();
         let attributes_json = message
             .attributes
             .as_ref()
@@ -413,42 +1228,123 @@ impl QueueService {
             delay_until: delay_until_str.as_deref(),
             message_group_id: message.message_group_id.as_deref(),
         };
-        self.db.send_message_with_delay_and_group(params).await?;
-
-        // Notify any waiting long polling requests
-        self.notify_message_arrival(queue_name).await;
+        let message_id = self.db.send_message_with_delay_and_group(params).await?;
+
+        // Notify any waiting long polling requests, unless this was a duplicate
+        if message_id == message.id {
+            self.notify_message_arrival(queue_name).await;
+            self.webhooks
+                .dispatch(WebhookEvent::Sent, queue_name, &message_id, None);
+            self.route_copies(&message, delay_seconds).await?;
+        }
 
         Ok(message_id)
     }
 
+    // Enqueue a copy of the message to the target of every matching routing rule.
+    // Copies go through the batch path, so they aren't routed again.
+    async fn route_copies(&self, message: &Message, delay_seconds: u32) -> Result<()> {
+        let entries: Vec<BatchSendEntry> = self
+            .routing_rules
+            .iter()
+            .filter(|rule| rule.source_queue == message.queue_name)
+            .filter(|rule| {
+                message
+                    .attributes
+                    .as_ref()
+                    .and_then(|attrs| attrs.get(&rule.attribute))
+                    .and_then(|attr| attr.string_value.as_deref())
+                    == Some(rule.value.as_str())
+            })
+            .map(|rule| {
+                (
+                    rule.target_queue.clone(),
+                    self.id_generator.generate(&rule.target_queue),
+                    message.body.clone(),
+                    message.attributes.clone(),
+                    None,
+                    delay_seconds,
+                    message.message_group_id.clone(),
+                )
+            })
+            .collect();
+
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        for result in self.send_messages_batch(entries).await? {
+            if let Err(e) = result {
+                tracing::warn!("Failed to route a copy of message {}: {}", message.id, e);
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(dead_code)]
     pub async fn receive_messages_enhanced(
         &self,
         queue_name: &str,
         max_messages: u32,
         wait_time_seconds: u32,
     ) -> Result<Vec<ReceivedMessage>> {
-        let mut messages = Vec::new();
+        self.receive_messages_with_minimum(queue_name, max_messages, wait_time_seconds, 1)
+            .await
+    }
+
+    // QLite extension: keep long polling until at least min_messages have been gathered
+    // or the wait time expires. Messages gathered while waiting are already in flight.
+    pub async fn receive_messages_with_minimum(
+        &self,
+        queue_name: &str,
+        max_messages: u32,
+        wait_time_seconds: u32,
+        min_messages: u32,
+    ) -> Result<Vec<ReceivedMessage>> {
+        self.receive_messages_as(
+            queue_name,
+            max_messages,
+            wait_time_seconds,
+            min_messages,
+            None,
+        )
+        .await
+    }
+
+    pub async fn receive_messages_as(
+        &self,
+        queue_name: &str,
+        max_messages: u32,
+        wait_time_seconds: u32,
+        min_messages: u32,
+        consumer_id: Option<&str>,
+    ) -> Result<Vec<ReceivedMessage>> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        let min_messages = min_messages.clamp(1, max_messages.max(1)) as usize;
+
+        // Subscribe before the first check so a send racing with it still wakes us up
+        let mut notification_receiver = if wait_time_seconds > 0 {
+            Some(self.get_notification_receiver(queue_name).await)
+        } else {
+            None
+        };
 
         // First, try to get available messages immediately
-        for _ in 0..max_messages {
-            if let Some(message) = self.receive_message(queue_name).await? {
-                messages.push(message);
-            } else {
-                break;
-            }
-        }
+        let mut messages = Vec::new();
+        self.fill_available_messages(queue_name, max_messages, consumer_id, &mut messages)
+            .await?;
 
-        // If we have messages or no wait time requested, return immediately
-        if !messages.is_empty() || wait_time_seconds == 0 {
+        // Return whatever is available, like SQS does, as soon as the minimum is met
+        let Some(receiver) = notification_receiver.as_mut() else {
+            return Ok(messages);
+        };
+        if messages.len() >= min_messages {
             return Ok(messages);
         }
 
         // Implement efficient long polling with notifications
         let wait_duration =
             std::time::Duration::from_secs(std::cmp::min(wait_time_seconds, 20) as u64);
-        let mut notification_receiver = self.get_notification_receiver(queue_name).await;
-
-        // Use tokio::select! to wait for either a timeout or a notification
         let timeout_future = tokio::time::sleep(wait_duration);
         tokio::pin!(timeout_future);
 
@@ -459,40 +1355,13 @@ impl QueueService {
                     break;
                 }
                 // Notification received (new message might be available)
-                result = notification_receiver.recv() => {
+                result = receiver.recv() => {
                     match result {
-                        Ok(_) => {
-                            // Check for messages again
-                            for _ in 0..(max_messages - messages.len() as u32) {
-                                if let Some(message) = self.receive_message(queue_name).await? {
-                                    messages.push(message);
-                                    if messages.len() >= max_messages as usize {
-                                        return Ok(messages);
-                                    }
-                                } else {
-                                    break;
-                                }
-                            }
-
-                            // If we got messages, return them
-                            if !messages.is_empty() {
-                                break;
-                            }
-                        }
-                        Err(broadcast::error::RecvError::Lagged(_)) => {
-                            // Channel lagged, try to get messages anyway
-                            for _ in 0..(max_messages - messages.len() as u32) {
-                                if let Some(message) = self.receive_message(queue_name).await? {
-                                    messages.push(message);
-                                    if messages.len() >= max_messages as usize {
-                                        return Ok(messages);
-                                    }
-                                } else {
-                                    break;
-                                }
-                            }
-
-                            if !messages.is_empty() {
+                        // A lagged channel still means messages arrived
+                        Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {
+                            self.fill_available_messages(queue_name, max_messages, consumer_id, &mut messages)
+                                .await?;
+                            if messages.len() >= min_messages {
                                 break;
                             }
                         }
@@ -508,6 +1377,27 @@ impl QueueService {
         Ok(messages)
     }
 
+    // Receive visible messages until max_messages is reached or the queue has none left
+    async fn fill_available_messages(
+        &self,
+        queue_name: &str,
+        max_messages: u32,
+        consumer_id: Option<&str>,
+        messages: &mut Vec<ReceivedMessage>,
+    ) -> Result<()> {
+        while messages.len() < max_messages as usize {
+            let delivered_ids: Vec<String> = messages.iter().map(|m| m.id.clone()).collect();
+            match self
+                .receive_next_message(queue_name, consumer_id, &delivered_ids)
+                .await?
+            {
+                Some(message) => messages.push(message),
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
     // Batch operations for Phase 2
     pub async fn send_messages_batch(
         &self,
@@ -515,13 +1405,24 @@ impl QueueService {
     ) -> Result<Vec<BatchSendResult>> {
         // Track which queues need notifications
         let mut queues_to_notify = std::collections::HashSet::new();
+        let mut sent_messages = Vec::with_capacity(entries.len());
 
         // Transform queue service entries to database format
         let db_entries: Vec<DelayedMessageTuple> = entries
             .into_iter()
             .map(
-                |(queue_name, message_id, body, attributes, deduplication_id, delay_seconds)| {
+                |(
+                    queue_name,
+                    message_id,
+                    body,
+                    attributes,
+                    deduplication_id,
+                    delay_seconds,
+                    message_group_id,
+                )| {
+                    let queue_name = self.normalize_queue_name(&queue_name);
                     queues_to_notify.insert(queue_name.clone());
+                    sent_messages.push((queue_name.clone(), message_id.clone()));
                     let attributes_json =
                         attributes.map(|attrs| serde_json::to_string(&attrs).unwrap());
                     let delay_until = if delay_seconds > 0 {
@@ -540,35 +1441,97 @@ impl QueueService {
                         attributes_json,
                         deduplication_id,
                         delay_until,
+                        message_group_id,
                     )
                 },
             )
             .collect();
 
-        let results = self.db.send_messages_batch(db_entries).await?;
+        let atomic = self.batch_mode == BatchMode::AllOrNothing;
+        let results = self.db.send_messages_batch(db_entries, atomic).await?;
+
+        // A rolled-back batch stored nothing, so no entry succeeded and nothing is announced
+        if atomic && results.iter().any(|r| r.is_err()) {
+            return Ok(results
+                .into_iter()
+                .map(|r| match r {
+                    Ok(_) => {
+                        Err("Rolled back because another entry in the batch failed".to_string())
+                    }
+                    Err(e) => Err(e),
+                })
+                .collect());
+        }
 
         // Notify all affected queues
         for queue_name in queues_to_notify {
             self.notify_message_arrival(&queue_name).await;
         }
 
-        // Transform database results back to service layer format
-        let mut service_results = Vec::new();
-        for result in results.into_iter() {
-            match result {
-                Ok(_) => service_results.push(Ok("Success".to_string())), // In real SQS, this would be MessageId
-                Err(e) => service_results.push(Err(e)),
+        // Duplicates come back with the original message's id and aren't announced again
+        for ((queue_name, message_id), result) in sent_messages.iter().zip(&results) {
+            if result
+                .as_ref()
+                .is_ok_and(|stored_id| stored_id == message_id)
+            {
+                self.webhooks
+                    .dispatch(WebhookEvent::Sent, queue_name, message_id, None);
             }
         }
 
-        Ok(service_results)
+        Ok(results)
     }
 
     pub async fn delete_messages_batch(
         &self,
-        message_ids: Vec<String>,
-    ) -> Result<Vec<std::result::Result<bool, String>>> {
-        self.db.delete_messages_batch(message_ids).await
+        queue_name: &str,
+        receipt_handles: Vec<String>,
+    ) -> Result<Vec<std::result::Result<DeleteOutcome, String>>> {
+        let queue_name = &self.normalize_queue_name(queue_name);
+        let resolved: Vec<Option<String>> = receipt_handles
+            .iter()
+            .map(|receipt_handle| self.resolve_receipt_handle(Some(queue_name), receipt_handle))
+            .collect();
+        let receipt_handles: Vec<String> = resolved.iter().flatten().cloned().collect();
+
+        let mut queue_names = Vec::with_capacity(receipt_handles.len());
+        for receipt_handle in &receipt_handles {
+            queue_names.push(
+                self.queue_for_webhook(receipt_message_id(receipt_handle))
+                    .await?,
+            );
+        }
+
+        let results = self
+            .db
+            .delete_messages_batch(receipt_handles.clone())
+            .await?;
+        let count = |outcome| results.iter().filter(|r| **r == Ok(outcome)).count() as u64;
+        self.metrics
+            .add_consumer_deleted(count(DeleteOutcome::Deleted));
+        for _ in 0..count(DeleteOutcome::StaleHandle) {
+            self.metrics.inc_stale_receipt_handle();
+        }
+
+        for ((receipt_handle, queue_name), result) in
+            receipt_handles.iter().zip(queue_names).zip(&results)
+        {
+            let message_id = receipt_message_id(receipt_handle);
+            if let (Some(queue_name), Ok(DeleteOutcome::Deleted)) = (queue_name, result) {
+                self.webhooks
+                    .dispatch(WebhookEvent::Deleted, &queue_name, message_id, None);
+            }
+        }
+
+        // Handles that failed verification never reached the database
+        let mut results = results.into_iter();
+        Ok(resolved
+            .iter()
+            .map(|handle| match handle {
+                Some(_) => results.next().unwrap_or(Ok(DeleteOutcome::NotFound)),
+                None => Ok(DeleteOutcome::NotFound),
+            })
+            .collect())
     }
 
     pub async fn receive_messages_batch(
@@ -576,22 +1539,46 @@ impl QueueService {
         queue_name: &str,
         max_messages: u32,
     ) -> Result<Vec<ReceivedMessage>> {
-        let db_messages = self
+        let queue_name = &self.normalize_queue_name(queue_name);
+        let (db_messages, dead_lettered) = self
             .db
             .receive_messages_batch(queue_name, max_messages)
             .await?;
+        self.notify_dead_lettered(dead_lettered);
 
         let mut messages = Vec::new();
-        for (id, body, _created_at, attributes_json) in db_messages {
+        for (
+            id,
+            body,
+            created_at,
+            attributes_json,
+            receipt_handle,
+            receive_count,
+            first_received_at,
+            visibility_deadline,
+        ) in db_messages
+        {
             let attributes = if let Some(json) = attributes_json {
                 serde_json::from_str(&json).ok()
             } else {
                 None
             };
 
-            messages.push(ReceivedMessage::new(id, body, attributes));
+            self.webhooks
+                .dispatch(WebhookEvent::Received, queue_name, &id, None);
+            messages.push(
+                ReceivedMessage::new(id, body, attributes)
+                    .with_receipt_handle(self.issue_receipt_handle(
+                        queue_name,
+                        receipt_handle,
+                        &visibility_deadline,
+                    ))
+                    .with_sent_timestamp(&created_at)
+                    .with_receive_stats(receive_count, &first_received_at),
+            );
         }
 
+        self.log_deliveries(&messages, None).await?;
         Ok(messages)
     }
 }
@@ -1,8 +1,9 @@
 use crate::config::Config;
 use crate::queue_service::QueueService;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio_cron_scheduler::{Job, JobScheduler};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 pub struct RetentionCleanupService {
     scheduler: JobScheduler,
@@ -89,12 +90,135 @@ impl RetentionCleanupService {
                 error!("Failed to run retention cleanup: {}", e);
             }
         }
+
+        match queue_service
+            .prune_deleted_messages(&retention_config)
+            .await
+        {
+            Ok(0) => {}
+            Ok(pruned) => info!("Pruned {} deleted messages", pruned),
+            Err(e) => error!("Failed to prune deleted messages: {}", e),
+        }
+
+        match queue_service
+            .cleanup_dead_letter_messages(&retention_config)
+            .await
+        {
+            Ok(0) => {}
+            Ok(deleted) => info!("DLQ cleanup completed: {} dead letters deleted", deleted),
+            Err(e) => error!("Failed to run DLQ retention cleanup: {}", e),
+        }
+    }
+}
+
+pub struct CounterReconciliationService {
+    scheduler: JobScheduler,
+    queue_service: Arc<QueueService>,
+    interval_seconds: u32,
+}
+
+impl CounterReconciliationService {
+    pub async fn new(
+        queue_service: Arc<QueueService>,
+        config: &Config,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let scheduler = JobScheduler::new().await?;
+
+        Ok(Self {
+            scheduler,
+            queue_service,
+            interval_seconds: config.metrics.counter_reconcile_interval_seconds,
+        })
+    }
+
+    pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let queue_service = Arc::clone(&self.queue_service);
+
+        let job = Job::new_repeated_async(
+            Duration::from_secs(self.interval_seconds as u64),
+            move |_uuid, _l| {
+                let queue_service_clone = Arc::clone(&queue_service);
+                Box::pin(async move {
+                    match queue_service_clone.reconcile_counters().await {
+                        Ok(0) => {}
+                        Ok(corrected) => {
+                            warn!(
+                                "Counter reconciliation corrected {} drifted rows",
+                                corrected
+                            )
+                        }
+                        Err(e) => error!("Failed to reconcile queue counters: {}", e),
+                    }
+                })
+            },
+        )?;
+
+        self.scheduler.add(job).await?;
+        self.scheduler.start().await?;
+
+        info!(
+            "Counter reconciliation service started with interval: {} seconds",
+            self.interval_seconds
+        );
+        Ok(())
+    }
+}
+
+pub struct WalCheckpointService {
+    scheduler: JobScheduler,
+    queue_service: Arc<QueueService>,
+    interval_seconds: u32,
+}
+
+impl WalCheckpointService {
+    pub async fn new(
+        queue_service: Arc<QueueService>,
+        config: &Config,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let scheduler = JobScheduler::new().await?;
+
+        Ok(Self {
+            scheduler,
+            queue_service,
+            interval_seconds: config.database.wal_checkpoint_interval_seconds,
+        })
+    }
+
+    pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let queue_service = Arc::clone(&self.queue_service);
+
+        let job = Job::new_repeated_async(
+            Duration::from_secs(self.interval_seconds as u64),
+            move |_uuid, _l| {
+                let queue_service_clone = Arc::clone(&queue_service);
+                Box::pin(async move {
+                    match queue_service_clone.wal_checkpoint().await {
+                        Ok(checkpoint) if checkpoint.busy => {
+                            warn!("WAL checkpoint could not complete; database was busy")
+                        }
+                        Ok(_) => {}
+                        Err(e) => error!("Failed to checkpoint WAL: {}", e),
+                    }
+                })
+            },
+        )?;
+
+        self.scheduler.add(job).await?;
+        self.scheduler.start().await?;
+
+        info!(
+            "WAL checkpoint service started with interval: {} seconds",
+            self.interval_seconds
+        );
+        Ok(())
     }
 }
 
 // Background service for handling all periodic tasks
 pub struct BackgroundServices {
     retention_service: Option<RetentionCleanupService>,
+    counter_reconciliation_service: Option<CounterReconciliationService>,
+    wal_checkpoint_service: Option<WalCheckpointService>,
 }
 
 impl Default for BackgroundServices {
@@ -107,6 +231,8 @@ impl BackgroundServices {
     pub fn new() -> Self {
         Self {
             retention_service: None,
+            counter_reconciliation_service: None,
+            wal_checkpoint_service: None,
         }
     }
 
@@ -120,6 +246,28 @@ impl BackgroundServices {
         self.retention_service = Some(service);
         Ok(())
     }
+
+    pub async fn start_counter_reconciliation(
+        &mut self,
+        queue_service: Arc<QueueService>,
+        config: &Config,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let service = CounterReconciliationService::new(queue_service, config).await?;
+        service.start().await?;
+        self.counter_reconciliation_service = Some(service);
+        Ok(())
+    }
+
+    pub async fn start_wal_checkpoint(
+        &mut self,
+        queue_service: Arc<QueueService>,
+        config: &Config,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let service = WalCheckpointService::new(queue_service, config).await?;
+        service.start().await?;
+        self.wal_checkpoint_service = Some(service);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -130,5 +278,7 @@ mod tests {
     fn test_background_services_creation() {
         let services = BackgroundServices::new();
         assert!(services.retention_service.is_none());
+        assert!(services.counter_reconciliation_service.is_none());
+        assert!(services.wal_checkpoint_service.is_none());
     }
 }
@@ -25,6 +25,11 @@ pub struct SendMessageResult {
     pub message_id: String,
     #[serde(rename = "MD5OfBody")]
     pub md5_of_body: String,
+    #[serde(
+        rename = "MD5OfMessageAttributes",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub md5_of_message_attributes: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -45,6 +50,7 @@ pub struct SqsMessage {
     pub message_id: String,
     #[serde(rename = "ReceiptHandle")]
     pub receipt_handle: String,
+    // Stored raw; quick-xml's serializer escapes &, <, >, and quotes, so never pre-escape
     #[serde(rename = "Body")]
     pub body: String,
     #[serde(rename = "Attributes", default)]
@@ -62,6 +68,15 @@ pub struct DeleteMessageResponse {
 #[derive(Debug, Serialize)]
 pub struct DeleteMessageResult {}
 
+#[derive(Debug, Serialize)]
+pub struct ChangeMessageVisibilityResponse {
+    #[serde(rename = "ChangeMessageVisibilityResult")]
+    pub change_message_visibility_result: ChangeMessageVisibilityResult,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangeMessageVisibilityResult {}
+
 #[derive(Debug, Serialize)]
 pub struct ListQueuesResponse {
     #[serde(rename = "ListQueuesResult")]
@@ -72,6 +87,79 @@ pub struct ListQueuesResponse {
 pub struct ListQueuesResult {
     #[serde(rename = "QueueUrl", default)]
     pub queue_urls: Vec<String>,
+    // QLite extension, only present when ListQueues is called with IncludeTags=true
+    #[serde(rename = "QueueTags", skip_serializing_if = "Option::is_none")]
+    pub queue_tags: Option<Vec<QueueTagSet>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueueTagSet {
+    #[serde(rename = "QueueUrl")]
+    pub queue_url: String,
+    #[serde(rename = "Tag", default)]
+    pub tags: Vec<QueueTag>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueueTag {
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "Value")]
+    pub value: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TagQueueResponse {
+    #[serde(rename = "TagQueueResult")]
+    pub tag_queue_result: TagQueueResult,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TagQueueResult {}
+
+#[derive(Debug, Serialize)]
+pub struct UntagQueueResponse {
+    #[serde(rename = "UntagQueueResult")]
+    pub untag_queue_result: UntagQueueResult,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UntagQueueResult {}
+
+#[derive(Debug, Serialize)]
+pub struct ListQueueTagsResponse {
+    #[serde(rename = "ListQueueTagsResult")]
+    pub list_queue_tags_result: ListQueueTagsResult,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListQueueTagsResult {
+    #[serde(rename = "Tag", default)]
+    pub tags: Vec<QueueTag>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListDeadLetterSourceQueuesResponse {
+    #[serde(rename = "ListDeadLetterSourceQueuesResult")]
+    pub list_dead_letter_source_queues_result: ListDeadLetterSourceQueuesResult,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListDeadLetterSourceQueuesResult {
+    #[serde(rename = "QueueUrl", default)]
+    pub queue_urls: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StartMessageMoveTaskResponse {
+    #[serde(rename = "StartMessageMoveTaskResult")]
+    pub start_message_move_task_result: StartMessageMoveTaskResult,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StartMessageMoveTaskResult {
+    #[serde(rename = "TaskHandle")]
+    pub task_handle: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -136,6 +224,15 @@ pub struct DeleteQueueResponse {
 #[derive(Debug, Serialize)]
 pub struct DeleteQueueResult {}
 
+#[derive(Debug, Serialize)]
+pub struct PurgeQueueResponse {
+    #[serde(rename = "PurgeQueueResult")]
+    pub purge_queue_result: PurgeQueueResult,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PurgeQueueResult {}
+
 #[derive(Debug, Serialize)]
 pub struct SendMessageBatchResponse {
     #[serde(rename = "SendMessageBatchResult")]
@@ -158,6 +255,11 @@ pub struct SendMessageBatchResultEntry {
     pub message_id: String,
     #[serde(rename = "MD5OfBody")]
     pub md5_of_body: String,
+    #[serde(
+        rename = "MD5OfMessageAttributes",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub md5_of_message_attributes: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
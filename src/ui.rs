@@ -1,13 +1,18 @@
 use askama::Template;
 use axum::{
-    extract::{Form, Path, State},
+    extract::{Form, Path, Query, State},
     http::StatusCode,
     response::{Html, Json, Redirect},
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::http_server::AppState;
+use crate::{
+    api::{ApiResponse, tag_filter_from_query},
+    database::QueueDeletion,
+    http_server::AppState,
+};
 
 #[derive(Template)]
 #[template(path = "dashboard.html")]
@@ -18,12 +23,17 @@ pub struct DashboardTemplate {
     pub total_messages: usize,
     pub total_available_messages: usize,
     pub total_in_flight_messages: usize,
+    pub tag_filter: Vec<(String, String)>,
 }
 
 #[derive(Template)]
-#[template(path = "messages.html")]
+#[template(path = "message_page.html")]
 pub struct MessagesTemplate {
     pub messages: Vec<MessageInfo>,
+    pub queue_name: String,
+    // 1-based
+    pub page: usize,
+    pub has_next_page: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +42,7 @@ pub struct QueueInfo {
     pub created_at: String,
     pub available_messages: u32,
     pub in_flight_messages: u32,
+    pub tags: Vec<(String, String)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,17 +59,17 @@ pub struct MessageInfo {
     pub deleted_at: String,
 }
 
-#[derive(Debug, Serialize)]
-pub struct ApiResponse {
-    pub success: bool,
-    pub message: String,
-}
+pub async fn dashboard(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Html<String>, String> {
+    // Same `tag.<key>=<value>` filter as GET /api/queues
+    let tag_filter = tag_filter_from_query(&params)?;
 
-pub async fn dashboard(State(state): State<Arc<AppState>>) -> Result<Html<String>, String> {
-    // Get all queues
+    // Get matching queues, with their tags for the badges
     let queues_data = state
         .queue_service
-        .list_queues()
+        .list_queues_with_tags(&tag_filter)
         .await
         .map_err(|e| format!("Failed to list queues: {}", e))?;
 
@@ -67,7 +78,7 @@ pub async fn dashboard(State(state): State<Arc<AppState>>) -> Result<Html<String
     let mut total_in_flight = 0u32;
 
     // Get queue attributes for each queue
-    for (queue_name, created_at) in queues_data {
+    for (queue_name, created_at, tags) in queues_data {
         if let Ok(Some(attrs)) = state.queue_service.get_queue_attributes(&queue_name).await {
             total_available += attrs.approximate_number_of_messages;
             total_in_flight += attrs.approximate_number_of_messages_not_visible;
@@ -77,6 +88,7 @@ pub async fn dashboard(State(state): State<Arc<AppState>>) -> Result<Html<String
                 created_at,
                 available_messages: attrs.approximate_number_of_messages,
                 in_flight_messages: attrs.approximate_number_of_messages_not_visible,
+                tags,
             });
         }
     }
@@ -88,6 +100,7 @@ pub async fn dashboard(State(state): State<Arc<AppState>>) -> Result<Html<String
         total_in_flight_messages: total_in_flight as usize,
         queues,
         messages: vec![], // Empty by default, populated when a queue is selected
+        tag_filter,
     };
 
     let html = template
@@ -97,19 +110,33 @@ pub async fn dashboard(State(state): State<Arc<AppState>>) -> Result<Html<String
     Ok(Html(html))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct MessagesPageQuery {
+    pub page: Option<usize>,
+}
+
+// One page of a queue's messages, ui_messages_per_page at a time
 pub async fn queue_messages(
     Path(queue_name): Path<String>,
+    Query(query): Query<MessagesPageQuery>,
     State(state): State<Arc<AppState>>,
 ) -> Result<Html<String>, String> {
-    // Get messages for the specific queue by reading directly from database
-    // Since we don't have a direct method, we'll simulate it by getting queue info
-    // and then fetching some messages (this is a simplified approach)
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = state.queue_service.ui_messages_per_page();
 
-    let messages = get_queue_messages(&state, &queue_name)
+    // One extra row tells whether there is a next page
+    let mut messages = get_queue_messages(&state, &queue_name, per_page + 1, (page - 1) * per_page)
         .await
         .map_err(|e| format!("Failed to get messages: {}", e))?;
-
-    let template = MessagesTemplate { messages };
+    let has_next_page = messages.len() > per_page;
+    messages.truncate(per_page);
+
+    let template = MessagesTemplate {
+        messages,
+        queue_name,
+        page,
+        has_next_page,
+    };
 
     let html = template
         .render()
@@ -121,10 +148,12 @@ pub async fn queue_messages(
 async fn get_queue_messages(
     state: &Arc<AppState>,
     queue_name: &str,
+    limit: usize,
+    offset: usize,
 ) -> Result<Vec<MessageInfo>, Box<dyn std::error::Error>> {
     let messages_data = state
         .queue_service
-        .get_all_queue_messages(queue_name)
+        .get_queue_messages_page(queue_name, limit, offset)
         .await?;
 
     let mut messages = Vec::new();
@@ -262,12 +291,23 @@ pub async fn delete_queue_ui(
     State(state): State<Arc<AppState>>,
     Path(queue_name): Path<String>,
 ) -> Result<Redirect, String> {
-    match state.queue_service.delete_queue(&queue_name).await {
+    match state.queue_service.delete_queue(&queue_name, false).await {
+        Ok(QueueDeletion::DeadLetterTargetInUse { source_queues }) => {
+            Err(in_use_message(&queue_name, &source_queues))
+        }
         Ok(_) => Ok(Redirect::to("/ui")),
         Err(e) => Err(format!("Failed to delete queue: {}", e)),
     }
 }
 
+fn in_use_message(queue_name: &str, source_queues: &[String]) -> String {
+    format!(
+        "Queue '{}' is the dead-letter queue of {}; remove their redrive policy first",
+        queue_name,
+        source_queues.join(", ")
+    )
+}
+
 pub async fn delete_message_ui(
     State(state): State<Arc<AppState>>,
     Path(message_id): Path<String>,
@@ -293,7 +333,14 @@ pub async fn delete_queue_json(
     State(state): State<Arc<AppState>>,
     Path(queue_name): Path<String>,
 ) -> Result<Json<ApiResponse>, (StatusCode, Json<ApiResponse>)> {
-    match state.queue_service.delete_queue(&queue_name).await {
+    match state.queue_service.delete_queue(&queue_name, false).await {
+        Ok(QueueDeletion::DeadLetterTargetInUse { source_queues }) => Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse {
+                success: false,
+                message: in_use_message(&queue_name, &source_queues),
+            }),
+        )),
         Ok(_) => Ok(Json(ApiResponse {
             success: true,
             message: format!("Queue '{}' deleted successfully", queue_name),
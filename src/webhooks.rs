@@ -0,0 +1,117 @@
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tracing::warn;
+
+use crate::config::{WebhookEvent, WebhookSubscription, WebhooksConfig};
+
+// Payload POSTed to every subscription matching an event
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub queue: String,
+    pub message_id: String,
+    pub timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+// Delivers message events to configured webhook subscriptions in the background
+#[derive(Debug, Clone)]
+pub struct WebhookDispatcher {
+    client: reqwest::Client,
+    subscriptions: Arc<Vec<WebhookSubscription>>,
+    permits: Arc<Semaphore>,
+    max_retries: u32,
+}
+
+impl WebhookDispatcher {
+    pub fn new(config: &WebhooksConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            client,
+            subscriptions: Arc::new(config.subscriptions.clone()),
+            permits: Arc::new(Semaphore::new(config.max_concurrency)),
+            max_retries: config.max_retries,
+        }
+    }
+
+    // Cheap check so callers can skip extra lookups when nobody is listening
+    pub fn has_subscribers(&self, event: WebhookEvent) -> bool {
+        self.subscriptions.iter().any(|s| s.events.contains(&event))
+    }
+
+    pub fn dispatch(
+        &self,
+        event: WebhookEvent,
+        queue: &str,
+        message_id: &str,
+        detail: Option<String>,
+    ) {
+        let urls: Vec<String> = self
+            .subscriptions
+            .iter()
+            .filter(|s| s.events.contains(&event))
+            .filter(|s| s.queue.as_deref().is_none_or(|q| q == queue))
+            .map(|s| s.url.clone())
+            .collect();
+        if urls.is_empty() {
+            return;
+        }
+
+        let payload = WebhookPayload {
+            event,
+            queue: queue.to_string(),
+            message_id: message_id.to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            detail,
+        };
+
+        for url in urls {
+            let client = self.client.clone();
+            let permits = Arc::clone(&self.permits);
+            let payload = payload.clone();
+            let max_retries = self.max_retries;
+
+            tokio::spawn(async move {
+                // Bound the number of in-flight deliveries
+                let Ok(_permit) = permits.acquire_owned().await else {
+                    return;
+                };
+                deliver(&client, &url, &payload, max_retries).await;
+            });
+        }
+    }
+}
+
+// POST with exponential backoff between attempts
+async fn deliver(client: &reqwest::Client, url: &str, payload: &WebhookPayload, max_retries: u32) {
+    let mut attempt = 0;
+    loop {
+        let error = match client.post(url).json(payload).send().await {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => format!("status {}", response.status()),
+            Err(e) => e.to_string(),
+        };
+
+        if attempt >= max_retries {
+            warn!(
+                "Giving up on webhook {} for {:?} on message {} after {} attempts: {}",
+                url,
+                payload.event,
+                payload.message_id,
+                attempt + 1,
+                error
+            );
+            return;
+        }
+
+        tokio::time::sleep(Duration::from_millis(100 * 2u64.pow(attempt))).await;
+        attempt += 1;
+    }
+}
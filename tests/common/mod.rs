@@ -0,0 +1,153 @@
+#![allow(dead_code)]
+
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+use qlite::config::Config;
+use qlite::http_server::create_router;
+use qlite::queue_service::QueueService;
+
+pub const BASE_URL: &str = "http://localhost:3000";
+
+/// Create a queue service backed by a fresh temporary database
+pub async fn test_service() -> (TempDir, Arc<QueueService>) {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("test.db");
+    let service = QueueService::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create queue service");
+    (temp_dir, Arc::new(service))
+}
+
+/// Like `test_service`, but with non-default configuration
+pub async fn test_service_with_config(config: &Config) -> (TempDir, Arc<QueueService>) {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("test.db");
+    let service = QueueService::new_with_config(db_path.to_str().unwrap(), config)
+        .await
+        .expect("Failed to create queue service");
+    (temp_dir, Arc::new(service))
+}
+
+pub fn test_router(service: Arc<QueueService>) -> Router {
+    create_router(service, BASE_URL.to_string(), false)
+}
+
+/// Send a form-encoded SQS request to the given path and return status and body
+pub async fn form_request(
+    router: &Router,
+    path: &str,
+    params: &[(&str, &str)],
+) -> (StatusCode, String) {
+    let body = params
+        .iter()
+        .map(|(k, v)| format!("{}={}", urlencoding::encode(k), urlencoding::encode(v)))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let request = Request::builder()
+        .method("POST")
+        .uri(path)
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body(Body::from(body))
+        .unwrap();
+
+    send(router, request).await
+}
+
+/// Send an AWS JSON-protocol request for the given action
+pub async fn json_request(
+    router: &Router,
+    action: &str,
+    payload: serde_json::Value,
+) -> (StatusCode, String) {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/")
+        .header("content-type", "application/x-amz-json-1.0")
+        .header("x-amz-target", format!("AmazonSQS.{}", action))
+        .body(Body::from(payload.to_string()))
+        .unwrap();
+
+    send(router, request).await
+}
+
+/// Send a JSON body to one of the `/api` management endpoints
+pub async fn json_post(
+    router: &Router,
+    path: &str,
+    payload: serde_json::Value,
+) -> (StatusCode, String) {
+    let request = Request::builder()
+        .method("POST")
+        .uri(path)
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .unwrap();
+
+    send(router, request).await
+}
+
+/// POST a JSON body to one of the `/admin` endpoints, with an optional bearer token
+pub async fn admin_post(
+    router: &Router,
+    path: &str,
+    token: Option<&str>,
+    payload: serde_json::Value,
+) -> (StatusCode, String) {
+    let mut request = Request::builder()
+        .method("POST")
+        .uri(path)
+        .header("content-type", "application/json");
+    if let Some(token) = token {
+        request = request.header("authorization", format!("Bearer {}", token));
+    }
+
+    send(
+        router,
+        request.body(Body::from(payload.to_string())).unwrap(),
+    )
+    .await
+}
+
+pub async fn get_request(router: &Router, path: &str) -> (StatusCode, String) {
+    let request = Request::builder()
+        .method("GET")
+        .uri(path)
+        .body(Body::empty())
+        .unwrap();
+
+    send(router, request).await
+}
+
+pub async fn send(router: &Router, request: Request<Body>) -> (StatusCode, String) {
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+/// Extract the text content of every `<tag>...</tag>` element in an XML document
+pub fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let mut values = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        match after.find(&close) {
+            Some(end) => {
+                values.push(after[..end].to_string());
+                rest = &after[end + close.len()..];
+            }
+            None => break,
+        }
+    }
+    values
+}
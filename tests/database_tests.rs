@@ -0,0 +1,960 @@
+use tempfile::TempDir;
+
+use qlite::config::{Config, QueueConfig, RetentionMode};
+use qlite::database::{
+    DEFAULT_MESSAGE_LIST_LIMIT, Database, EXPORT_CHANNEL_CAPACITY, SendMessageParams,
+    content_dedup_id,
+};
+use qlite::queue_service::QueueService;
+
+// A plain send straight to the database: no attributes, deduplication id, delay or group
+async fn send_message(
+    db: &Database,
+    queue_name: &str,
+    message_id: &str,
+    body: &str,
+) -> tokio_rusqlite::Result<String> {
+    db.send_message_with_delay_and_group(SendMessageParams {
+        queue_name,
+        message_id,
+        body,
+        attributes: None,
+        deduplication_id: None,
+        delay_until: None,
+        message_group_id: None,
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_queue_counters_follow_message_changes() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("counters.db");
+    let db = Database::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create database");
+
+    db.create_queue("counter-queue")
+        .await
+        .expect("Failed to create queue");
+
+    for i in 1..=3 {
+        send_message(&db, "counter-queue", &format!("msg-{}", i), "Counter test")
+            .await
+            .expect("Failed to send message");
+    }
+
+    db.delete_message("msg-1")
+        .await
+        .expect("Failed to delete message");
+
+    let counters = db
+        .get_queue_counters()
+        .await
+        .expect("Failed to read counters");
+    assert!(counters.contains(&("counter-queue".to_string(), "active".to_string(), 2)));
+    assert!(counters.contains(&("counter-queue".to_string(), "deleted".to_string(), 1)));
+
+    // Counters maintained by triggers should never need correction
+    let corrected = db
+        .reconcile_queue_counters()
+        .await
+        .expect("Failed to reconcile counters");
+    assert_eq!(corrected, 0);
+}
+
+#[tokio::test]
+async fn test_counter_reconciliation_corrects_drift() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("drift.db");
+    let service = QueueService::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create queue service");
+
+    service
+        .create_queue("drift-queue")
+        .await
+        .expect("Failed to create queue");
+    service
+        .send_message("drift-queue", "Drift test", None, None)
+        .await
+        .expect("Failed to send message");
+
+    // Simulate a manual edit that bypasses the triggers
+    let conn = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+    conn.execute(
+        "UPDATE queue_counters SET count = 42 WHERE queue_name = 'drift-queue'",
+        [],
+    )
+    .expect("Failed to tamper with counters");
+    drop(conn);
+
+    let corrected = service
+        .reconcile_counters()
+        .await
+        .expect("Failed to reconcile counters");
+    assert_eq!(corrected, 1);
+    assert_eq!(service.metrics().counter_drift_corrected_total(), 1);
+
+    let counters = service
+        .get_queue_counters()
+        .await
+        .expect("Failed to read counters");
+    assert_eq!(
+        counters,
+        vec![("drift-queue".to_string(), "active".to_string(), 1)]
+    );
+}
+
+#[tokio::test]
+async fn test_count_receives_disabled_never_dead_letters() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("broadcast.db");
+    let service = QueueService::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create queue service");
+
+    let config = QueueConfig {
+        name: "broadcast-queue".to_string(),
+        max_receive_count: Some(1),
+        dead_letter_target_arn: Some("broadcast-dlq".to_string()),
+        count_receives: false,
+        ..Default::default()
+    };
+    service
+        .create_queue_with_config(&config)
+        .await
+        .expect("Failed to create queue");
+    let message_id = service
+        .send_message("broadcast-queue", "Everyone gets this", None, None)
+        .await
+        .expect("Failed to send message");
+
+    for _ in 0..5 {
+        let received = service
+            .receive_message("broadcast-queue")
+            .await
+            .expect("Failed to receive message")
+            .expect("Message should still be deliverable");
+        assert_eq!(received.id, message_id);
+
+        // Make the message visible again for the next delivery
+        service
+            .restore_message(&message_id)
+            .await
+            .expect("Failed to restore message");
+    }
+
+    let messages = service
+        .get_oldest_queue_messages("broadcast-queue", DEFAULT_MESSAGE_LIST_LIMIT)
+        .await
+        .expect("Failed to list messages");
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].4, 0, "receive_count should not be incremented");
+    assert_eq!(messages[0].7, "active");
+}
+
+#[tokio::test]
+async fn test_inflight_age_stats() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("inflight.db");
+    let db = Database::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create database");
+
+    db.create_queue("inflight-queue")
+        .await
+        .expect("Failed to create queue");
+    for i in 1..=3 {
+        send_message(&db, "inflight-queue", &format!("msg-{}", i), "Work")
+            .await
+            .expect("Failed to send message");
+    }
+    db.receive_next_message("inflight-queue", &[])
+        .await
+        .expect("Failed to receive message");
+    db.receive_next_message("inflight-queue", &[])
+        .await
+        .expect("Failed to receive message");
+
+    // Backdate one delivery to simulate a consumer that never deleted its message
+    let processed_at = (chrono::Utc::now() - chrono::Duration::seconds(120)).to_rfc3339();
+    let conn = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+    conn.execute(
+        "UPDATE messages SET processed_at = ?1 WHERE id = 'msg-1'",
+        [&processed_at],
+    )
+    .expect("Failed to backdate message");
+    drop(conn);
+
+    let stats = db
+        .get_inflight_age_stats()
+        .await
+        .expect("Failed to read in-flight stats");
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].queue_name, "inflight-queue");
+    assert_eq!(stats[0].count, 2);
+    assert!((119.0..130.0).contains(&stats[0].max_age_seconds));
+    assert!(stats[0].sum_age_seconds >= stats[0].max_age_seconds);
+}
+
+#[tokio::test]
+async fn test_migrations_upgrade_legacy_schema_once() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("legacy.db");
+
+    // The original messages table, before any columns were added
+    let conn = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+    conn.execute(
+        r#"
+        CREATE TABLE messages (
+            id TEXT PRIMARY KEY,
+            queue_name TEXT NOT NULL,
+            body TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            visibility_timeout TEXT,
+            receive_count INTEGER DEFAULT 0,
+            attributes TEXT,
+            deduplication_id TEXT
+        )
+        "#,
+        [],
+    )
+    .expect("Failed to create legacy table");
+    drop(conn);
+
+    let db = Database::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to migrate legacy database");
+    let version = db
+        .schema_version()
+        .await
+        .expect("Failed to read schema version");
+    assert!(version > 0);
+
+    db.create_queue("legacy-queue")
+        .await
+        .expect("Failed to create queue");
+    send_message(&db, "legacy-queue", "msg-1", "After upgrade")
+        .await
+        .expect("Failed to send message");
+    assert!(
+        db.receive_next_message("legacy-queue", &[])
+            .await
+            .expect("Failed to receive message")
+            .0
+            .is_some()
+    );
+
+    // Running again, directly or by reopening, applies nothing
+    assert_eq!(db.run_migrations().await.expect("Failed to rerun"), 0);
+    drop(db);
+    let reopened = Database::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to reopen database");
+    assert_eq!(reopened.run_migrations().await.expect("Failed to rerun"), 0);
+    assert_eq!(reopened.schema_version().await.unwrap(), version);
+}
+
+#[tokio::test]
+async fn test_null_status_messages_are_receivable_after_migration() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("null-status.db");
+
+    // A status column without a default leaves older rows NULL
+    let conn = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE messages (
+            id TEXT PRIMARY KEY,
+            queue_name TEXT NOT NULL,
+            body TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            visibility_timeout TEXT,
+            receive_count INTEGER DEFAULT 0,
+            attributes TEXT,
+            deduplication_id TEXT,
+            status TEXT
+        );
+        INSERT INTO messages (id, queue_name, body, created_at)
+        VALUES ('old-msg', 'upgrade-queue', 'Sent before upgrade', '2024-01-01T00:00:00+00:00');
+        "#,
+    )
+    .expect("Failed to create legacy data");
+    drop(conn);
+
+    let db = Database::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to migrate database");
+    db.create_queue("upgrade-queue")
+        .await
+        .expect("Failed to create queue");
+
+    let (id, body, _, _, _, _, _, _) = db
+        .receive_next_message("upgrade-queue", &[])
+        .await
+        .expect("Failed to receive message")
+        .0
+        .expect("Legacy message should be receivable");
+    assert_eq!(id, "old-msg");
+    assert_eq!(body, "Sent before upgrade");
+}
+
+#[tokio::test]
+async fn test_attribute_index_is_used_for_queue_filters() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("attr-index.db");
+    let db = Database::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create database");
+    db.create_queue("events")
+        .await
+        .expect("Failed to create queue");
+
+    let index_name = db
+        .create_attribute_index("events", "type")
+        .await
+        .expect("Failed to create attribute index");
+    // Creating it again is a no-op
+    db.create_attribute_index("events", "type")
+        .await
+        .expect("Failed to recreate attribute index");
+    assert!(
+        db.create_attribute_index("events", "type'; DROP TABLE messages; --")
+            .await
+            .is_err()
+    );
+
+    let conn = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+    let plan: Vec<String> = conn
+        .prepare(
+            "EXPLAIN QUERY PLAN SELECT id FROM messages WHERE queue_name = 'events' AND json_extract(attributes, '$.\"type\".StringValue') = ?1",
+        )
+        .unwrap()
+        .query_map(["order_created"], |row| row.get::<_, String>(3))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert!(
+        plan.iter().any(|step| step.contains(&index_name)),
+        "expected {} in plan {:?}",
+        index_name,
+        plan
+    );
+}
+
+#[tokio::test]
+async fn test_delete_queue_drops_its_attribute_indexes() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("attr-index-drop.db");
+    let db = Database::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create database");
+    // "a__b"'s index name starts with "a"'s index prefix
+    for queue_name in ["a", "a__b"] {
+        db.create_queue(queue_name)
+            .await
+            .expect("Failed to create queue");
+        db.create_attribute_index(queue_name, "type")
+            .await
+            .expect("Failed to create attribute index");
+    }
+
+    db.delete_queue("a", false)
+        .await
+        .expect("Failed to delete queue");
+
+    let conn = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+    let indexes: Vec<String> = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'index' AND name LIKE 'idx_attr__%' ORDER BY name")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(indexes, vec!["idx_attr__a__b__type".to_string()]);
+}
+
+#[tokio::test]
+async fn test_dlq_retention_removes_old_dead_letters() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("dlq-retention.db");
+    let service = QueueService::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create queue service");
+
+    let config = QueueConfig {
+        name: "work-queue".to_string(),
+        max_receive_count: Some(1),
+        dead_letter_target_arn: Some("work-dlq".to_string()),
+        ..Default::default()
+    };
+    service
+        .create_queue_with_config(&config)
+        .await
+        .expect("Failed to create queue");
+    service
+        .create_queue("work-dlq")
+        .await
+        .expect("Failed to create DLQ");
+
+    let mut message_ids = Vec::new();
+    for body in ["old failure", "recent failure"] {
+        let id = service
+            .send_message("work-queue", body, None, None)
+            .await
+            .expect("Failed to send message");
+        assert!(
+            service
+                .move_message_to_dlq(&id, "test")
+                .await
+                .expect("Failed to dead-letter message")
+        );
+        message_ids.push(id);
+    }
+
+    let moved_at = (chrono::Utc::now() - chrono::Duration::days(3)).to_rfc3339();
+    let conn = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+    conn.execute(
+        "UPDATE dead_letter_messages SET moved_at = ?1 WHERE id = ?2",
+        [&moved_at, &message_ids[0]],
+    )
+    .expect("Failed to backdate dead letter");
+    drop(conn);
+
+    // Dead letters are kept forever by default
+    let mut retention = Config::default().retention;
+    assert_eq!(
+        service
+            .cleanup_dead_letter_messages(&retention)
+            .await
+            .unwrap(),
+        0
+    );
+
+    retention.dlq_retention_seconds = Some(24 * 3600);
+    let deleted = service
+        .cleanup_dead_letter_messages(&retention)
+        .await
+        .expect("Failed to clean up dead letters");
+    assert_eq!(deleted, 1);
+    assert_eq!(service.metrics().dlq_retention_deleted_total(), 1);
+
+    let remaining = service
+        .get_dlq_messages("work-dlq")
+        .await
+        .expect("Failed to list dead letters");
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].0, message_ids[1]);
+}
+
+#[tokio::test]
+async fn test_prune_deleted_messages_after_configured_period() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("prune.db");
+    let service = QueueService::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create queue service");
+    service
+        .create_queue("prune-queue")
+        .await
+        .expect("Failed to create queue");
+
+    let mut message_ids = Vec::new();
+    for body in ["consumed long ago", "consumed just now", "still pending"] {
+        message_ids.push(
+            service
+                .send_message("prune-queue", body, None, None)
+                .await
+                .expect("Failed to send message"),
+        );
+    }
+    service.delete_message(&message_ids[0]).await.unwrap();
+    service.delete_message(&message_ids[1]).await.unwrap();
+
+    let deleted_at = (chrono::Utc::now() - chrono::Duration::hours(2)).to_rfc3339();
+    let conn = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+    conn.execute(
+        "UPDATE messages SET deleted_at = ?1 WHERE id = ?2",
+        [&deleted_at, &message_ids[0]],
+    )
+    .expect("Failed to backdate deletion");
+    drop(conn);
+
+    // KeepForever is the default mode; pruning is independent of it
+    let mut retention = Config::default().retention;
+    assert_eq!(service.prune_deleted_messages(&retention).await.unwrap(), 0);
+
+    retention.prune_deleted_after_seconds = Some(3600);
+    let pruned = service
+        .prune_deleted_messages(&retention)
+        .await
+        .expect("Failed to prune deleted messages");
+    assert_eq!(pruned, 1);
+
+    let remaining: Vec<String> = service
+        .get_oldest_queue_messages("prune-queue", DEFAULT_MESSAGE_LIST_LIMIT)
+        .await
+        .expect("Failed to list messages")
+        .into_iter()
+        .map(|m| m.0)
+        .collect();
+    assert_eq!(remaining.len(), 2);
+    assert!(!remaining.contains(&message_ids[0]));
+}
+
+#[tokio::test]
+async fn test_consumer_and_retention_deletes_are_counted_separately() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("delete-metrics.db");
+    let service = QueueService::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create queue service");
+    service
+        .create_queue("metrics-queue")
+        .await
+        .expect("Failed to create queue");
+
+    let mut message_ids = Vec::new();
+    for i in 0..4 {
+        message_ids.push(
+            service
+                .send_message("metrics-queue", &format!("msg-{}", i), None, None)
+                .await
+                .expect("Failed to send message"),
+        );
+    }
+
+    assert!(service.delete_message(&message_ids[0]).await.unwrap());
+    service
+        .delete_messages_batch(
+            "metrics-queue",
+            vec![message_ids[1].clone(), "missing".to_string()],
+        )
+        .await
+        .unwrap();
+    assert_eq!(service.metrics().consumer_deleted_total(), 2);
+
+    // Age one unconsumed message past the retention period
+    let created_at = (chrono::Utc::now() - chrono::Duration::days(3)).to_rfc3339();
+    let conn = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+    conn.execute(
+        "UPDATE messages SET created_at = ?1 WHERE id = ?2",
+        [&created_at, &message_ids[2]],
+    )
+    .expect("Failed to backdate message");
+    drop(conn);
+
+    let mut retention = Config::default().retention;
+    retention.mode = RetentionMode::Delete;
+    retention.delete_after_days = Some(1);
+    let expired = service
+        .cleanup_expired_messages(&retention)
+        .await
+        .expect("Failed to run cleanup");
+    assert_eq!(expired, 1);
+    assert_eq!(service.metrics().retention_deleted_total(), 1);
+    assert_eq!(service.metrics().consumer_deleted_total(), 2);
+
+    let rendered = service.metrics().render();
+    assert!(rendered.contains("qlite_messages_consumer_deleted_total 2"));
+    assert!(rendered.contains("qlite_messages_retention_deleted_total 1"));
+}
+
+#[tokio::test]
+async fn test_visibility_timeout_expires_regardless_of_timestamp_format() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("visibility-format.db");
+    let db = Database::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create database");
+
+    db.create_queue("format-queue")
+        .await
+        .expect("Failed to create queue");
+    send_message(&db, "format-queue", "msg-1", "Work")
+        .await
+        .expect("Failed to send message");
+    let (received, _) = db
+        .receive_next_message("format-queue", &[])
+        .await
+        .expect("Failed to receive message");
+    assert!(received.is_some());
+
+    // SQLite's own datetime format sorts below any RFC 3339 string from the same day, so a
+    // text comparison would treat this deadline as already passed
+    let conn = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+    conn.execute(
+        "UPDATE messages SET visibility_timeout = datetime('now', '+2 seconds') WHERE id = 'msg-1'",
+        [],
+    )
+    .expect("Failed to rewrite deadline");
+    drop(conn);
+
+    let retention = Config::default().retention;
+    assert_eq!(db.cleanup_expired_messages(&retention).await.unwrap(), 0);
+    let (early, _) = db.receive_next_message("format-queue", &[]).await.unwrap();
+    assert!(early.is_none(), "message reappeared before its deadline");
+
+    tokio::time::sleep(std::time::Duration::from_millis(2500)).await;
+    assert_eq!(db.cleanup_expired_messages(&retention).await.unwrap(), 1);
+    let (late, _) = db.receive_next_message("format-queue", &[]).await.unwrap();
+    assert_eq!(late.expect("message should be visible again").0, "msg-1");
+}
+
+#[tokio::test]
+async fn test_delay_expires_regardless_of_timestamp_format() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("delay-format.db");
+    let db = Database::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create database");
+
+    db.create_queue("delay-queue")
+        .await
+        .expect("Failed to create queue");
+    send_message(&db, "delay-queue", "msg-1", "Later")
+        .await
+        .expect("Failed to send message");
+
+    // msg-1's deadline is in SQLite's datetime format, which sorts as text before any
+    // RFC 3339 string from the same day, so a text comparison would release it first
+    let rfc3339_deadline = (chrono::Utc::now() + chrono::Duration::seconds(1)).to_rfc3339();
+    let conn = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+    conn.execute(
+        "UPDATE messages SET delay_until = datetime('now', '+3 seconds') WHERE id = 'msg-1'",
+        [],
+    )
+    .expect("Failed to delay message");
+    drop(conn);
+    send_message(&db, "delay-queue", "msg-2", "Sooner")
+        .await
+        .expect("Failed to send message");
+    let conn = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+    conn.execute(
+        "UPDATE messages SET delay_until = ?1 WHERE id = 'msg-2'",
+        [&rfc3339_deadline],
+    )
+    .expect("Failed to delay message");
+    drop(conn);
+
+    let (early, _) = db.receive_next_message("delay-queue", &[]).await.unwrap();
+    assert!(early.is_none(), "delayed message was delivered early");
+
+    tokio::time::sleep(std::time::Duration::from_millis(1200)).await;
+    let (sooner, _) = db.receive_next_message("delay-queue", &[]).await.unwrap();
+    assert_eq!(sooner.expect("first delay should have passed").0, "msg-2");
+    let (later, _) = db.receive_next_message("delay-queue", &[]).await.unwrap();
+    assert!(later.is_none(), "second delay ended early");
+
+    tokio::time::sleep(std::time::Duration::from_millis(2300)).await;
+    let (later, _) = db.receive_next_message("delay-queue", &[]).await.unwrap();
+    assert_eq!(later.expect("second delay should have passed").0, "msg-1");
+}
+
+#[tokio::test]
+async fn test_atomic_batch_send_rolls_back_on_entry_error() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("atomic-batch.db");
+    let db = Database::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create database");
+    db.create_queue("batch-queue")
+        .await
+        .expect("Failed to create queue");
+
+    // Reusing a message id makes the second insert fail
+    let entry = |id: &str| {
+        (
+            "batch-queue".to_string(),
+            id.to_string(),
+            "body".to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+    };
+    let batch = vec![entry("msg-1"), entry("msg-2"), entry("msg-1")];
+
+    let results = db
+        .send_messages_batch(batch.clone(), true)
+        .await
+        .expect("Failed to send batch");
+    assert!(results[2].is_err());
+    assert!(
+        db.get_queue_messages_page("batch-queue", DEFAULT_MESSAGE_LIST_LIMIT, 0)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // Per-entry, the valid entries are kept
+    let results = db
+        .send_messages_batch(batch, false)
+        .await
+        .expect("Failed to send batch");
+    assert!(results[0].is_ok() && results[1].is_ok() && results[2].is_err());
+    assert_eq!(
+        db.get_queue_messages_page("batch-queue", DEFAULT_MESSAGE_LIST_LIMIT, 0)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn test_restore_from_backup_on_startup() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let source_path = temp_dir.path().join("source.db");
+    let backup_path = temp_dir.path().join("backup.db");
+    let restored_path = temp_dir.path().join("restored.db");
+
+    let source = QueueService::new(source_path.to_str().unwrap())
+        .await
+        .expect("Failed to create service");
+    source.create_queue("restored-queue").await.unwrap();
+    for i in 0..3 {
+        source
+            .send_message("restored-queue", &format!("msg-{}", i), None, None)
+            .await
+            .unwrap();
+    }
+    source
+        .backup_to(backup_path.to_str().unwrap())
+        .await
+        .unwrap();
+
+    // The copy goes through a temporary file; one left by an interrupted restore is replaced
+    let restoring_path = temp_dir.path().join("restored.db.restoring");
+    std::fs::write(&restoring_path, b"partial copy").unwrap();
+
+    let mut config = Config::default();
+    config.database.restore_from = Some(backup_path.to_str().unwrap().to_string());
+    let service = QueueService::new_with_config(restored_path.to_str().unwrap(), &config)
+        .await
+        .expect("Failed to restore from backup");
+    assert!(!restoring_path.exists());
+    let queues = service.list_queues().await.unwrap();
+    assert_eq!(queues.len(), 1);
+    assert_eq!(queues[0].0, "restored-queue");
+    assert_eq!(
+        service
+            .get_oldest_queue_messages("restored-queue", DEFAULT_MESSAGE_LIST_LIMIT)
+            .await
+            .unwrap()
+            .len(),
+        3
+    );
+
+    // An existing database is never clobbered
+    service.create_queue("added-after-restore").await.unwrap();
+    drop(service);
+    let service = QueueService::new_with_config(restored_path.to_str().unwrap(), &config)
+        .await
+        .unwrap();
+    assert_eq!(service.list_queues().await.unwrap().len(), 2);
+
+    // Anything that isn't a SQLite database is rejected
+    let bogus_path = temp_dir.path().join("bogus.db");
+    std::fs::write(&bogus_path, b"not a database").unwrap();
+    config.database.restore_from = Some(bogus_path.to_str().unwrap().to_string());
+    let fresh_path = temp_dir.path().join("fresh.db");
+    assert!(
+        QueueService::new_with_config(fresh_path.to_str().unwrap(), &config)
+            .await
+            .is_err()
+    );
+    assert!(!fresh_path.exists());
+}
+
+#[tokio::test]
+async fn test_stream_all_queue_messages_is_bounded() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("export.db");
+    let db = Database::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create database");
+    db.create_queue("export-queue")
+        .await
+        .expect("Failed to create queue");
+
+    const MESSAGES: usize = 2_000;
+    let batch: Vec<_> = (0..MESSAGES)
+        .map(|i| {
+            (
+                "export-queue".to_string(),
+                format!("msg-{:05}", i),
+                format!("body {}", i),
+                None,
+                None,
+                None,
+                None,
+            )
+        })
+        .collect();
+    db.send_messages_batch(batch, true)
+        .await
+        .expect("Failed to send messages");
+
+    let mut rows = db.stream_all_queue_messages("export-queue");
+
+    // Left unread, the reader stops once the channel is full rather than loading the queue
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    assert!(rows.len() <= EXPORT_CHANNEL_CAPACITY);
+    assert!(rows.len() < MESSAGES);
+
+    let mut exported = Vec::with_capacity(MESSAGES);
+    while let Some(row) = rows.recv().await {
+        exported.push(row.expect("Failed to read row").id);
+    }
+    assert_eq!(exported.len(), MESSAGES);
+    let mut unique = exported.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), MESSAGES);
+}
+
+#[tokio::test]
+async fn test_queue_message_listing_is_capped_and_paged() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("listing.db");
+    let db = Database::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create database");
+    db.create_queue("deep-queue")
+        .await
+        .expect("Failed to create queue");
+
+    let total = DEFAULT_MESSAGE_LIST_LIMIT + 5;
+    let conn = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+    conn.execute(
+        r#"
+        WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < ?1)
+        INSERT INTO messages (id, queue_name, body, created_at, status)
+        SELECT printf('msg-%05d', i), 'deep-queue', 'Body', printf('2024-01-01T00:00:00.%06dZ', i), 'active'
+        FROM n
+        "#,
+        [total as i64],
+    )
+    .expect("Failed to insert messages");
+    drop(conn);
+
+    let all = db
+        .get_queue_messages_page("deep-queue", DEFAULT_MESSAGE_LIST_LIMIT, 0)
+        .await
+        .unwrap();
+    assert_eq!(all.len(), DEFAULT_MESSAGE_LIST_LIMIT);
+    assert_eq!(all[0].0, "msg-00001");
+
+    let last_page = db
+        .get_queue_messages_page("deep-queue", 10, DEFAULT_MESSAGE_LIST_LIMIT)
+        .await
+        .unwrap();
+    let ids: Vec<&str> = last_page.iter().map(|m| m.0.as_str()).collect();
+    assert_eq!(
+        ids,
+        vec![
+            "msg-01001",
+            "msg-01002",
+            "msg-01003",
+            "msg-01004",
+            "msg-01005"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_list_dlq_source_queues_matches_arn_and_name_targets() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("dlq-sources.db");
+    let db = Database::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create database");
+
+    let targets = [
+        ("billing", "arn:aws:sqs:us-east-1:000000000000:shared-dlq"),
+        ("shipping", "shared-dlq"),
+        // Only a suffix of the DLQ name, so it must not match
+        ("audit", "arn:aws:sqs:us-east-1:000000000000:dlq"),
+    ];
+    for (queue, target) in targets {
+        db.create_queue(queue)
+            .await
+            .expect("Failed to create queue");
+        let policy = format!(
+            r#"{{"deadLetterTargetArn":"{}","maxReceiveCount":3}}"#,
+            target
+        );
+        db.set_queue_attributes(
+            queue,
+            &std::collections::HashMap::from([("RedrivePolicy".to_string(), policy)]),
+        )
+        .await
+        .expect("Failed to set redrive policy");
+    }
+    db.create_queue("no-dlq")
+        .await
+        .expect("Failed to create queue");
+
+    let sources = db
+        .list_dlq_source_queues("shared-dlq")
+        .await
+        .expect("Failed to list source queues");
+    assert_eq!(sources, vec!["billing", "shipping"]);
+    assert!(
+        db.list_dlq_source_queues("missing-dlq")
+            .await
+            .expect("Failed to list source queues")
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_content_dedup_id_is_sha256_hex_of_body() {
+    assert_eq!(
+        content_dedup_id("hello"),
+        "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+    );
+    assert_eq!(
+        content_dedup_id(""),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+}
+
+#[tokio::test]
+async fn test_leftover_config_of_deleted_queue_is_ignored() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("leftover.db");
+    let db = Database::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create database");
+
+    // Older versions kept queue_config rows after DeleteQueue
+    let conn = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+    conn.execute(
+        "INSERT INTO queue_config (name, is_fifo, visibility_timeout_seconds) VALUES ('leftover', 0, 5)",
+        [],
+    )
+    .expect("Failed to insert leftover config");
+    drop(conn);
+
+    assert!(
+        db.get_queue_config("leftover")
+            .await
+            .expect("Failed to read config")
+            .is_none()
+    );
+
+    db.create_queue("leftover")
+        .await
+        .expect("Failed to create queue");
+    assert!(
+        db.get_queue_config("leftover")
+            .await
+            .expect("Failed to read config")
+            .is_none(),
+        "a recreated queue shouldn't inherit the old configuration"
+    );
+}
@@ -0,0 +1,3759 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{
+    BASE_URL, admin_post, form_request, get_request, json_post, json_request, send, test_router,
+    test_service, test_service_with_config, xml_values,
+};
+use qlite::config::{BatchMode, Config, QueueConfig, ReceiptHandleEncoding};
+use qlite::database::{DEFAULT_MESSAGE_LIST_LIMIT, MissingMessageGroupId};
+use qlite::http_server::create_router;
+use qlite::message::{MessageAttributeValue, ReceiptSigner, receipt_sequence_number};
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn test_sent_timestamp_reflects_send_time() {
+    let (_temp_dir, service) = test_service().await;
+    let router = test_router(service);
+    let queue_url = format!("{}/timestamp-queue", BASE_URL);
+
+    form_request(
+        &router,
+        "/?Action=CreateQueue",
+        &[("QueueName", "timestamp-queue")],
+    )
+    .await;
+
+    let sent_at = chrono::Utc::now().timestamp_millis();
+    let (status, _) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[
+            ("QueueUrl", &queue_url),
+            ("MessageBody", "When was I sent?"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=ReceiveMessage",
+        &[
+            ("QueueUrl", &queue_url),
+            ("AttributeName.1", "SentTimestamp"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let sent_timestamp: i64 = xml_values(&body, "SentTimestamp")[0].parse().unwrap();
+    let received_at = chrono::Utc::now().timestamp_millis();
+
+    assert!((sent_timestamp - sent_at).abs() < 500);
+    assert!(received_at - sent_timestamp >= 1500);
+}
+
+#[tokio::test]
+async fn test_receive_reports_receive_count_and_first_receive_time() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("redelivery-queue").await.unwrap();
+    service
+        .send_message("redelivery-queue", "deliver me twice", None, None)
+        .await
+        .unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/redelivery-queue", BASE_URL);
+
+    let receive_params = [("QueueUrl", queue_url.as_str()), ("AttributeName.1", "All")];
+    let receive = || form_request(&router, "/?Action=ReceiveMessage", &receive_params);
+
+    let (_, first) = receive().await;
+    assert_eq!(xml_values(&first, "ApproximateReceiveCount"), vec!["1"]);
+    let receipt = xml_values(&first, "ReceiptHandle").remove(0);
+
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let (status, body) = form_request(
+        &router,
+        "/?Action=ChangeMessageVisibility",
+        &[
+            ("QueueUrl", queue_url.as_str()),
+            ("ReceiptHandle", receipt.as_str()),
+            ("VisibilityTimeout", "0"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (_, second) = receive().await;
+    assert_eq!(xml_values(&second, "ApproximateReceiveCount"), vec!["2"]);
+    assert_eq!(
+        xml_values(&second, "SentTimestamp"),
+        xml_values(&first, "SentTimestamp")
+    );
+    // The first receive time sticks across redeliveries
+    let first_receive = xml_values(&first, "ApproximateFirstReceiveTimestamp");
+    assert_eq!(first_receive.len(), 1);
+    assert_eq!(
+        xml_values(&second, "ApproximateFirstReceiveTimestamp"),
+        first_receive
+    );
+    let sent: i64 = xml_values(&first, "SentTimestamp")[0].parse().unwrap();
+    assert!(first_receive[0].parse::<i64>().unwrap() >= sent);
+}
+
+#[tokio::test]
+async fn test_list_dead_letter_source_queues() {
+    let (_temp_dir, service) = test_service().await;
+    let router = test_router(service);
+
+    for name in ["orders", "payments", "unrelated", "orders-dlq"] {
+        form_request(&router, "/?Action=CreateQueue", &[("QueueName", name)]).await;
+    }
+
+    // One queue points at the DLQ by ARN, the other by plain name
+    let policies = [
+        (
+            "orders",
+            r#"{"deadLetterTargetArn":"arn:aws:sqs:us-east-1:000000000000:orders-dlq","maxReceiveCount":3}"#,
+        ),
+        (
+            "payments",
+            r#"{"deadLetterTargetArn":"orders-dlq","maxReceiveCount":5}"#,
+        ),
+    ];
+    for (queue, policy) in policies {
+        let queue_url = format!("{}/{}", BASE_URL, queue);
+        let (status, _) = form_request(
+            &router,
+            "/?Action=SetQueueAttributes",
+            &[
+                ("QueueUrl", &queue_url),
+                ("Attribute.1.Name", "RedrivePolicy"),
+                ("Attribute.1.Value", policy),
+            ],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let dlq_url = format!("{}/orders-dlq", BASE_URL);
+    let (status, body) = form_request(
+        &router,
+        "/?Action=ListDeadLetterSourceQueues",
+        &[("QueueUrl", &dlq_url)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        xml_values(&body, "QueueUrl"),
+        vec![
+            format!("{}/orders", BASE_URL),
+            format!("{}/payments", BASE_URL)
+        ]
+    );
+
+    let missing_url = format!("{}/missing-dlq", BASE_URL);
+    let (status, body) = form_request(
+        &router,
+        "/?Action=ListDeadLetterSourceQueues",
+        &[("QueueUrl", &missing_url)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("AWS.SimpleQueueService.NonExistentQueue"));
+}
+
+// Dead-letter one message from each of `sources` into `dlq`
+async fn dead_letter_one_each(
+    service: &qlite::queue_service::QueueService,
+    sources: &[&str],
+    dlq: &str,
+) {
+    for source in sources {
+        service.create_queue(source).await.unwrap();
+        let policy = format!(r#"{{"deadLetterTargetArn":"{}","maxReceiveCount":3}}"#, dlq);
+        service
+            .set_queue_attributes(
+                source,
+                HashMap::from([("RedrivePolicy".to_string(), policy)]),
+            )
+            .await
+            .unwrap();
+        let id = service
+            .send_message(source, &format!("from {}", source), None, None)
+            .await
+            .unwrap();
+        assert!(service.move_message_to_dlq(&id, "test").await.unwrap());
+    }
+}
+
+// Poll until the queue holds `expected` visible messages, as move tasks run in the background
+async fn wait_for_messages(
+    service: &qlite::queue_service::QueueService,
+    queue: &str,
+    expected: u32,
+) {
+    for _ in 0..100 {
+        let attributes = service.get_queue_attributes(queue).await.unwrap().unwrap();
+        if attributes.approximate_number_of_messages == expected {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    panic!("{} never reached {} messages", queue, expected);
+}
+
+#[tokio::test]
+async fn test_start_message_move_task_redrives_to_original_queues() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("moves-dlq").await.unwrap();
+    dead_letter_one_each(&service, &["moves-orders", "moves-payments"], "moves-dlq").await;
+    let router = test_router(service.clone());
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=StartMessageMoveTask",
+        &[("SourceArn", "arn:aws:sqs:us-east-1:000000000000:moves-dlq")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(xml_values(&body, "TaskHandle").len(), 1);
+
+    wait_for_messages(&service, "moves-orders", 1).await;
+    wait_for_messages(&service, "moves-payments", 1).await;
+    assert!(
+        service
+            .get_dlq_messages("moves-dlq")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    let message = service
+        .receive_message("moves-orders")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(message.body, "from moves-orders");
+}
+
+#[tokio::test]
+async fn test_start_message_move_task_with_destination() {
+    let (_temp_dir, service) = test_service().await;
+    for name in ["dest-dlq", "dest-target"] {
+        service.create_queue(name).await.unwrap();
+    }
+    dead_letter_one_each(&service, &["dest-a", "dest-b"], "dest-dlq").await;
+    let router = test_router(service.clone());
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=StartMessageMoveTask",
+        &[
+            ("SourceArn", "arn:aws:sqs:us-east-1:000000000000:dest-dlq"),
+            (
+                "DestinationArn",
+                "arn:aws:sqs:us-east-1:000000000000:dest-missing",
+            ),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("AWS.SimpleQueueService.NonExistentQueue"));
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=StartMessageMoveTask",
+        &[
+            ("SourceArn", "arn:aws:sqs:us-east-1:000000000000:dest-dlq"),
+            ("MaxNumberOfMessagesPerSecond", "501"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(xml_values(&body, "Code"), vec!["InvalidParameterValue"]);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=StartMessageMoveTask",
+        &[
+            ("SourceArn", "arn:aws:sqs:us-east-1:000000000000:dest-dlq"),
+            (
+                "DestinationArn",
+                "arn:aws:sqs:us-east-1:000000000000:dest-target",
+            ),
+            ("MaxNumberOfMessagesPerSecond", "10"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    wait_for_messages(&service, "dest-target", 2).await;
+    assert!(
+        service
+            .get_dlq_messages("dest-dlq")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    for source in ["dest-a", "dest-b"] {
+        wait_for_messages(&service, source, 0).await;
+    }
+}
+
+#[tokio::test]
+async fn test_get_queue_config_json() {
+    let (_temp_dir, service) = test_service().await;
+    let config = QueueConfig {
+        name: "config-queue.fifo".to_string(),
+        is_fifo: true,
+        content_based_deduplication: true,
+        visibility_timeout_seconds: 45,
+        message_retention_period_seconds: 3600,
+        max_receive_count: Some(4),
+        dead_letter_target_arn: Some("qlite://queue/config-dlq".to_string()),
+        delay_seconds: 5,
+        receive_message_wait_time_seconds: 10,
+        count_receives: true,
+        ack_watermark: false,
+        deduplication_window_seconds: 300,
+    };
+    service
+        .create_queue_with_config(&config)
+        .await
+        .expect("Failed to create queue");
+    let router = test_router(service);
+
+    let (status, body) = get_request(&router, "/api/queue/config-queue.fifo/config").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["name"], "config-queue.fifo");
+    assert_eq!(json["is_fifo"], true);
+    assert_eq!(json["content_based_deduplication"], true);
+    assert_eq!(json["visibility_timeout_seconds"], 45);
+    assert_eq!(json["message_retention_period_seconds"], 3600);
+    assert_eq!(json["max_receive_count"], 4);
+    assert_eq!(json["dead_letter_target_arn"], "qlite://queue/config-dlq");
+    assert_eq!(json["delay_seconds"], 5);
+    assert_eq!(json["receive_message_wait_time_seconds"], 10);
+    assert_eq!(json["count_receives"], true);
+
+    // Queues created without explicit configuration report the defaults
+    form_request(
+        &router,
+        "/?Action=CreateQueue",
+        &[("QueueName", "plain-queue")],
+    )
+    .await;
+    let (status, body) = get_request(&router, "/api/queue/plain-queue/config").await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["is_fifo"], false);
+    assert_eq!(json["dead_letter_target_arn"], serde_json::Value::Null);
+
+    let (status, body) = get_request(&router, "/api/queue/missing-queue/config").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body.contains("does not exist"));
+}
+
+#[tokio::test]
+async fn test_clone_queue_copies_config() {
+    let (_temp_dir, service) = test_service().await;
+    let config = QueueConfig {
+        name: "source.fifo".to_string(),
+        is_fifo: true,
+        content_based_deduplication: false,
+        visibility_timeout_seconds: 90,
+        message_retention_period_seconds: 7200,
+        max_receive_count: Some(2),
+        dead_letter_target_arn: Some("qlite://queue/source-dlq.fifo".to_string()),
+        delay_seconds: 3,
+        receive_message_wait_time_seconds: 5,
+        count_receives: false,
+        ack_watermark: true,
+        deduplication_window_seconds: 60,
+    };
+    service
+        .create_queue_with_config(&config)
+        .await
+        .expect("Failed to create queue");
+    service
+        .send_message_enhanced_with_group(
+            "source.fifo",
+            "Not copied",
+            None,
+            Some("dedup-1".to_string()),
+            None,
+            Some("group".to_string()),
+        )
+        .await
+        .expect("Failed to send message");
+    let router = test_router(service.clone());
+
+    let (status, body) = json_post(
+        &router,
+        "/api/queue/source.fifo/clone",
+        serde_json::json!({ "new_name": "copy.fifo" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["name"], "copy.fifo");
+
+    let cloned = service
+        .get_queue_config("copy.fifo")
+        .await
+        .unwrap()
+        .expect("Cloned queue should exist");
+    assert!(cloned.is_fifo);
+    assert!(!cloned.content_based_deduplication);
+    assert_eq!(cloned.visibility_timeout_seconds, 90);
+    assert_eq!(cloned.message_retention_period_seconds, 7200);
+    assert_eq!(cloned.max_receive_count, Some(2));
+    assert_eq!(
+        cloned.dead_letter_target_arn.as_deref(),
+        Some("qlite://queue/source-dlq.fifo")
+    );
+    assert_eq!(cloned.delay_seconds, 3);
+    assert_eq!(cloned.deduplication_window_seconds, 60);
+    assert_eq!(cloned.receive_message_wait_time_seconds, 5);
+    assert!(!cloned.count_receives);
+    assert!(cloned.ack_watermark);
+
+    let attrs = service
+        .get_queue_attributes("copy.fifo")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(attrs.approximate_number_of_messages, 0);
+
+    // Suffix must match the source queue type
+    let (status, _) = json_post(
+        &router,
+        "/api/queue/source.fifo/clone",
+        serde_json::json!({ "new_name": "copy-standard" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = json_post(
+        &router,
+        "/api/queue/source.fifo/clone",
+        serde_json::json!({ "new_name": "copy.fifo" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, _) = json_post(
+        &router,
+        "/api/queue/missing/clone",
+        serde_json::json!({ "new_name": "other" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_nack_requeues_and_dead_letters() {
+    let (_temp_dir, service) = test_service().await;
+    let config = QueueConfig {
+        name: "nack-queue".to_string(),
+        max_receive_count: Some(3),
+        dead_letter_target_arn: Some("nack-dlq".to_string()),
+        ..Default::default()
+    };
+    service
+        .create_queue_with_config(&config)
+        .await
+        .expect("Failed to create queue");
+    service
+        .create_queue("nack-dlq")
+        .await
+        .expect("Failed to create DLQ");
+    let message_id = service
+        .send_message("nack-queue", "Poison", None, None)
+        .await
+        .expect("Failed to send message");
+    let router = test_router(service.clone());
+    let nack_path = format!("/api/message/{}/nack", message_id);
+
+    // A message that hasn't been received can't be nacked
+    let (status, _) = json_post(&router, &nack_path, serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    for attempt in 1..=3 {
+        let received = service
+            .receive_message("nack-queue")
+            .await
+            .unwrap()
+            .expect("Nacked message should be immediately visible");
+        assert_eq!(received.id, message_id);
+
+        let (status, body) = json_post(&router, &nack_path, serde_json::json!({})).await;
+        assert_eq!(status, StatusCode::OK);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["nack_count"], attempt);
+        assert_eq!(json["dead_lettered"], attempt == 3);
+    }
+
+    assert!(
+        service
+            .receive_message("nack-queue")
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let dlq_messages = service.get_dlq_messages("nack-dlq").await.unwrap();
+    assert_eq!(dlq_messages.len(), 1);
+    assert_eq!(dlq_messages[0].0, message_id);
+
+    assert_eq!(service.metrics().nack_redeliveries_total(), 2);
+    let (_, metrics) = get_request(&router, "/metrics").await;
+    assert!(metrics.contains("qlite_message_redeliveries_total{reason=\"nack\"} 2"));
+}
+
+#[tokio::test]
+async fn test_message_receive_count() {
+    let (_temp_dir, service) = test_service().await;
+    let config = QueueConfig {
+        name: "count-queue".to_string(),
+        visibility_timeout_seconds: 0,
+        ..Default::default()
+    };
+    service.create_queue_with_config(&config).await.unwrap();
+    let message_id = service
+        .send_message("count-queue", "counted", None, None)
+        .await
+        .unwrap();
+    let router = test_router(service.clone());
+    let path = format!("/api/message/{}/receive-count", message_id);
+
+    for _ in 0..2 {
+        let received = service
+            .receive_message("count-queue")
+            .await
+            .unwrap()
+            .expect("message should be visible again");
+        assert_eq!(received.id, message_id);
+    }
+
+    let (status, body) = get_request(&router, &path).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["message_id"], message_id.as_str());
+    assert_eq!(json["receive_count"], 2);
+
+    let (status, _) = get_request(&router, "/api/message/missing/receive-count").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // A deleted message is no longer in its queue
+    assert!(service.delete_message(&message_id).await.unwrap());
+    let (status, _) = get_request(&router, &path).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_purge_filtered() {
+    let (_temp_dir, service) = test_service().await;
+    service
+        .create_queue("purge-queue")
+        .await
+        .expect("Failed to create queue");
+
+    let tagged = HashMap::from([(
+        "env".to_string(),
+        MessageAttributeValue {
+            string_value: Some("test".to_string()),
+            binary_value: None,
+            data_type: "String".to_string(),
+        },
+    )]);
+    service
+        .send_message("purge-queue", "keep me", None, None)
+        .await
+        .unwrap();
+    service
+        .send_message("purge-queue", "test fixture 1", None, None)
+        .await
+        .unwrap();
+    service
+        .send_message("purge-queue", "tagged", Some(tagged.clone()), None)
+        .await
+        .unwrap();
+    let router = test_router(service.clone());
+    let path = "/api/queue/purge-queue/purge-filtered";
+
+    // An empty filter would purge everything, so it's rejected
+    let (status, _) = json_post(&router, path, serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = json_post(&router, path, serde_json::json!({ "status": "bogus" })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = json_post(
+        &router,
+        path,
+        serde_json::json!({ "body_contains": "fixture" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["purged"], 1);
+
+    let (status, body) = json_post(
+        &router,
+        path,
+        serde_json::json!({ "attribute_name": "env", "attribute_value": "prod" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["purged"], 0);
+
+    let (status, body) = json_post(
+        &router,
+        path,
+        serde_json::json!({ "status": "active", "attribute_name": "env", "attribute_value": "test" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["purged"], 1);
+
+    let remaining = service
+        .get_oldest_queue_messages("purge-queue", DEFAULT_MESSAGE_LIST_LIMIT)
+        .await
+        .unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].1, "keep me");
+
+    let (status, _) = json_post(
+        &router,
+        "/api/queue/missing/purge-filtered",
+        serde_json::json!({ "body_contains": "x" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_client_request_token_deduplicates_standard_queue_sends() {
+    let (_temp_dir, service) = test_service().await;
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/idempotent-queue", BASE_URL);
+
+    form_request(
+        &router,
+        "/?Action=CreateQueue",
+        &[("QueueName", "idempotent-queue")],
+    )
+    .await;
+
+    let mut message_ids = Vec::new();
+    for _ in 0..2 {
+        let (status, body) = form_request(
+            &router,
+            "/?Action=SendMessage",
+            &[
+                ("QueueUrl", &queue_url),
+                ("MessageBody", "Charge card"),
+                ("ClientRequestToken", "payment-42"),
+            ],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        message_ids.push(xml_values(&body, "MessageId")[0].clone());
+    }
+
+    assert_eq!(message_ids[0], message_ids[1]);
+    let messages = service
+        .get_oldest_queue_messages("idempotent-queue", DEFAULT_MESSAGE_LIST_LIMIT)
+        .await
+        .unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].0, message_ids[0]);
+}
+
+#[tokio::test]
+async fn test_exclusive_receive_lock_blocks_other_consumers() {
+    let (_temp_dir, service) = test_service().await;
+    service
+        .create_queue("leader-queue")
+        .await
+        .expect("Failed to create queue");
+    service
+        .send_message("leader-queue", "For the leader", None, None)
+        .await
+        .unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/leader-queue", BASE_URL);
+
+    let (status, body) = json_post(
+        &router,
+        "/api/queue/leader-queue/lock",
+        serde_json::json!({ "lease_seconds": 60 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let token = json["token"].as_str().unwrap().to_string();
+
+    // A second lock request is refused while the lease is held
+    let (status, _) = json_post(
+        &router,
+        "/api/queue/leader-queue/lock",
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Consumers without the token are blocked
+    let (status, body) = form_request(
+        &router,
+        "/?Action=ReceiveMessage",
+        &[("QueueUrl", &queue_url)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(body.contains("OverLimit"));
+
+    // The lock holder can receive
+    let (status, body) = form_request(
+        &router,
+        "/?Action=ReceiveMessage",
+        &[("QueueUrl", &queue_url), ("LockToken", &token)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(xml_values(&body, "Body"), vec!["For the leader"]);
+
+    let request = axum::http::Request::builder()
+        .method("DELETE")
+        .uri(format!("/api/queue/leader-queue/lock/{}", token))
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let (status, _) = common::send(&router, request).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = form_request(
+        &router,
+        "/?Action=ReceiveMessage",
+        &[("QueueUrl", &queue_url)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_replay_copies_processed_messages() {
+    let (_temp_dir, service) = test_service().await;
+    service
+        .create_queue("replay-queue")
+        .await
+        .expect("Failed to create queue");
+
+    let from = chrono::Utc::now() - chrono::Duration::seconds(5);
+    let delivered = service
+        .send_message("replay-queue", "already handled", None, None)
+        .await
+        .unwrap();
+    service
+        .send_message("replay-queue", "still pending", None, None)
+        .await
+        .unwrap();
+    let received = service
+        .receive_message("replay-queue")
+        .await
+        .unwrap()
+        .expect("Message should be available");
+    assert_eq!(received.id, delivered);
+    service.delete_message(&delivered).await.unwrap();
+    let to = chrono::Utc::now() + chrono::Duration::seconds(5);
+
+    let router = test_router(service.clone());
+    let path = "/api/queue/replay-queue/replay";
+
+    let (status, _) = json_post(
+        &router,
+        path,
+        serde_json::json!({ "from": to.to_rfc3339(), "to": from.to_rfc3339() }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = json_post(
+        &router,
+        path,
+        serde_json::json!({ "from": "yesterday", "to": to.to_rfc3339() }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = json_post(
+        &router,
+        "/api/queue/missing-queue/replay",
+        serde_json::json!({ "from": from.to_rfc3339(), "to": to.to_rfc3339() }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = json_post(
+        &router,
+        path,
+        serde_json::json!({ "from": from.to_rfc3339(), "to": to.to_rfc3339() }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["replayed"], 1);
+    assert_eq!(json["messages"][0]["original_id"], delivered.as_str());
+    let copy_id = json["messages"][0]["message_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert_ne!(copy_id, delivered);
+
+    // The original is left as-is; the copy is a fresh active message
+    let messages = service
+        .get_oldest_queue_messages("replay-queue", DEFAULT_MESSAGE_LIST_LIMIT)
+        .await
+        .unwrap();
+    assert_eq!(messages.len(), 3);
+    let original = messages.iter().find(|m| m.0 == delivered).unwrap();
+    assert_eq!(original.7, "deleted");
+    let copy = messages.iter().find(|m| m.0 == copy_id).unwrap();
+    assert_eq!(copy.1, "already handled");
+    assert_eq!(copy.4, 0);
+    assert_eq!(copy.7, "active");
+}
+
+#[tokio::test]
+async fn test_readiness_reports_schema_ready() {
+    let (_temp_dir, service) = test_service().await;
+    assert!(service.schema_ready());
+
+    let router = test_router(service);
+    let (status, body) = get_request(&router, "/health/ready").await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["status"], "ready");
+    assert_eq!(json["schema_ready"], true);
+}
+
+#[tokio::test]
+async fn test_send_rejects_too_many_attributes() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("attr-queue").await.unwrap();
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/attr-queue", BASE_URL);
+
+    let mut owned = Vec::new();
+    for i in 1..=11 {
+        owned.push((format!("MessageAttribute.{}.Name", i), format!("attr{}", i)));
+        owned.push((
+            format!("MessageAttribute.{}.Value.StringValue", i),
+            "value".to_string(),
+        ));
+        owned.push((
+            format!("MessageAttribute.{}.Value.DataType", i),
+            "String".to_string(),
+        ));
+    }
+    let mut params: Vec<(&str, &str)> = vec![
+        ("QueueUrl", queue_url.as_str()),
+        ("MessageBody", "Too many attributes"),
+    ];
+    params.extend(owned.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+
+    let (status, body) = form_request(&router, "/?Action=SendMessage", &params).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(xml_values(&body, "Code"), vec!["InvalidParameterValue"]);
+
+    // Ten is the limit, not an error
+    let (status, _) = form_request(&router, "/?Action=SendMessage", &params[..32]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        service
+            .get_oldest_queue_messages("attr-queue", DEFAULT_MESSAGE_LIST_LIMIT)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_send_rejects_reserved_attribute_prefixes() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("reserved-queue").await.unwrap();
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/reserved-queue", BASE_URL);
+
+    for name in ["AWS.TraceHeader", "amazon.custom"] {
+        let (status, body) = form_request(
+            &router,
+            "/?Action=SendMessage",
+            &[
+                ("QueueUrl", &queue_url),
+                ("MessageBody", "Reserved"),
+                ("MessageAttribute.1.Name", name),
+                ("MessageAttribute.1.Value.StringValue", "value"),
+                ("MessageAttribute.1.Value.DataType", "String"),
+            ],
+        )
+        .await;
+        assert_eq!(
+            status,
+            StatusCode::BAD_REQUEST,
+            "{} should be rejected",
+            name
+        );
+        assert_eq!(xml_values(&body, "Code"), vec!["InvalidParameterValue"]);
+    }
+
+    // In a batch only the offending entry fails
+    let (status, body) = form_request(
+        &router,
+        "/?Action=SendMessageBatch",
+        &[
+            ("QueueUrl", &queue_url),
+            ("SendMessageBatchRequestEntry.1.Id", "ok"),
+            ("SendMessageBatchRequestEntry.1.MessageBody", "Fine"),
+            ("SendMessageBatchRequestEntry.2.Id", "reserved"),
+            ("SendMessageBatchRequestEntry.2.MessageBody", "Reserved"),
+            (
+                "SendMessageBatchRequestEntry.2.MessageAttribute.1.Name",
+                "AWS.TraceHeader",
+            ),
+            (
+                "SendMessageBatchRequestEntry.2.MessageAttribute.1.Value.StringValue",
+                "value",
+            ),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(xml_values(&body, "Id"), vec!["ok", "reserved"]);
+    assert_eq!(xml_values(&body, "Code"), vec!["InvalidParameterValue"]);
+    assert_eq!(
+        service
+            .get_oldest_queue_messages("reserved-queue", DEFAULT_MESSAGE_LIST_LIMIT)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_special_characters_round_trip_through_xml() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("xml-queue").await.unwrap();
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/xml-queue", BASE_URL);
+    let original = "if a < b && c > d { \"quoted\" 'single' } 🚀 ünïcödé\ttab\nline";
+
+    let (status, _) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[("QueueUrl", &queue_url), ("MessageBody", original)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=ReceiveMessage",
+        &[("QueueUrl", &queue_url)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let received = xml_values(&body, "Body");
+    assert_eq!(received.len(), 1);
+    let decoded = quick_xml::escape::unescape(&received[0]).unwrap();
+    assert_eq!(decoded.as_bytes(), original.as_bytes());
+
+    // Characters XML can't carry are rejected before anything is stored
+    let (status, body) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[("QueueUrl", &queue_url), ("MessageBody", "bell \u{7}")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(xml_values(&body, "Code"), vec!["InvalidMessageContents"]);
+    assert_eq!(
+        service
+            .get_oldest_queue_messages("xml-queue", DEFAULT_MESSAGE_LIST_LIMIT)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_markup_in_body_is_escaped_in_receive_xml() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("markup-queue").await.unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/markup-queue", BASE_URL);
+
+    form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[("QueueUrl", &queue_url), ("MessageBody", "a<b>&c")],
+    )
+    .await;
+    let (status, body) = form_request(
+        &router,
+        "/?Action=ReceiveMessage",
+        &[("QueueUrl", &queue_url)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("<Body>a&lt;b&gt;&amp;c</Body>"), "{}", body);
+
+    // The whole document stays well-formed and the body decodes back exactly
+    let mut reader = quick_xml::Reader::from_str(&body);
+    let mut in_body = false;
+    let mut decoded = None;
+    loop {
+        match reader.read_event().expect("Response should be valid XML") {
+            quick_xml::events::Event::Start(e) => in_body = e.name().as_ref() == b"Body",
+            quick_xml::events::Event::Text(text) if in_body => {
+                decoded = Some(text.unescape().unwrap().into_owned());
+            }
+            quick_xml::events::Event::End(_) => in_body = false,
+            quick_xml::events::Event::Eof => break,
+            _ => {}
+        }
+    }
+    assert_eq!(decoded.as_deref(), Some("a<b>&c"));
+}
+
+#[tokio::test]
+async fn test_list_queues_include_tags() {
+    let (_temp_dir, service) = test_service().await;
+    for name in ["billing", "untagged"] {
+        service.create_queue(name).await.unwrap();
+    }
+    let router = test_router(service);
+    let queue_url = format!("{}/billing", BASE_URL);
+
+    let (status, _) = form_request(
+        &router,
+        "/?Action=TagQueue",
+        &[
+            ("QueueUrl", &queue_url),
+            ("Tag.1.Key", "team"),
+            ("Tag.1.Value", "payments"),
+            ("Tag.2.Key", "env"),
+            ("Tag.2.Value", "prod"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=ListQueueTags",
+        &[("QueueUrl", &queue_url)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(xml_values(&body, "Key"), vec!["env", "team"]);
+
+    // Tags are only inlined when asked for
+    let (_, body) = form_request(&router, "/?Action=ListQueues", &[]).await;
+    assert!(!body.contains("QueueTags"));
+
+    let (status, body) =
+        form_request(&router, "/?Action=ListQueues", &[("IncludeTags", "true")]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(xml_values(&body, "QueueTags").len(), 2);
+    assert_eq!(xml_values(&body, "Key"), vec!["env", "team"]);
+    assert_eq!(xml_values(&body, "Value"), vec!["prod", "payments"]);
+
+    let (status, _) = form_request(
+        &router,
+        "/?Action=UntagQueue",
+        &[("QueueUrl", &queue_url), ("TagKey.1", "env")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = form_request(
+        &router,
+        "/?Action=ListQueueTags",
+        &[("QueueUrl", &queue_url)],
+    )
+    .await;
+    assert_eq!(xml_values(&body, "Key"), vec!["team"]);
+
+    let missing_url = format!("{}/missing", BASE_URL);
+    let (status, body) = form_request(
+        &router,
+        "/?Action=TagQueue",
+        &[
+            ("QueueUrl", &missing_url),
+            ("Tag.1.Key", "team"),
+            ("Tag.1.Value", "payments"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("AWS.SimpleQueueService.NonExistentQueue"));
+}
+
+#[tokio::test]
+async fn test_default_message_delay_applies_without_delay_seconds() {
+    let mut config = Config::default();
+    config.queues.default_message_delay_seconds = 60;
+    let (_temp_dir, service) = test_service_with_config(&config).await;
+    service.create_queue("settling").await.unwrap();
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/settling", BASE_URL);
+
+    let (status, _) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[("QueueUrl", &queue_url), ("MessageBody", "settling")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = form_request(
+        &router,
+        "/?Action=SendMessageBatch",
+        &[
+            ("QueueUrl", &queue_url),
+            ("SendMessageBatchRequestEntry.1.Id", "1"),
+            ("SendMessageBatchRequestEntry.1.MessageBody", "batched"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let receive_params = [
+        ("QueueUrl", queue_url.as_str()),
+        ("MaxNumberOfMessages", "10"),
+    ];
+    // Plain service sends, as made by `qlite send`, are delayed too
+    service
+        .send_message("settling", "from the cli", None, None)
+        .await
+        .unwrap();
+
+    let (_, body) = form_request(&router, "/?Action=ReceiveMessage", &receive_params).await;
+    assert!(xml_values(&body, "Body").is_empty(), "{}", body);
+
+    // An explicit DelaySeconds still wins
+    let (status, _) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[
+            ("QueueUrl", &queue_url),
+            ("MessageBody", "immediate"),
+            ("DelaySeconds", "0"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = form_request(&router, "/?Action=ReceiveMessage", &receive_params).await;
+    assert_eq!(xml_values(&body, "Body"), vec!["immediate"]);
+}
+
+#[tokio::test]
+async fn test_case_insensitive_queue_names() {
+    let mut config = Config::default();
+    config.queues.case_insensitive_queue_names = true;
+    let (_temp_dir, service) = test_service_with_config(&config).await;
+    let router = test_router(service.clone());
+
+    let (status, body) =
+        form_request(&router, "/?Action=CreateQueue", &[("QueueName", "Orders")]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        xml_values(&body, "QueueUrl"),
+        vec![format!("{}/orders", BASE_URL)]
+    );
+    let (status, _) =
+        form_request(&router, "/?Action=CreateQueue", &[("QueueName", "orders")]).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = form_request(&router, "/?Action=ListQueues", &[]).await;
+    assert_eq!(
+        xml_values(&body, "QueueUrl"),
+        vec![format!("{}/orders", BASE_URL)]
+    );
+
+    let (status, _) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[
+            ("QueueUrl", &format!("{}/ORDERS", BASE_URL)),
+            ("MessageBody", "mixed case"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) =
+        form_request(&router, "/?Action=GetQueueUrl", &[("QueueName", "oRdErS")]).await;
+    assert_eq!(status, StatusCode::OK);
+    let queue_url = xml_values(&body, "QueueUrl").remove(0);
+    let (_, body) = form_request(
+        &router,
+        "/?Action=ReceiveMessage",
+        &[("QueueUrl", &queue_url)],
+    )
+    .await;
+    assert_eq!(xml_values(&body, "Body"), vec!["mixed case"]);
+
+    // The JSON API, the service itself (as used by the CLI and UI) and redrive targets
+    // agree on the spelling too
+    let (status, body) = get_request(&router, "/api/queue/Orders/config").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["name"], "orders");
+
+    service
+        .send_message("ORDERS", "from the service", None, None)
+        .await
+        .unwrap();
+    let stats = service.queue_stats(Some("Orders")).await.unwrap();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].queue, "orders");
+    assert_eq!(stats[0].visible, 1);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=SetQueueAttributes",
+        &[
+            ("QueueUrl", &queue_url),
+            ("Attribute.1.Name", "RedrivePolicy"),
+            (
+                "Attribute.1.Value",
+                r#"{"deadLetterTargetArn":"Orders-DLQ","maxReceiveCount":3}"#,
+            ),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let config = service.get_queue_config("orders").await.unwrap().unwrap();
+    assert_eq!(config.dead_letter_target_arn.as_deref(), Some("orders-dlq"));
+}
+
+#[tokio::test]
+async fn test_create_queue_with_tags() {
+    let (_temp_dir, service) = test_service().await;
+    let router = test_router(service);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=CreateQueue",
+        &[
+            ("QueueName", "tagged-at-creation"),
+            ("Tag.1.Key", "team"),
+            ("Tag.1.Value", "payments"),
+            ("Tag.2.Key", "env"),
+            ("Tag.2.Value", "prod"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let queue_url = format!("{}/tagged-at-creation", BASE_URL);
+    let (_, body) = form_request(
+        &router,
+        "/?Action=ListQueueTags",
+        &[("QueueUrl", &queue_url)],
+    )
+    .await;
+    assert_eq!(xml_values(&body, "Key"), vec!["env", "team"]);
+    assert_eq!(xml_values(&body, "Value"), vec!["prod", "payments"]);
+
+    // The JSON protocol sends CreateQueue tags as a lowercase "tags" map
+    let (status, _) = json_request(
+        &router,
+        "CreateQueue",
+        serde_json::json!({"QueueName": "json-tagged", "tags": {"team": "search"}}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = form_request(
+        &router,
+        "/?Action=ListQueueTags",
+        &[("QueueUrl", &format!("{}/json-tagged", BASE_URL))],
+    )
+    .await;
+    assert_eq!(xml_values(&body, "Value"), vec!["search"]);
+
+    // No tags is fine
+    let (status, _) = form_request(
+        &router,
+        "/?Action=CreateQueue",
+        &[("QueueName", "untagged")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = form_request(
+        &router,
+        "/?Action=ListQueueTags",
+        &[("QueueUrl", &format!("{}/untagged", BASE_URL))],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(xml_values(&body, "Key").is_empty(), "{}", body);
+}
+
+#[tokio::test]
+async fn test_deleting_a_queue_drops_its_tags() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("tagged").await.unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/tagged", BASE_URL);
+
+    let (status, _) = form_request(
+        &router,
+        "/?Action=TagQueue",
+        &[
+            ("QueueUrl", &queue_url),
+            ("Tag.1.Key", "team"),
+            ("Tag.1.Value", "payments"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) =
+        form_request(&router, "/?Action=DeleteQueue", &[("QueueUrl", &queue_url)]).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // A queue recreated under the same name starts untagged
+    form_request(&router, "/?Action=CreateQueue", &[("QueueName", "tagged")]).await;
+    let (status, body) = form_request(
+        &router,
+        "/?Action=ListQueueTags",
+        &[("QueueUrl", &queue_url)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(xml_values(&body, "Key").is_empty(), "{}", body);
+}
+
+#[tokio::test]
+async fn test_list_queues_filtered_by_tag() {
+    let (_temp_dir, service) = test_service().await;
+    let tagged = [
+        ("orders-prod", vec![("env", "prod"), ("team", "orders")]),
+        ("billing-prod", vec![("env", "prod"), ("team", "billing")]),
+        (
+            "orders-staging",
+            vec![("env", "staging"), ("team", "orders")],
+        ),
+        ("scratch", vec![]),
+    ];
+    for (name, tags) in &tagged {
+        service.create_queue(name).await.unwrap();
+        let tags = tags
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<Vec<_>>();
+        if !tags.is_empty() {
+            assert!(service.tag_queue(name, tags).await.unwrap());
+        }
+    }
+    let router = test_router(service);
+
+    let names = |body: &str| -> Vec<String> {
+        let json: serde_json::Value = serde_json::from_str(body).unwrap();
+        json["queues"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|q| q["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let (status, body) = get_request(&router, "/api/queues?tag.env=prod").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(names(&body), vec!["billing-prod", "orders-prod"]);
+
+    // Every tag in the filter must match, and all of a queue's tags are returned
+    let (_, body) = get_request(&router, "/api/queues?tag.env=prod&tag.team=orders").await;
+    assert_eq!(names(&body), vec!["orders-prod"]);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["queues"][0]["tags"]["team"], "orders");
+
+    let (_, body) = get_request(&router, "/api/queues?tag.env=dev").await;
+    assert!(names(&body).is_empty());
+
+    let (_, body) = get_request(&router, "/api/queues").await;
+    assert_eq!(names(&body).len(), 4);
+
+    let (status, _) = get_request(&router, "/api/queues?tag.=prod").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_oversized_batch_is_rejected_without_inserts() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("batch-queue").await.unwrap();
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/batch-queue", BASE_URL);
+
+    // Each entry is within the per-message limit, but together they exceed 256 KiB
+    let large_body = "x".repeat(100 * 1024);
+    let mut params = vec![("QueueUrl".to_string(), queue_url.clone())];
+    for i in 1..=3 {
+        params.push((
+            format!("SendMessageBatchRequestEntry.{}.Id", i),
+            format!("entry-{}", i),
+        ));
+        params.push((
+            format!("SendMessageBatchRequestEntry.{}.MessageBody", i),
+            large_body.clone(),
+        ));
+    }
+    let params: Vec<(&str, &str)> = params
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+
+    let (status, body) = form_request(&router, "/?Action=SendMessageBatch", &params).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(xml_values(&body, "Code"), vec!["BatchRequestTooLong"]);
+    assert!(
+        service
+            .get_oldest_queue_messages("batch-queue", DEFAULT_MESSAGE_LIST_LIMIT)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // Two of the same entries fit
+    let (status, body) = form_request(&router, "/?Action=SendMessageBatch", &params[..5]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(xml_values(&body, "MessageId").len(), 2);
+}
+
+// Three entries, the second of which has a body XML can't carry
+async fn send_batch_with_invalid_entry(batch_mode: BatchMode) -> (StatusCode, String, usize) {
+    let mut config = Config::default();
+    config.queues.batch_mode = batch_mode;
+    let (_temp_dir, service) = test_service_with_config(&config).await;
+    service.create_queue("mode-queue").await.unwrap();
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/mode-queue", BASE_URL);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=SendMessageBatch",
+        &[
+            ("QueueUrl", &queue_url),
+            ("SendMessageBatchRequestEntry.1.Id", "good-1"),
+            ("SendMessageBatchRequestEntry.1.MessageBody", "fine"),
+            ("SendMessageBatchRequestEntry.2.Id", "bad"),
+            ("SendMessageBatchRequestEntry.2.MessageBody", "bell \u{7}"),
+            ("SendMessageBatchRequestEntry.3.Id", "good-2"),
+            ("SendMessageBatchRequestEntry.3.MessageBody", "also fine"),
+        ],
+    )
+    .await;
+    let stored = service
+        .get_oldest_queue_messages("mode-queue", DEFAULT_MESSAGE_LIST_LIMIT)
+        .await
+        .unwrap()
+        .len();
+    (status, body, stored)
+}
+
+#[tokio::test]
+async fn test_per_entry_batch_mode_stores_valid_entries() {
+    let (status, body, stored) = send_batch_with_invalid_entry(BatchMode::PerEntry).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(xml_values(&body, "Code"), vec!["InvalidMessageContents"]);
+    assert_eq!(xml_values(&body, "MessageId").len(), 2);
+    assert_eq!(stored, 2);
+}
+
+#[tokio::test]
+async fn test_all_or_nothing_batch_mode_rejects_whole_batch() {
+    let (status, body, stored) = send_batch_with_invalid_entry(BatchMode::AllOrNothing).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(xml_values(&body, "Code"), vec!["InvalidMessageContents"]);
+    assert!(body.contains("bad"));
+    assert!(xml_values(&body, "MessageId").is_empty());
+    assert_eq!(stored, 0);
+}
+
+#[tokio::test]
+async fn test_account_path_queue_urls() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("orders.fifo").await.unwrap();
+    service.create_queue("plain").await.unwrap();
+    let router = test_router(service);
+
+    // The URL shape AWS SDKs build, plus a trailing slash variant
+    let fifo_url = format!("{}/000000000000/orders.fifo", BASE_URL);
+    let (status, _) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[
+            ("QueueUrl", &fifo_url),
+            ("MessageBody", "via account path"),
+            ("MessageGroupId", "group-1"),
+            ("MessageDeduplicationId", "dedup-1"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=GetQueueAttributes",
+        &[("QueueUrl", &format!("{}/", fifo_url))],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        xml_values(&body, "Value")[0],
+        "1",
+        "the message sent above should be counted: {}",
+        body
+    );
+
+    // Path-style requests accept the account segment too
+    let (status, body) = form_request(
+        &router,
+        "/000000000000/orders.fifo?Action=ReceiveMessage",
+        &[],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(xml_values(&body, "Body"), vec!["via account path"]);
+
+    let plain_url = format!("{}/000000000000/plain", BASE_URL);
+    let (status, _) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[("QueueUrl", &plain_url), ("MessageBody", "standard")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = form_request(&router, "/plain?Action=ReceiveMessage", &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(xml_values(&body, "Body"), vec!["standard"]);
+}
+
+#[tokio::test]
+async fn test_unimplemented_sqs_actions_are_unsupported_not_invalid() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("actions-queue").await.unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/actions-queue", BASE_URL);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=AddPermission",
+        &[("QueueUrl", &queue_url)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(xml_values(&body, "Code"), vec!["UnsupportedOperation"]);
+    assert!(
+        body.contains("SendMessage"),
+        "should list supported actions"
+    );
+
+    let (_, body) = form_request(&router, "/actions-queue?Action=AddPermission", &[]).await;
+    assert_eq!(xml_values(&body, "Code"), vec!["UnsupportedOperation"]);
+
+    let (status, body) = form_request(&router, "/?Action=SendMesage", &[]).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(xml_values(&body, "Code"), vec!["InvalidAction"]);
+}
+
+#[tokio::test]
+async fn test_actions_listing_matches_dispatch() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("probe-queue").await.unwrap();
+    let router = test_router(service);
+
+    let (status, body) = get_request(&router, "/actions").await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let actions = json["actions"].as_array().unwrap();
+
+    let find = |name: &str| actions.iter().find(|a| a["name"] == name).unwrap();
+    assert_eq!(find("SendMessage")["implemented"], true);
+    assert_eq!(find("SendMessage")["extension"], false);
+    assert_eq!(find("ReceiveMessageBatch")["extension"], true);
+    assert_eq!(find("AddPermission")["implemented"], false);
+
+    // Each listed endpoint dispatches the action, and unimplemented AWS actions say so
+    for entry in actions {
+        let name = entry["name"].as_str().unwrap();
+        let endpoints: Vec<&str> = entry["endpoints"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e.as_str().unwrap())
+            .collect();
+        for (endpoint, path) in [("/", "/"), ("/{queue_name}", "/probe-queue")] {
+            let (_, body) = form_request(&router, &format!("{}?Action={}", path, name), &[]).await;
+            let code = xml_values(&body, "Code");
+            if endpoints.contains(&endpoint) {
+                assert!(
+                    !code.contains(&"InvalidAction".to_string())
+                        && !code.contains(&"UnsupportedOperation".to_string()),
+                    "{} should be dispatched at {}: {}",
+                    name,
+                    path,
+                    body
+                );
+            } else if entry["extension"] == false {
+                assert_eq!(code, vec!["UnsupportedOperation"], "{} at {}", name, path);
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_root_and_queue_path_accept_the_same_actions() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("shared-queue").await.unwrap();
+    service
+        .send_message("shared-queue", "hello", None, None)
+        .await
+        .unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/shared-queue", BASE_URL);
+
+    let (_, body) = get_request(&router, "/actions").await;
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    for entry in json["actions"].as_array().unwrap() {
+        if entry["implemented"] == true {
+            assert_eq!(
+                entry["endpoints"],
+                serde_json::json!(["/", "/{queue_name}"]),
+                "{}",
+                entry["name"]
+            );
+        }
+    }
+
+    // Previously only available on one of the two endpoints
+    let (status, body) = form_request(
+        &router,
+        "/shared-queue?Action=GetQueueUrl",
+        &[("QueueName", "shared-queue")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(xml_values(&body, "QueueUrl"), vec![queue_url.clone()]);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=ReceiveMessageBatch",
+        &[("QueueUrl", &queue_url)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(xml_values(&body, "Body"), vec!["hello"]);
+}
+
+// Run every implemented action against one queue, either through POST / with a
+// QueueUrl or through POST /<queue-name> without one
+async fn exercise_every_action(router: &axum::Router, queue: &str, path_style: bool) {
+    let queue_url = format!("{}/{}", BASE_URL, queue);
+    let call = |action: &'static str, params: Vec<(&'static str, String)>| {
+        let queue_url = queue_url.clone();
+        async move {
+            let mut params = params;
+            let path = if path_style {
+                format!("/{}?Action={}", queue, action)
+            } else {
+                params.push(("QueueUrl", queue_url));
+                format!("/?Action={}", action)
+            };
+            let params: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+            let (status, body) = form_request(router, &path, &params).await;
+            assert_eq!(status, StatusCode::OK, "{} via {}: {}", action, path, body);
+            body
+        }
+    };
+
+    call("CreateQueue", vec![("QueueName", queue.to_string())]).await;
+    let body = call("GetQueueUrl", vec![("QueueName", queue.to_string())]).await;
+    assert_eq!(xml_values(&body, "QueueUrl"), vec![queue_url.clone()]);
+    let body = call("ListQueues", vec![]).await;
+    assert!(xml_values(&body, "QueueUrl").contains(&queue_url));
+
+    call(
+        "SetQueueAttributes",
+        vec![
+            ("Attribute.1.Name", "VisibilityTimeout".to_string()),
+            ("Attribute.1.Value", "45".to_string()),
+        ],
+    )
+    .await;
+    call("GetQueueAttributes", vec![]).await;
+
+    call(
+        "TagQueue",
+        vec![
+            ("Tag.1.Key", "route".to_string()),
+            ("Tag.1.Value", "both".to_string()),
+        ],
+    )
+    .await;
+    let body = call("ListQueueTags", vec![]).await;
+    assert_eq!(xml_values(&body, "Key"), vec!["route"]);
+    call("UntagQueue", vec![("TagKey.1", "route".to_string())]).await;
+
+    call("SendMessage", vec![("MessageBody", "single".to_string())]).await;
+    let body = call(
+        "SendMessageBatch",
+        vec![
+            ("SendMessageBatchRequestEntry.1.Id", "a".to_string()),
+            (
+                "SendMessageBatchRequestEntry.1.MessageBody",
+                "batched".to_string(),
+            ),
+        ],
+    )
+    .await;
+    assert_eq!(xml_values(&body, "MessageId").len(), 1);
+
+    let body = call("ReceiveMessage", vec![]).await;
+    let receipt = xml_values(&body, "ReceiptHandle").remove(0);
+    call(
+        "ChangeMessageVisibility",
+        vec![
+            ("ReceiptHandle", receipt.clone()),
+            ("VisibilityTimeout", "60".to_string()),
+        ],
+    )
+    .await;
+    call("DeleteMessage", vec![("ReceiptHandle", receipt)]).await;
+
+    let body = call("ReceiveMessageBatch", vec![]).await;
+    let receipt = xml_values(&body, "ReceiptHandle").remove(0);
+    let body = call(
+        "DeleteMessageBatch",
+        vec![
+            ("DeleteMessageBatchRequestEntry.1.Id", "a".to_string()),
+            ("DeleteMessageBatchRequestEntry.1.ReceiptHandle", receipt),
+        ],
+    )
+    .await;
+    assert_eq!(xml_values(&body, "Id"), vec!["a"]);
+
+    call("ListDeadLetterSourceQueues", vec![]).await;
+    call("PurgeQueue", vec![]).await;
+    call("DeleteQueue", vec![]).await;
+}
+
+#[tokio::test]
+async fn test_every_action_works_from_both_routes() {
+    let (_temp_dir, service) = test_service().await;
+    let router = test_router(service);
+
+    exercise_every_action(&router, "root-style", false).await;
+    exercise_every_action(&router, "path-style", true).await;
+}
+
+#[tokio::test]
+async fn test_fifo_batch_send_applies_content_based_deduplication() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("dedup.fifo").await.unwrap();
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/dedup.fifo", BASE_URL);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=SendMessageBatch",
+        &[
+            ("QueueUrl", &queue_url),
+            ("SendMessageBatchRequestEntry.1.Id", "a"),
+            ("SendMessageBatchRequestEntry.1.MessageBody", "same body"),
+            ("SendMessageBatchRequestEntry.1.MessageGroupId", "group"),
+            ("SendMessageBatchRequestEntry.2.Id", "b"),
+            ("SendMessageBatchRequestEntry.2.MessageBody", "same body"),
+            ("SendMessageBatchRequestEntry.2.MessageGroupId", "group"),
+            ("SendMessageBatchRequestEntry.3.Id", "c"),
+            ("SendMessageBatchRequestEntry.3.MessageBody", "other body"),
+            ("SendMessageBatchRequestEntry.3.MessageGroupId", "group"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let bodies: Vec<String> = service
+        .get_oldest_queue_messages("dedup.fifo", DEFAULT_MESSAGE_LIST_LIMIT)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|message| message.1)
+        .collect();
+    assert_eq!(bodies, vec!["same body", "other body"]);
+}
+
+#[tokio::test]
+async fn test_send_message_stores_message_group_id() {
+    let (temp_dir, service) = test_service().await;
+    service.create_queue("grouped.fifo").await.unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/grouped.fifo", BASE_URL);
+
+    for (body, group) in [("payment", "customer-42"), ("refund", "customer-7")] {
+        let params = [
+            ("QueueUrl", queue_url.as_str()),
+            ("MessageBody", body),
+            ("MessageGroupId", group),
+        ];
+        let (status, body) = form_request(&router, "/?Action=SendMessage", &params).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+    }
+
+    let conn = rusqlite::Connection::open(temp_dir.path().join("test.db")).unwrap();
+    let group_of = |body: &str| -> String {
+        conn.query_row(
+            "SELECT message_group_id FROM messages WHERE body = ?1",
+            [body],
+            |row| row.get(0),
+        )
+        .unwrap()
+    };
+    assert_eq!(group_of("payment"), "customer-42");
+    assert_eq!(group_of("refund"), "customer-7");
+}
+
+#[tokio::test]
+async fn test_fifo_sends_require_message_group_id() {
+    let (_temp_dir, service) = test_service().await;
+    // FIFO-ness comes from the queue's configuration, not the URL
+    service.create_queue("required.fifo").await.unwrap();
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/required.fifo", BASE_URL);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[
+            ("QueueUrl", &queue_url),
+            ("MessageBody", "ungrouped"),
+            ("MessageDeduplicationId", "ungrouped"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(xml_values(&body, "Code"), vec!["MissingParameter"]);
+    assert!(body.contains("MessageGroupId"), "{}", body);
+
+    // In a batch only the entry without a group fails
+    let (status, body) = form_request(
+        &router,
+        "/?Action=SendMessageBatch",
+        &[
+            ("QueueUrl", &queue_url),
+            ("SendMessageBatchRequestEntry.1.Id", "grouped"),
+            ("SendMessageBatchRequestEntry.1.MessageBody", "grouped"),
+            ("SendMessageBatchRequestEntry.1.MessageGroupId", "orders"),
+            (
+                "SendMessageBatchRequestEntry.1.MessageDeduplicationId",
+                "grouped",
+            ),
+            ("SendMessageBatchRequestEntry.2.Id", "ungrouped"),
+            ("SendMessageBatchRequestEntry.2.MessageBody", "ungrouped"),
+            (
+                "SendMessageBatchRequestEntry.2.MessageDeduplicationId",
+                "ungrouped",
+            ),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let failed = body
+        .split("<BatchResultErrorEntry>")
+        .nth(1)
+        .expect("one entry should fail");
+    assert_eq!(xml_values(failed, "Id"), vec!["ungrouped"]);
+    assert_eq!(xml_values(failed, "Code"), vec!["MissingParameter"]);
+    assert_eq!(xml_values(failed, "SenderFault"), vec!["true"]);
+
+    // The plain service send, used by `qlite send`, can't name a group either
+    let err = service
+        .send_message("required.fifo", "ungrouped", None, None)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, tokio_rusqlite::Error::Other(e) if e.is::<MissingMessageGroupId>()),
+        "{:?}",
+        err
+    );
+
+    let attributes = service
+        .get_queue_attributes("required.fifo")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(attributes.approximate_number_of_messages, 1);
+}
+
+#[tokio::test]
+async fn test_batch_send_honors_message_deduplication_id() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("dedup-batch").await.unwrap();
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/dedup-batch", BASE_URL);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=SendMessageBatch",
+        &[
+            ("QueueUrl", &queue_url),
+            ("SendMessageBatchRequestEntry.1.Id", "a"),
+            ("SendMessageBatchRequestEntry.1.MessageBody", "charge card"),
+            (
+                "SendMessageBatchRequestEntry.1.MessageDeduplicationId",
+                "order-7",
+            ),
+            ("SendMessageBatchRequestEntry.2.Id", "b"),
+            (
+                "SendMessageBatchRequestEntry.2.MessageBody",
+                "charge card again",
+            ),
+            (
+                "SendMessageBatchRequestEntry.2.MessageDeduplicationId",
+                "order-7",
+            ),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    // Both entries succeed, and the duplicate reports the original message id
+    let message_ids = xml_values(&body, "MessageId");
+    assert_eq!(message_ids.len(), 2);
+    assert_eq!(message_ids[0], message_ids[1]);
+
+    // Same as a single send repeating the dedup id
+    let (_, body) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[
+            ("QueueUrl", &queue_url),
+            ("MessageBody", "charge card once more"),
+            ("MessageDeduplicationId", "order-7"),
+        ],
+    )
+    .await;
+    assert_eq!(xml_values(&body, "MessageId"), vec![message_ids[0].clone()]);
+
+    let messages = service
+        .get_oldest_queue_messages("dedup-batch", DEFAULT_MESSAGE_LIST_LIMIT)
+        .await
+        .unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].1, "charge card");
+}
+
+#[tokio::test]
+async fn test_fifo_batch_send_preserves_order_and_groups() {
+    let (temp_dir, service) = test_service().await;
+    service.create_queue("ordered.fifo").await.unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/ordered.fifo", BASE_URL);
+
+    let (status, _) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[
+            ("QueueUrl", &queue_url),
+            ("MessageBody", "first"),
+            ("MessageGroupId", "payments"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = form_request(
+        &router,
+        "/?Action=SendMessageBatch",
+        &[
+            ("QueueUrl", &queue_url),
+            ("SendMessageBatchRequestEntry.1.Id", "a"),
+            ("SendMessageBatchRequestEntry.1.MessageBody", "second"),
+            ("SendMessageBatchRequestEntry.1.MessageGroupId", "orders"),
+            ("SendMessageBatchRequestEntry.2.Id", "b"),
+            ("SendMessageBatchRequestEntry.2.MessageBody", "third"),
+            ("SendMessageBatchRequestEntry.2.MessageGroupId", "refunds"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (_, body) = form_request(
+        &router,
+        "/?Action=ReceiveMessage",
+        &[("QueueUrl", &queue_url), ("MaxNumberOfMessages", "10")],
+    )
+    .await;
+    assert_eq!(xml_values(&body, "Body"), vec!["first", "second", "third"]);
+
+    let conn = rusqlite::Connection::open(temp_dir.path().join("test.db")).unwrap();
+    let mut stmt = conn
+        .prepare("SELECT message_group_id, sequence_number FROM messages WHERE queue_name = 'ordered.fifo' ORDER BY sequence_number")
+        .unwrap();
+    let rows: Vec<(String, i64)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            ("payments".to_string(), 1),
+            ("orders".to_string(), 2),
+            ("refunds".to_string(), 3)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_fifo_group_blocks_while_a_message_is_in_flight() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("blocking.fifo").await.unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/blocking.fifo", BASE_URL);
+
+    for (body, group) in [
+        ("first", "orders"),
+        ("second", "orders"),
+        ("other", "refunds"),
+    ] {
+        let (status, _) = form_request(
+            &router,
+            "/?Action=SendMessage",
+            &[
+                ("QueueUrl", &queue_url),
+                ("MessageBody", body),
+                ("MessageGroupId", group),
+                ("MessageDeduplicationId", body),
+            ],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let receive_params = [("QueueUrl", queue_url.as_str())];
+    let (_, body) = form_request(&router, "/?Action=ReceiveMessage", &receive_params).await;
+    assert_eq!(xml_values(&body, "Body"), vec!["first"]);
+    let first_handle = xml_values(&body, "ReceiptHandle").remove(0);
+
+    // The orders group is blocked, but refunds is still delivered
+    let (_, body) = form_request(&router, "/?Action=ReceiveMessage", &receive_params).await;
+    assert_eq!(xml_values(&body, "Body"), vec!["other"]);
+    let (_, body) = form_request(&router, "/?Action=ReceiveMessage", &receive_params).await;
+    assert!(xml_values(&body, "Body").is_empty(), "{}", body);
+
+    let (status, _) = form_request(
+        &router,
+        "/?Action=DeleteMessage",
+        &[("QueueUrl", &queue_url), ("ReceiptHandle", &first_handle)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = form_request(&router, "/?Action=ReceiveMessage", &receive_params).await;
+    assert_eq!(xml_values(&body, "Body"), vec!["second"]);
+}
+
+#[tokio::test]
+async fn test_fifo_receipt_handles_carry_sequence_number_when_enabled() {
+    for encoding in [ReceiptHandleEncoding::Plain, ReceiptHandleEncoding::Signed] {
+        let mut config = Config::default();
+        config.receipt_handles.encoding = encoding;
+        config.receipt_handles.include_sequence_number = true;
+        let (_temp_dir, service) = test_service_with_config(&config).await;
+        service.create_queue("sequenced.fifo").await.unwrap();
+        service.create_queue("standard-queue").await.unwrap();
+        let router = test_router(service);
+        let queue_url = format!("{}/sequenced.fifo", BASE_URL);
+
+        for (body, group) in [("first", "orders"), ("second", "refunds")] {
+            let (status, _) = form_request(
+                &router,
+                "/?Action=SendMessage",
+                &[
+                    ("QueueUrl", &queue_url),
+                    ("MessageBody", body),
+                    ("MessageGroupId", group),
+                    ("MessageDeduplicationId", body),
+                ],
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+        }
+
+        let (_, body) = form_request(
+            &router,
+            "/?Action=ReceiveMessage",
+            &[("QueueUrl", &queue_url), ("MaxNumberOfMessages", "10")],
+        )
+        .await;
+        let handles = xml_values(&body, "ReceiptHandle");
+        assert_eq!(xml_values(&body, "Body"), vec!["first", "second"]);
+        assert_eq!(
+            handles
+                .iter()
+                .map(|handle| receipt_sequence_number(handle))
+                .collect::<Vec<_>>(),
+            vec![Some(1), Some(2)],
+            "{:?}",
+            encoding
+        );
+
+        // The sequence number doesn't stop the handle from deleting the message
+        let (status, body) = form_request(
+            &router,
+            "/?Action=DeleteMessage",
+            &[("QueueUrl", &queue_url), ("ReceiptHandle", &handles[0])],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+
+        // Standard queue messages have no sequence number to carry
+        let standard_url = format!("{}/standard-queue", BASE_URL);
+        form_request(
+            &router,
+            "/?Action=SendMessage",
+            &[("QueueUrl", &standard_url), ("MessageBody", "plain")],
+        )
+        .await;
+        let (_, body) = form_request(
+            &router,
+            "/?Action=ReceiveMessage",
+            &[("QueueUrl", &standard_url)],
+        )
+        .await;
+        let handle = xml_values(&body, "ReceiptHandle").remove(0);
+        assert_eq!(receipt_sequence_number(&handle), None);
+    }
+}
+
+#[tokio::test]
+async fn test_ack_watermark_rejects_out_of_order_delete() {
+    let (_temp_dir, service) = test_service().await;
+    let config = QueueConfig {
+        ack_watermark: true,
+        ..QueueConfig::new("watermark.fifo".to_string(), true)
+    };
+    service.create_queue_with_config(&config).await.unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/watermark.fifo", BASE_URL);
+
+    for (body, dedup_id) in [("first", "1"), ("second", "2")] {
+        let (status, _) = form_request(
+            &router,
+            "/?Action=SendMessage",
+            &[
+                ("QueueUrl", &queue_url),
+                ("MessageBody", body),
+                ("MessageGroupId", "orders"),
+                ("MessageDeduplicationId", dedup_id),
+            ],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (_, body) = form_request(
+        &router,
+        "/?Action=ReceiveMessage",
+        &[("QueueUrl", &queue_url), ("MaxNumberOfMessages", "10")],
+    )
+    .await;
+    let handles = xml_values(&body, "ReceiptHandle");
+    assert_eq!(handles.len(), 2);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=DeleteMessage",
+        &[("QueueUrl", &queue_url), ("ReceiptHandle", &handles[1])],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("MessageOutOfOrder"), "{}", body);
+
+    for handle in [&handles[0], &handles[1]] {
+        let (status, body) = form_request(
+            &router,
+            "/?Action=DeleteMessage",
+            &[("QueueUrl", &queue_url), ("ReceiptHandle", handle)],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+    }
+}
+
+#[tokio::test]
+async fn test_delivery_log_records_each_receive() {
+    let mut config = Config::default();
+    config.logging.delivery_log = true;
+    let (_temp_dir, service) = test_service_with_config(&config).await;
+    service.create_queue("logged-queue").await.unwrap();
+    let message_id = service
+        .send_message("logged-queue", "traced", None, None)
+        .await
+        .unwrap();
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/logged-queue", BASE_URL);
+    let deliveries_path = format!("/api/message/{}/deliveries", message_id);
+
+    let (status, body) = get_request(&router, &deliveries_path).await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(json["deliveries"].as_array().unwrap().is_empty());
+
+    for (attempt, consumer) in ["worker-1", "worker-2"].into_iter().enumerate() {
+        let (status, body) = form_request(
+            &router,
+            "/?Action=ReceiveMessage",
+            &[("QueueUrl", &queue_url), ("ConsumerId", consumer)],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        let receipt = xml_values(&body, "ReceiptHandle").remove(0);
+        service.nack_message(&message_id).await.unwrap();
+
+        let (_, body) = get_request(&router, &deliveries_path).await;
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        let deliveries = json["deliveries"].as_array().unwrap();
+        assert_eq!(deliveries.len(), attempt + 1);
+        assert_eq!(deliveries[attempt]["consumer_id"], consumer);
+        assert_eq!(deliveries[attempt]["receipt_handle"], receipt.as_str());
+    }
+
+    // Receives without a ConsumerId are logged too
+    service
+        .receive_message("logged-queue")
+        .await
+        .unwrap()
+        .unwrap();
+    let (_, body) = get_request(&router, &deliveries_path).await;
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["deliveries"].as_array().unwrap().len(), 3);
+    assert!(json["deliveries"][2]["consumer_id"].is_null());
+}
+
+#[tokio::test]
+async fn test_delivery_log_disabled_by_default() {
+    let (_temp_dir, service) = test_service().await;
+    let router = test_router(service);
+
+    let (status, _) = get_request(&router, "/api/message/anything/deliveries").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_stale_receipt_handle_is_rejected() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("stale-queue").await.unwrap();
+    service
+        .send_message("stale-queue", "work", None, None)
+        .await
+        .unwrap();
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/stale-queue", BASE_URL);
+
+    // The first consumer's visibility timeout lapses and the message is redelivered
+    let stale = service
+        .receive_message("stale-queue")
+        .await
+        .unwrap()
+        .unwrap();
+    service
+        .change_message_visibility("stale-queue", &stale.receipt_handle, 0)
+        .await
+        .unwrap();
+    let current = service
+        .receive_message("stale-queue")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(current.id, stale.id);
+    assert_ne!(current.receipt_handle, stale.receipt_handle);
+
+    let delete = |receipt_handle: String| {
+        let (router, queue_url) = (router.clone(), queue_url.clone());
+        async move {
+            form_request(
+                &router,
+                "/?Action=DeleteMessage",
+                &[("QueueUrl", &queue_url), ("ReceiptHandle", &receipt_handle)],
+            )
+            .await
+        }
+    };
+
+    let (status, body) = delete(stale.receipt_handle.clone()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("ReceiptHandleIsInvalid"), "{}", body);
+    assert!(body.contains("has expired"), "{}", body);
+    assert_eq!(service.metrics().stale_receipt_handle_total(), 1);
+
+    let (status, body) = delete("never-issued:0000".to_string()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("is not valid"), "{}", body);
+    assert_eq!(service.metrics().stale_receipt_handle_total(), 1);
+
+    let (status, body) = delete(current.receipt_handle).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
+
+#[tokio::test]
+async fn test_visibility_extension_ceiling_is_enforced() {
+    let mut config = Config::default();
+    config.queues.max_visibility_extension_seconds = 10;
+    let (_temp_dir, service) = test_service_with_config(&config).await;
+    service.create_queue("extend-queue").await.unwrap();
+    service
+        .send_message("extend-queue", "slow work", None, None)
+        .await
+        .unwrap();
+    let receipt = service
+        .receive_message("extend-queue")
+        .await
+        .unwrap()
+        .unwrap()
+        .receipt_handle;
+    let router = test_router(service);
+
+    let queue_url = format!("{}/extend-queue", BASE_URL);
+    let change_visibility = |timeout: &'static str| {
+        let (router, queue_url, receipt) = (router.clone(), queue_url.clone(), receipt.clone());
+        async move {
+            form_request(
+                &router,
+                "/?Action=ChangeMessageVisibility",
+                &[
+                    ("QueueUrl", &queue_url),
+                    ("ReceiptHandle", &receipt),
+                    ("VisibilityTimeout", timeout),
+                ],
+            )
+            .await
+        }
+    };
+
+    // Repeated extensions are fine while they stay within the ceiling
+    for timeout in ["3", "6", "9"] {
+        let (status, body) = change_visibility(timeout).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+    }
+
+    let (status, body) = change_visibility("30").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("InvalidParameterValue"), "{}", body);
+    assert!(body.contains("beyond the limit [10 seconds]"), "{}", body);
+
+    let (status, body) = change_visibility("43201").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("InvalidParameterValue"), "{}", body);
+
+    // 0 makes the message visible again, after which it's no longer in flight
+    let (status, _) = change_visibility("0").await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = change_visibility("5").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("MessageNotInflight"), "{}", body);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=ChangeMessageVisibility",
+        &[
+            ("QueueUrl", &queue_url),
+            ("ReceiptHandle", "no-such-message"),
+            ("VisibilityTimeout", "5"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("ReceiptHandleIsInvalid"), "{}", body);
+}
+
+#[tokio::test]
+async fn test_queue_url_takes_precedence_over_path() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("path-queue").await.unwrap();
+    service.create_queue("url-queue").await.unwrap();
+    let router = test_router(service.clone());
+
+    let url_queue = format!("{}/url-queue", BASE_URL);
+    let (status, _) = form_request(
+        &router,
+        "/path-queue?Action=SendMessage",
+        &[
+            ("QueueUrl", &url_queue),
+            ("MessageBody", "routed by QueueUrl"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = form_request(
+        &router,
+        "/path-queue?Action=SendMessageBatch",
+        &[
+            ("QueueUrl", &url_queue),
+            ("SendMessageBatchRequestEntry.1.Id", "a"),
+            ("SendMessageBatchRequestEntry.1.MessageBody", "batched"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert_eq!(
+        service
+            .get_oldest_queue_messages("url-queue", DEFAULT_MESSAGE_LIST_LIMIT)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+    assert!(
+        service
+            .get_oldest_queue_messages("path-queue", DEFAULT_MESSAGE_LIST_LIMIT)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_simulated_latency_delays_sends() {
+    let mut config = Config::default();
+    config.testing.simulate_latency_ms = 200;
+    let (_temp_dir, service) = test_service_with_config(&config).await;
+    service.create_queue("slow-queue").await.unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/slow-queue", BASE_URL);
+
+    let started = std::time::Instant::now();
+    let (status, _) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[("QueueUrl", &queue_url), ("MessageBody", "eventually")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(started.elapsed() >= std::time::Duration::from_millis(200));
+
+    // Queue management isn't delayed
+    let started = std::time::Instant::now();
+    form_request(&router, "/?Action=ListQueues", &[]).await;
+    assert!(started.elapsed() < std::time::Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn test_fault_injection_fails_every_request_at_full_rate() {
+    let mut config = Config::default();
+    config.testing.fault_injection_rate = 1.0;
+    let (_temp_dir, service) = test_service_with_config(&config).await;
+    service.create_queue("flaky-queue").await.unwrap();
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/flaky-queue", BASE_URL);
+
+    for _ in 0..20 {
+        let (status, body) = form_request(
+            &router,
+            "/?Action=SendMessage",
+            &[("QueueUrl", &queue_url), ("MessageBody", "retry me")],
+        )
+        .await;
+        let code = xml_values(&body, "Code").remove(0);
+        match code.as_str() {
+            "ServiceUnavailable" => assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE),
+            "Throttling" => assert_eq!(status, StatusCode::TOO_MANY_REQUESTS),
+            other => panic!("unexpected error code {}", other),
+        }
+    }
+    assert!(
+        service
+            .get_oldest_queue_messages("flaky-queue", DEFAULT_MESSAGE_LIST_LIMIT)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // The bypass header and non-SQS endpoints are never faulted
+    let request = axum::http::Request::builder()
+        .method("POST")
+        .uri("/?Action=ListQueues")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .header(qlite::http_server::NO_FAULT_HEADER, "1")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let (status, _) = send(&router, request).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = get_request(&router, "/health/live").await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_backup_produces_consistent_copy() {
+    let mut config = Config::default();
+    config.server.admin_token = Some("secret".to_string());
+    let (temp_dir, service) = test_service_with_config(&config).await;
+    service.create_queue("backed-up").await.unwrap();
+    for i in 0..5 {
+        service
+            .send_message("backed-up", &format!("msg-{}", i), None, None)
+            .await
+            .unwrap();
+    }
+    let router = test_router(service);
+    let backup_path = temp_dir.path().join("backup.db");
+    let payload = serde_json::json!({ "path": backup_path.to_str().unwrap() });
+
+    let (status, _) = admin_post(&router, "/admin/backup", None, payload.clone()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = admin_post(&router, "/admin/backup", Some("wrong"), payload.clone()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) =
+        admin_post(&router, "/admin/backup", Some("secret"), payload.clone()).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        json["size_bytes"].as_u64().unwrap(),
+        std::fs::metadata(&backup_path).unwrap().len()
+    );
+
+    // Never overwrites an existing file
+    let (status, _) = admin_post(&router, "/admin/backup", Some("secret"), payload).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let restored = qlite::database::Database::new(backup_path.to_str().unwrap())
+        .await
+        .expect("Backup should open as a database");
+    let queues = restored.list_queues().await.unwrap();
+    assert_eq!(queues.len(), 1);
+    assert_eq!(queues[0].0, "backed-up");
+    assert_eq!(
+        restored
+            .get_queue_messages_page("backed-up", DEFAULT_MESSAGE_LIST_LIMIT, 0)
+            .await
+            .unwrap()
+            .len(),
+        5
+    );
+}
+
+#[tokio::test]
+async fn test_admin_endpoints_disabled_without_token() {
+    let (temp_dir, service) = test_service().await;
+    let router = test_router(service);
+    let backup_path = temp_dir.path().join("backup.db");
+
+    let (status, _) = admin_post(
+        &router,
+        "/admin/backup",
+        Some("anything"),
+        serde_json::json!({ "path": backup_path.to_str().unwrap() }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(!backup_path.exists());
+
+    let (status, _) = admin_post(
+        &router,
+        "/admin/checkpoint",
+        Some("anything"),
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_manual_wal_checkpoint_truncates_wal() {
+    let mut config = Config::default();
+    config.server.admin_token = Some("secret".to_string());
+    let (_temp_dir, service) = test_service_with_config(&config).await;
+    service.create_queue("wal-queue").await.unwrap();
+    for i in 0..20 {
+        service
+            .send_message("wal-queue", &format!("msg-{}", i), None, None)
+            .await
+            .unwrap();
+    }
+    assert!(service.wal_size_bytes() > 0);
+    let router = test_router(service);
+
+    let (status, _) = admin_post(&router, "/admin/checkpoint", None, serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = admin_post(
+        &router,
+        "/admin/checkpoint",
+        Some("secret"),
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["busy"], false);
+    assert_eq!(json["wal_size_bytes"], 0);
+
+    let (_, metrics) = get_request(&router, "/metrics").await;
+    assert!(metrics.contains("qlite_wal_size_bytes 0\n"));
+    assert!(metrics.contains("qlite_wal_checkpoints_total 1\n"));
+    assert!(!metrics.contains("qlite_wal_last_checkpoint_timestamp_seconds 0\n"));
+}
+
+#[tokio::test]
+async fn test_base_url_trailing_slash_is_trimmed() {
+    let (_temp_dir, service) = test_service().await;
+    let router = create_router(service, format!("{}/", BASE_URL), false);
+
+    let (_, body) = form_request(
+        &router,
+        "/?Action=CreateQueue",
+        &[("QueueName", "slash-queue")],
+    )
+    .await;
+    let queue_url = format!("{}/slash-queue", BASE_URL);
+    assert_eq!(xml_values(&body, "QueueUrl"), vec![queue_url.clone()]);
+
+    let (_, body) = form_request(&router, "/?Action=ListQueues", &[]).await;
+    assert_eq!(xml_values(&body, "QueueUrl"), vec![queue_url.clone()]);
+
+    let (_, body) = form_request(
+        &router,
+        "/?Action=GetQueueUrl",
+        &[("QueueName", "slash-queue")],
+    )
+    .await;
+    let urls = xml_values(&body, "QueueUrl");
+    assert_eq!(urls, vec![queue_url]);
+    assert!(!urls[0].trim_start_matches("http://").contains("//"));
+}
+
+#[tokio::test]
+async fn test_json_protocol_message_attributes_are_stored() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("json-queue").await.unwrap();
+    let router = test_router(service.clone());
+
+    let (status, body) = json_request(
+        &router,
+        "SendMessage",
+        serde_json::json!({
+            "QueueUrl": format!("{}/json-queue", BASE_URL),
+            "MessageBody": "from the json protocol",
+            "MessageAttributes": {
+                "author": { "DataType": "String", "StringValue": "x" },
+                "priority": { "DataType": "Number", "StringValue": "5" }
+            }
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let message = service
+        .receive_message("json-queue")
+        .await
+        .unwrap()
+        .unwrap();
+    let attributes = message.attributes.expect("attributes should be stored");
+    assert_eq!(attributes.len(), 2);
+    assert_eq!(attributes["author"].string_value.as_deref(), Some("x"));
+    assert_eq!(attributes["author"].data_type, "String");
+    assert_eq!(attributes["priority"].string_value.as_deref(), Some("5"));
+    assert_eq!(attributes["priority"].data_type, "Number");
+}
+
+#[tokio::test]
+async fn test_json_protocol_batch_attributes_round_trip() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("json-batch-queue").await.unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/json-batch-queue", BASE_URL);
+
+    let (status, body) = json_request(
+        &router,
+        "SendMessageBatch",
+        serde_json::json!({
+            "QueueUrl": queue_url,
+            "Entries": [{
+                "Id": "a",
+                "MessageBody": "batched",
+                "MessageAttributes": {
+                    "author": { "DataType": "String", "StringValue": "x" },
+                    "retries": { "DataType": "Number", "StringValue": "3" }
+                }
+            }]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (status, body) = json_request(
+        &router,
+        "ReceiveMessage",
+        serde_json::json!({ "QueueUrl": queue_url, "MessageAttributeNames": ["All"] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(xml_values(&body, "Body"), vec!["batched"]);
+
+    let mut values = xml_values(&body, "StringValue");
+    values.sort();
+    assert_eq!(values, vec!["3", "x"]);
+    let mut data_types = xml_values(&body, "DataType");
+    data_types.sort();
+    assert_eq!(data_types, vec!["Number", "String"]);
+}
+
+#[tokio::test]
+async fn test_purge_queue_removes_messages_and_rate_limits() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("purge-queue").await.unwrap();
+    for body in ["one", "two", "three"] {
+        service
+            .send_message("purge-queue", body, None, None)
+            .await
+            .unwrap();
+    }
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/purge-queue", BASE_URL);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=PurgeQueue",
+        &[("QueueUrl", queue_url.as_str())],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("<PurgeQueueResponse>"));
+    let attributes = service
+        .get_queue_attributes("purge-queue")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(attributes.approximate_number_of_messages, 0);
+    assert_eq!(attributes.approximate_number_of_messages_not_visible, 0);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=PurgeQueue",
+        &[("QueueUrl", queue_url.as_str())],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(xml_values(&body, "Code"), vec!["PurgeQueueInProgress"]);
+
+    let missing_url = format!("{}/missing-queue", BASE_URL);
+    let (status, body) = form_request(
+        &router,
+        "/?Action=PurgeQueue",
+        &[("QueueUrl", missing_url.as_str())],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        xml_values(&body, "Code"),
+        vec!["AWS.SimpleQueueService.NonExistentQueue"]
+    );
+}
+
+#[tokio::test]
+async fn test_zero_visibility_timeout_makes_message_visible_again() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("reset-queue").await.unwrap();
+    service.create_queue("other-queue").await.unwrap();
+    service
+        .send_message("reset-queue", "retry me", None, None)
+        .await
+        .unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/reset-queue", BASE_URL);
+
+    let (_, body) = form_request(
+        &router,
+        "/?Action=ReceiveMessage",
+        &[("QueueUrl", queue_url.as_str())],
+    )
+    .await;
+    let receipt = xml_values(&body, "ReceiptHandle").remove(0);
+
+    let (_, body) = form_request(
+        &router,
+        "/?Action=ReceiveMessage",
+        &[("QueueUrl", queue_url.as_str())],
+    )
+    .await;
+    assert!(xml_values(&body, "Body").is_empty());
+
+    // The handle only works through the URL of the message's own queue
+    let other_url = format!("{}/other-queue", BASE_URL);
+    let (status, body) = form_request(
+        &router,
+        "/?Action=ChangeMessageVisibility",
+        &[
+            ("QueueUrl", other_url.as_str()),
+            ("ReceiptHandle", receipt.as_str()),
+            ("VisibilityTimeout", "0"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("ReceiptHandleIsInvalid"), "{}", body);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=ChangeMessageVisibility",
+        &[
+            ("QueueUrl", queue_url.as_str()),
+            ("ReceiptHandle", receipt.as_str()),
+            ("VisibilityTimeout", "0"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body.contains("<ChangeMessageVisibilityResponse>"));
+
+    let (_, body) = form_request(
+        &router,
+        "/?Action=ReceiveMessage",
+        &[("QueueUrl", queue_url.as_str())],
+    )
+    .await;
+    assert_eq!(xml_values(&body, "Body"), vec!["retry me"]);
+}
+
+#[tokio::test]
+async fn test_json_protocol_receive_honors_attribute_selectors() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("selector-queue").await.unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/selector-queue", BASE_URL);
+
+    let (status, body) = json_request(
+        &router,
+        "SendMessage",
+        serde_json::json!({
+            "QueueUrl": queue_url,
+            "MessageBody": "pick and choose",
+            "MessageAttributes": {
+                "author": { "DataType": "String", "StringValue": "x" },
+                "priority": { "DataType": "Number", "StringValue": "5" }
+            }
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (status, body) = json_request(
+        &router,
+        "ReceiveMessage",
+        serde_json::json!({
+            "QueueUrl": queue_url,
+            "AttributeNames": ["SentTimestamp"],
+            "MessageAttributeNames": ["author"]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(xml_values(&body, "SentTimestamp").len(), 1);
+    assert!(xml_values(&body, "SenderId").is_empty());
+    assert!(xml_values(&body, "ApproximateReceiveCount").is_empty());
+    assert_eq!(xml_values(&body, "author").len(), 1);
+    assert!(xml_values(&body, "priority").is_empty());
+}
+
+#[tokio::test]
+async fn test_signed_receipt_handles_reject_tampering_and_expiry() {
+    let mut config = Config::default();
+    config.receipt_handles.encoding = ReceiptHandleEncoding::Signed;
+    config.receipt_handles.secret = Some("test-secret".to_string());
+    let (_temp_dir, service) = test_service_with_config(&config).await;
+    service.create_queue("signed-queue").await.unwrap();
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/signed-queue", BASE_URL);
+
+    let delete = |receipt: String| {
+        let router = router.clone();
+        let queue_url = queue_url.clone();
+        async move {
+            form_request(
+                &router,
+                "/?Action=DeleteMessage",
+                &[("QueueUrl", &queue_url), ("ReceiptHandle", &receipt)],
+            )
+            .await
+        }
+    };
+
+    service
+        .send_message("signed-queue", "signed", None, None)
+        .await
+        .unwrap();
+    let received = service
+        .receive_message("signed-queue")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!received.receipt_handle.contains(&received.id));
+
+    // Change the first character of the signed payload
+    let first = received.receipt_handle.chars().next().unwrap();
+    let tampered = format!(
+        "{}{}",
+        if first == 'A' { 'B' } else { 'A' },
+        &received.receipt_handle[1..]
+    );
+    let (status, body) = delete(tampered).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("ReceiptHandleIsInvalid"), "{}", body);
+
+    let (status, body) = delete(received.receipt_handle.clone()).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    // A handle signed with the same secret but an expiry in the past
+    let message_id = service
+        .send_message("signed-queue", "expired", None, None)
+        .await
+        .unwrap();
+    service
+        .receive_message("signed-queue")
+        .await
+        .unwrap()
+        .unwrap();
+    let signer = ReceiptSigner::new(Some("test-secret"));
+    let expired = signer.sign(
+        &message_id,
+        "signed-queue",
+        chrono::Utc::now() - chrono::Duration::seconds(1),
+    );
+    let (status, body) = delete(expired).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("ReceiptHandleIsInvalid"), "{}", body);
+
+    // Plain handles aren't accepted once signing is on
+    let (status, body) = delete(message_id.clone()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("ReceiptHandleIsInvalid"), "{}", body);
+
+    let valid = signer.sign(
+        &message_id,
+        "signed-queue",
+        chrono::Utc::now() + chrono::Duration::seconds(60),
+    );
+    let (status, body) = delete(valid).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
+
+#[tokio::test]
+async fn test_signed_receipt_handles_are_bound_to_queue_and_visibility_deadline() {
+    let mut config = Config::default();
+    config.receipt_handles.encoding = ReceiptHandleEncoding::Signed;
+    let (_temp_dir, service) = test_service_with_config(&config).await;
+    service.create_queue("signed-queue").await.unwrap();
+    service.create_queue("other-queue").await.unwrap();
+    service
+        .set_queue_attributes(
+            "signed-queue",
+            HashMap::from([("VisibilityTimeout".to_string(), "2".to_string())]),
+        )
+        .await
+        .unwrap();
+    let router = test_router(service.clone());
+
+    let request = |action: &'static str, queue: &'static str, receipt: String| {
+        let router = router.clone();
+        async move {
+            let queue_url = format!("{}/{}", BASE_URL, queue);
+            form_request(
+                &router,
+                &format!("/?Action={}", action),
+                &[
+                    ("QueueUrl", queue_url.as_str()),
+                    ("ReceiptHandle", receipt.as_str()),
+                    ("VisibilityTimeout", "5"),
+                ],
+            )
+            .await
+        }
+    };
+
+    service
+        .send_message("signed-queue", "bound", None, None)
+        .await
+        .unwrap();
+    let received = service
+        .receive_message("signed-queue")
+        .await
+        .unwrap()
+        .unwrap();
+
+    // A valid handle for signed-queue is rejected through another queue's URL
+    for action in ["DeleteMessage", "ChangeMessageVisibility"] {
+        let (status, body) = request(action, "other-queue", received.receipt_handle.clone()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", action);
+        assert!(body.contains("ReceiptHandleIsInvalid"), "{}", body);
+    }
+
+    // Once the visibility deadline has passed, the handle has expired
+    tokio::time::sleep(std::time::Duration::from_millis(2500)).await;
+    let (status, body) = request("DeleteMessage", "signed-queue", received.receipt_handle).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("ReceiptHandleIsInvalid"), "{}", body);
+
+    let redelivered = service
+        .receive_message("signed-queue")
+        .await
+        .unwrap()
+        .unwrap();
+    let (status, body) = request("DeleteMessage", "signed-queue", redelivered.receipt_handle).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
+
+#[tokio::test]
+async fn test_metrics_report_database_storage() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("storage-queue").await.unwrap();
+    let router = test_router(service.clone());
+
+    let gauge = |metrics: &str, name: &str| -> i64 {
+        metrics
+            .lines()
+            .find_map(|line| line.strip_prefix(&format!("{} ", name)))
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_else(|| panic!("{} missing from metrics", name))
+    };
+
+    let body = "x".repeat(4096);
+    for _ in 0..50 {
+        service
+            .send_message("storage-queue", &body, None, None)
+            .await
+            .unwrap();
+    }
+    let (_, metrics) = get_request(&router, "/metrics").await;
+    assert!(gauge(&metrics, "qlite_db_size_bytes") > 0);
+    let pages = gauge(&metrics, "qlite_db_page_count");
+    assert!(pages > 50, "{} pages", pages);
+
+    // Deleted rows leave free pages behind until a VACUUM
+    service.purge_queue("storage-queue").await.unwrap();
+    let (_, metrics) = get_request(&router, "/metrics").await;
+    assert!(gauge(&metrics, "qlite_db_freelist_count") > 0);
+    assert_eq!(gauge(&metrics, "qlite_db_page_count"), pages);
+}
+
+#[tokio::test]
+async fn test_send_message_returns_md5_of_message_attributes() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("md5-queue").await.unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/md5-queue", BASE_URL);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[
+            ("QueueUrl", queue_url.as_str()),
+            ("MessageBody", "checksummed"),
+            ("MessageAttribute.1.Name", "priority"),
+            ("MessageAttribute.1.Value.StringValue", "5"),
+            ("MessageAttribute.1.Value.DataType", "Number"),
+            ("MessageAttribute.2.Name", "author"),
+            ("MessageAttribute.2.Value.StringValue", "x"),
+            ("MessageAttribute.2.Value.DataType", "String"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        xml_values(&body, "MD5OfMessageAttributes"),
+        vec!["314326d3d7174a181cd3e52b63b08cb7"]
+    );
+
+    let (_, body) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[("QueueUrl", queue_url.as_str()), ("MessageBody", "plain")],
+    )
+    .await;
+    assert!(!body.contains("MD5OfMessageAttributes"), "{}", body);
+
+    let (_, body) = form_request(
+        &router,
+        "/?Action=SendMessageBatch",
+        &[
+            ("QueueUrl", queue_url.as_str()),
+            ("SendMessageBatchRequestEntry.1.Id", "with"),
+            ("SendMessageBatchRequestEntry.1.MessageBody", "one"),
+            (
+                "SendMessageBatchRequestEntry.1.MessageAttribute.1.Name",
+                "author",
+            ),
+            (
+                "SendMessageBatchRequestEntry.1.MessageAttribute.1.Value.StringValue",
+                "x",
+            ),
+            (
+                "SendMessageBatchRequestEntry.1.MessageAttribute.1.Value.DataType",
+                "String",
+            ),
+            (
+                "SendMessageBatchRequestEntry.1.MessageAttribute.2.Name",
+                "priority",
+            ),
+            (
+                "SendMessageBatchRequestEntry.1.MessageAttribute.2.Value.StringValue",
+                "5",
+            ),
+            (
+                "SendMessageBatchRequestEntry.1.MessageAttribute.2.Value.DataType",
+                "Number",
+            ),
+            ("SendMessageBatchRequestEntry.2.Id", "without"),
+            ("SendMessageBatchRequestEntry.2.MessageBody", "two"),
+        ],
+    )
+    .await;
+    assert_eq!(
+        xml_values(&body, "MD5OfMessageAttributes"),
+        vec!["314326d3d7174a181cd3e52b63b08cb7"]
+    );
+}
+
+#[tokio::test]
+async fn test_receive_attribute_selection() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("selection-queue").await.unwrap();
+    let attributes = HashMap::from([
+        (
+            "author".to_string(),
+            MessageAttributeValue {
+                string_value: Some("x".to_string()),
+                binary_value: None,
+                data_type: "String".to_string(),
+            },
+        ),
+        (
+            "priority".to_string(),
+            MessageAttributeValue {
+                string_value: Some("5".to_string()),
+                binary_value: None,
+                data_type: "Number".to_string(),
+            },
+        ),
+    ]);
+    for _ in 0..3 {
+        service
+            .send_message(
+                "selection-queue",
+                "selected",
+                Some(attributes.clone()),
+                None,
+            )
+            .await
+            .unwrap();
+    }
+    let router = test_router(service);
+    let queue_url = format!("{}/selection-queue", BASE_URL);
+    let receive = |selectors: Vec<(&'static str, &'static str)>| {
+        let router = router.clone();
+        let queue_url = queue_url.clone();
+        async move {
+            let mut params = vec![("QueueUrl", queue_url.as_str())];
+            params.extend(selectors);
+            let (status, body) = form_request(&router, "/?Action=ReceiveMessage", &params).await;
+            assert_eq!(status, StatusCode::OK, "{}", body);
+            body
+        }
+    };
+
+    // Nothing requested: no system or message attributes
+    let body = receive(vec![]).await;
+    assert_eq!(xml_values(&body, "Body"), vec!["selected"]);
+    assert!(xml_values(&body, "SentTimestamp").is_empty());
+    assert!(xml_values(&body, "ApproximateReceiveCount").is_empty());
+    assert!(xml_values(&body, "author").is_empty());
+    assert!(xml_values(&body, "priority").is_empty());
+
+    // A specific subset
+    let body = receive(vec![
+        ("AttributeName.1", "ApproximateReceiveCount"),
+        ("MessageAttributeName.1", "priority"),
+    ])
+    .await;
+    assert_eq!(xml_values(&body, "ApproximateReceiveCount").len(), 1);
+    assert!(xml_values(&body, "SentTimestamp").is_empty());
+    assert!(xml_values(&body, "author").is_empty());
+    assert_eq!(xml_values(&body, "priority").len(), 1);
+
+    // All
+    let body = receive(vec![
+        ("AttributeName.1", "All"),
+        ("MessageAttributeName.1", "All"),
+    ])
+    .await;
+    assert_eq!(xml_values(&body, "SentTimestamp").len(), 1);
+    assert_eq!(xml_values(&body, "SenderId").len(), 1);
+    assert_eq!(xml_values(&body, "author").len(), 1);
+    assert_eq!(xml_values(&body, "priority").len(), 1);
+}
+
+#[tokio::test]
+async fn test_export_queue_streams_ndjson_and_csv() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("export-queue").await.unwrap();
+    for body in ["plain", "with, comma", "with \"quotes\""] {
+        service
+            .send_message("export-queue", body, None, None)
+            .await
+            .unwrap();
+    }
+    let router = test_router(service);
+
+    let (status, body) = get_request(&router, "/api/queue/export-queue/export").await;
+    assert_eq!(status, StatusCode::OK);
+    let lines: Vec<serde_json::Value> = body
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines.iter().all(|line| line["status"] == "active"));
+    assert!(lines.iter().any(|line| line["body"] == "with \"quotes\""));
+
+    let (status, body) = get_request(&router, "/api/queue/export-queue/export?format=csv").await;
+    assert_eq!(status, StatusCode::OK);
+    let mut lines = body.lines();
+    assert!(lines.next().unwrap().starts_with("id,created_at,status"));
+    let rows: Vec<&str> = lines.collect();
+    assert_eq!(rows.len(), 3);
+    assert!(rows.iter().any(|row| row.ends_with(",\"with, comma\"")));
+    assert!(
+        rows.iter()
+            .any(|row| row.ends_with(",\"with \"\"quotes\"\"\""))
+    );
+
+    let (status, _) = get_request(&router, "/api/queue/missing-queue/export").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_oversized_request_body_is_rejected_with_request_too_large() {
+    let mut config = Config::default();
+    config.server.max_request_body_bytes = 4096;
+    let (_temp_dir, service) = test_service_with_config(&config).await;
+    service.create_queue("limited-queue").await.unwrap();
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/limited-queue", BASE_URL);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[("QueueUrl", &queue_url), ("MessageBody", &"x".repeat(8192))],
+    )
+    .await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(xml_values(&body, "Code"), vec!["RequestTooLarge"]);
+
+    // A declared Content-Length over the limit is refused before the body is read
+    let request = axum::http::Request::builder()
+        .method("POST")
+        .uri("/?Action=SendMessage")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .header("content-length", "1000000")
+        .body(axum::body::Body::from("x".repeat(1_000_000)))
+        .unwrap();
+    let (status, body) = send(&router, request).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(xml_values(&body, "Code"), vec!["RequestTooLarge"]);
+
+    // Requests under the limit are unaffected
+    let (status, _) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[("QueueUrl", &queue_url), ("MessageBody", "small")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let attributes = service
+        .get_queue_attributes("limited-queue")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(attributes.approximate_number_of_messages, 1);
+}
+
+#[tokio::test]
+async fn test_batch_over_entry_limit_is_rejected_whole() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("limit-queue").await.unwrap();
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/limit-queue", BASE_URL);
+
+    let mut params = vec![("QueueUrl".to_string(), queue_url.clone())];
+    for i in 1..=11 {
+        params.push((
+            format!("SendMessageBatchRequestEntry.{}.Id", i),
+            format!("entry-{}", i),
+        ));
+        params.push((
+            format!("SendMessageBatchRequestEntry.{}.MessageBody", i),
+            format!("body {}", i),
+        ));
+    }
+    let params: Vec<(&str, &str)> = params
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    let (status, body) = form_request(&router, "/?Action=SendMessageBatch", &params).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        xml_values(&body, "Code"),
+        vec!["TooManyEntriesInBatchRequest"]
+    );
+    let attributes = service
+        .get_queue_attributes("limit-queue")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(attributes.approximate_number_of_messages, 0);
+
+    let mut params = vec![("QueueUrl".to_string(), queue_url.clone())];
+    for i in 1..=11 {
+        params.push((
+            format!("DeleteMessageBatchRequestEntry.{}.Id", i),
+            format!("entry-{}", i),
+        ));
+        params.push((
+            format!("DeleteMessageBatchRequestEntry.{}.ReceiptHandle", i),
+            format!("handle-{}", i),
+        ));
+    }
+    let params: Vec<(&str, &str)> = params
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    let (status, body) = form_request(&router, "/?Action=DeleteMessageBatch", &params).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        xml_values(&body, "Code"),
+        vec!["TooManyEntriesInBatchRequest"]
+    );
+}
+
+#[tokio::test]
+async fn test_batch_entry_limit_is_configurable() {
+    let mut config = Config::default();
+    config.queues.max_batch_entries = 2;
+    let (_temp_dir, service) = test_service_with_config(&config).await;
+    service.create_queue("small-batches").await.unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/small-batches", BASE_URL);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=SendMessageBatch",
+        &[
+            ("QueueUrl", &queue_url),
+            ("SendMessageBatchRequestEntry.1.Id", "a"),
+            ("SendMessageBatchRequestEntry.1.MessageBody", "one"),
+            ("SendMessageBatchRequestEntry.2.Id", "b"),
+            ("SendMessageBatchRequestEntry.2.MessageBody", "two"),
+            ("SendMessageBatchRequestEntry.3.Id", "c"),
+            ("SendMessageBatchRequestEntry.3.MessageBody", "three"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("Maximum number of entries per request are 2. You have sent 3."));
+}
+
+#[tokio::test]
+async fn test_delete_queue_refuses_dlq_in_use_unless_forced() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("guarded-dlq").await.unwrap();
+    dead_letter_one_each(&service, &["guarded-source"], "guarded-dlq").await;
+    let router = test_router(service.clone());
+    let dlq_url = format!("{}/guarded-dlq", BASE_URL);
+
+    let (status, body) =
+        form_request(&router, "/?Action=DeleteQueue", &[("QueueUrl", &dlq_url)]).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(xml_values(&body, "Code"), vec!["ResourceInUse"]);
+    assert!(body.contains("guarded-source"));
+    assert_eq!(
+        service.get_dlq_messages("guarded-dlq").await.unwrap().len(),
+        1
+    );
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=DeleteQueue",
+        &[("QueueUrl", &dlq_url), ("Force", "true")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(
+        service
+            .get_dlq_messages("guarded-dlq")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(
+        service
+            .list_dlq_source_queues("guarded-dlq")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    let config = service
+        .get_queue_config("guarded-source")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(config.dead_letter_target_arn, None);
+
+    // Once its only source queue is deleted, the DLQ can be deleted without Force
+    service.create_queue("released-dlq").await.unwrap();
+    dead_letter_one_each(&service, &["released-source"], "released-dlq").await;
+    let (status, body) = form_request(
+        &router,
+        "/?Action=DeleteQueue",
+        &[("QueueUrl", &format!("{}/released-source", BASE_URL))],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, body) = form_request(
+        &router,
+        "/?Action=DeleteQueue",
+        &[("QueueUrl", &format!("{}/released-dlq", BASE_URL))],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
+
+#[tokio::test]
+async fn test_queue_delay_seconds_applies_when_message_has_none() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("delayed-queue").await.unwrap();
+    let router = test_router(service.clone());
+    let queue_url = format!("{}/delayed-queue", BASE_URL);
+
+    let (status, _) = form_request(
+        &router,
+        "/?Action=SetQueueAttributes",
+        &[
+            ("QueueUrl", &queue_url),
+            ("Attribute.1.Name", "DelaySeconds"),
+            ("Attribute.1.Value", "10"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[("QueueUrl", &queue_url), ("MessageBody", "queue default")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = form_request(
+        &router,
+        "/?Action=ReceiveMessage",
+        &[("QueueUrl", &queue_url)],
+    )
+    .await;
+    assert!(xml_values(&body, "Body").is_empty());
+    let attributes = service
+        .get_queue_attributes("delayed-queue")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(attributes.approximate_number_of_messages_delayed, 1);
+
+    // An explicit DelaySeconds=0 overrides the queue default
+    let (status, _) = form_request(
+        &router,
+        "/?Action=SendMessage",
+        &[
+            ("QueueUrl", &queue_url),
+            ("MessageBody", "no delay"),
+            ("DelaySeconds", "0"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = form_request(
+        &router,
+        "/?Action=ReceiveMessage",
+        &[("QueueUrl", &queue_url)],
+    )
+    .await;
+    assert_eq!(xml_values(&body, "Body"), vec!["no delay"]);
+}
+
+#[tokio::test]
+async fn test_deduplication_window_is_configurable_per_queue() {
+    let (_temp_dir, service) = test_service().await;
+    service.create_queue("short-dedup").await.unwrap();
+    let router = test_router(service);
+    let queue_url = format!("{}/short-dedup", BASE_URL);
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=SetQueueAttributes",
+        &[
+            ("QueueUrl", &queue_url),
+            ("Attribute.1.Name", "DeduplicationWindowSeconds"),
+            ("Attribute.1.Value", "1"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let send_params = [
+        ("QueueUrl", queue_url.as_str()),
+        ("MessageBody", "charge card"),
+        ("MessageDeduplicationId", "order-7"),
+    ];
+    let (_, body) = form_request(&router, "/?Action=SendMessage", &send_params).await;
+    let first_id = xml_values(&body, "MessageId");
+    let (_, body) = form_request(&router, "/?Action=SendMessage", &send_params).await;
+    assert_eq!(xml_values(&body, "MessageId"), first_id);
+
+    // Outside the 1 second window the same deduplication id is accepted again
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    let (_, body) = form_request(&router, "/?Action=SendMessage", &send_params).await;
+    let resent_id = xml_values(&body, "MessageId");
+    assert_eq!(resent_id.len(), 1);
+    assert_ne!(resent_id, first_id);
+}
+
+#[tokio::test]
+async fn test_create_queue_rejects_existing_queue_with_different_attributes() {
+    let (_temp_dir, service) = test_service().await;
+    let router = test_router(service.clone());
+    let create = |timeout: &'static str| {
+        [
+            ("QueueName", "settled"),
+            ("Attribute.1.Name", "VisibilityTimeout"),
+            ("Attribute.1.Value", timeout),
+        ]
+    };
+
+    let (status, body) = form_request(&router, "/?Action=CreateQueue", &create("60")).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let config = service.get_queue_config("settled").await.unwrap().unwrap();
+    assert_eq!(config.visibility_timeout_seconds, 60);
+
+    // Recreating with the same attributes, or none, is idempotent
+    let (status, body) = form_request(&router, "/?Action=CreateQueue", &create("60")).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        xml_values(&body, "QueueUrl"),
+        vec![format!("{}/settled", BASE_URL)]
+    );
+    let (status, body) =
+        form_request(&router, "/?Action=CreateQueue", &[("QueueName", "settled")]).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (status, body) = form_request(&router, "/?Action=CreateQueue", &create("30")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("<Code>QueueAlreadyExists</Code>"), "{}", body);
+    assert!(body.contains("VisibilityTimeout"), "{}", body);
+    let config = service.get_queue_config("settled").await.unwrap().unwrap();
+    assert_eq!(config.visibility_timeout_seconds, 60);
+}
+
+#[tokio::test]
+async fn test_create_queue_after_delete_recreates_it() {
+    let (_temp_dir, service) = test_service().await;
+    let router = test_router(service.clone());
+    let delete = |name: &str| {
+        let router = router.clone();
+        let queue_url = format!("{}/{}", BASE_URL, name);
+        async move { form_request(&router, "/?Action=DeleteQueue", &[("QueueUrl", &queue_url)]).await }
+    };
+    let listed = || async {
+        let (_, body) = form_request(&router, "/?Action=ListQueues", &[]).await;
+        xml_values(&body, "QueueUrl")
+    };
+
+    // Identical attributes
+    let fifo = [
+        ("QueueName", "recreated.fifo"),
+        ("Attribute.1.Name", "FifoQueue"),
+        ("Attribute.1.Value", "true"),
+    ];
+    let (status, body) = form_request(&router, "/?Action=CreateQueue", &fifo).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, _) = delete("recreated.fifo").await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = form_request(&router, "/?Action=CreateQueue", &fifo).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(listed().await, vec![format!("{}/recreated.fifo", BASE_URL)]);
+
+    // Different attributes
+    let create = |timeout: &'static str| {
+        [
+            ("QueueName", "reconfigured"),
+            ("Attribute.1.Name", "VisibilityTimeout"),
+            ("Attribute.1.Value", timeout),
+        ]
+    };
+    let (status, body) = form_request(&router, "/?Action=CreateQueue", &create("60")).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, _) = delete("reconfigured").await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = form_request(&router, "/?Action=CreateQueue", &create("45")).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(
+        listed()
+            .await
+            .contains(&format!("{}/reconfigured", BASE_URL))
+    );
+    let config = service
+        .get_queue_config("reconfigured")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(config.visibility_timeout_seconds, 45);
+}
+
+#[tokio::test]
+async fn test_both_receive_actions_dead_letter_past_max_receive_count() {
+    for action in ["ReceiveMessage", "ReceiveMessageBatch"] {
+        let (_temp_dir, service) = test_service().await;
+        for name in ["poison-source", "poison-dlq"] {
+            service.create_queue(name).await.unwrap();
+        }
+        let router = test_router(service.clone());
+        let queue_url = format!("{}/poison-source", BASE_URL);
+
+        let (status, body) = form_request(
+            &router,
+            "/?Action=SetQueueAttributes",
+            &[
+                ("QueueUrl", &queue_url),
+                ("Attribute.1.Name", "RedrivePolicy"),
+                (
+                    "Attribute.1.Value",
+                    r#"{"deadLetterTargetArn":"poison-dlq","maxReceiveCount":1}"#,
+                ),
+                ("Attribute.2.Name", "VisibilityTimeout"),
+                ("Attribute.2.Value", "1"),
+            ],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        let (status, _) = form_request(
+            &router,
+            "/?Action=SendMessage",
+            &[("QueueUrl", &queue_url), ("MessageBody", "poison")],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let receive_path = format!("/?Action={}", action);
+        let receive_params = [
+            ("QueueUrl", queue_url.as_str()),
+            ("MaxNumberOfMessages", "10"),
+        ];
+        let (_, body) = form_request(&router, &receive_path, &receive_params).await;
+        assert_eq!(xml_values(&body, "Body"), vec!["poison"], "{}", action);
+
+        // The redelivery would be the second receive, past maxReceiveCount
+        tokio::time::sleep(std::time::Duration::from_millis(1200)).await;
+        let (_, body) = form_request(&router, &receive_path, &receive_params).await;
+        assert!(xml_values(&body, "Body").is_empty(), "{}: {}", action, body);
+
+        let dead_lettered = service.get_dlq_messages("poison-dlq").await.unwrap();
+        assert_eq!(dead_lettered.len(), 1, "{}", action);
+        assert_eq!(dead_lettered[0].1, "poison");
+    }
+}
+
+#[tokio::test]
+async fn test_create_queue_applies_fifo_attributes() {
+    let (_temp_dir, service) = test_service().await;
+    let router = test_router(service.clone());
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=CreateQueue",
+        &[
+            ("QueueName", "payments.fifo"),
+            ("Attribute.1.Name", "FifoQueue"),
+            ("Attribute.1.Value", "true"),
+            ("Attribute.2.Name", "ContentBasedDeduplication"),
+            ("Attribute.2.Value", "true"),
+            ("Attribute.3.Name", "VisibilityTimeout"),
+            ("Attribute.3.Value", "45"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let config = service
+        .get_queue_config("payments.fifo")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(config.is_fifo);
+    assert!(config.content_based_deduplication);
+    assert_eq!(config.visibility_timeout_seconds, 45);
+
+    // Content-based deduplication: the same body is only enqueued once
+    let queue_url = format!("{}/payments.fifo", BASE_URL);
+    let send_params = [
+        ("QueueUrl", queue_url.as_str()),
+        ("MessageBody", "charge 42"),
+        ("MessageGroupId", "customer-1"),
+    ];
+    let (_, first) = form_request(&router, "/?Action=SendMessage", &send_params).await;
+    let (_, second) = form_request(&router, "/?Action=SendMessage", &send_params).await;
+    assert_eq!(
+        xml_values(&first, "MessageId"),
+        xml_values(&second, "MessageId")
+    );
+
+    let (status, body) = form_request(
+        &router,
+        "/?Action=CreateQueue",
+        &[
+            ("QueueName", "payments"),
+            ("Attribute.1.Name", "FifoQueue"),
+            ("Attribute.1.Value", "true"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        body.contains("<Code>InvalidParameterValue</Code>"),
+        "{}",
+        body
+    );
+    assert!(body.contains(".fifo"), "{}", body);
+    assert!(
+        service
+            .get_queue_config("payments")
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
@@ -0,0 +1,774 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+use qlite::config::{Config, MessageIdFormat, QueueConfig, RoutingRule};
+use qlite::message::MessageAttributeValue;
+use qlite::queue_service::{QueueService, QueueStats};
+
+async fn create_service() -> (TempDir, Arc<QueueService>) {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("service.db");
+    let service = QueueService::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create queue service");
+    (temp_dir, Arc::new(service))
+}
+
+#[tokio::test]
+async fn test_long_poll_returns_promptly_with_partial_batch() {
+    let (_temp_dir, service) = create_service().await;
+    service
+        .create_queue("poll-queue")
+        .await
+        .expect("Failed to create queue");
+
+    let poller = Arc::clone(&service);
+    let poll = tokio::spawn(async move {
+        let started = Instant::now();
+        let messages = poller
+            .receive_messages_enhanced("poll-queue", 10, 10)
+            .await
+            .expect("Failed to long poll");
+        (messages, started.elapsed())
+    });
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    service
+        .send_message("poll-queue", "Only one", None, None)
+        .await
+        .expect("Failed to send message");
+
+    let (messages, elapsed) = poll.await.unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].body, "Only one");
+    assert!(
+        elapsed < Duration::from_secs(2),
+        "Long poll should return as soon as a message arrives, took {:?}",
+        elapsed
+    );
+}
+
+#[tokio::test]
+async fn test_long_poll_waits_for_minimum_messages() {
+    let (_temp_dir, service) = create_service().await;
+    service
+        .create_queue("batch-queue")
+        .await
+        .expect("Failed to create queue");
+
+    let poller = Arc::clone(&service);
+    let poll = tokio::spawn(async move {
+        poller
+            .receive_messages_with_minimum("batch-queue", 10, 10, 3)
+            .await
+            .expect("Failed to long poll")
+    });
+
+    for i in 1..=3 {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(
+            !poll.is_finished(),
+            "Poll returned before 3 messages arrived"
+        );
+        service
+            .send_message("batch-queue", &format!("Message {}", i), None, None)
+            .await
+            .expect("Failed to send message");
+    }
+
+    let messages = tokio::time::timeout(Duration::from_secs(2), poll)
+        .await
+        .expect("Poll should return once the minimum is reached")
+        .unwrap();
+    assert_eq!(messages.len(), 3);
+}
+
+#[tokio::test]
+async fn test_long_poll_minimum_times_out_with_partial_batch() {
+    let (_temp_dir, service) = create_service().await;
+    service
+        .create_queue("sparse-queue")
+        .await
+        .expect("Failed to create queue");
+    for i in 1..=2 {
+        service
+            .send_message("sparse-queue", &format!("Message {}", i), None, None)
+            .await
+            .expect("Failed to send message");
+    }
+
+    let started = Instant::now();
+    let messages = service
+        .receive_messages_with_minimum("sparse-queue", 10, 1, 3)
+        .await
+        .expect("Failed to long poll");
+
+    assert_eq!(messages.len(), 2);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn test_uuid_v7_message_ids_sort_by_send_time() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("ids.db");
+    let mut config = Config::default();
+    config.queues.message_id_format = MessageIdFormat::UuidV7;
+    let service = QueueService::new_with_config(db_path.to_str().unwrap(), &config)
+        .await
+        .expect("Failed to create queue service");
+    service
+        .create_queue("ordered-queue")
+        .await
+        .expect("Failed to create queue");
+
+    let mut sent = Vec::new();
+    for i in 0..5 {
+        let id = service
+            .send_message("ordered-queue", &format!("msg-{}", i), None, None)
+            .await
+            .expect("Failed to send message");
+        assert_eq!(uuid::Uuid::parse_str(&id).unwrap().get_version_num(), 7);
+        sent.push(id);
+        tokio::time::sleep(Duration::from_millis(2)).await;
+    }
+
+    let mut sorted = sent.clone();
+    sorted.sort();
+    assert_eq!(sorted, sent);
+}
+
+#[tokio::test]
+async fn test_prefixed_message_ids_use_queue_name() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("prefixed.db");
+    let mut config = Config::default();
+    config.queues.message_id_format = MessageIdFormat::Prefixed;
+    let service = QueueService::new_with_config(db_path.to_str().unwrap(), &config)
+        .await
+        .expect("Failed to create queue service");
+    service
+        .create_queue("orders.fifo")
+        .await
+        .expect("Failed to create queue");
+
+    let id = service
+        .send_message_enhanced_with_group(
+            "orders.fifo",
+            "Prefixed",
+            None,
+            Some("dedup-1".to_string()),
+            Some(0),
+            Some("group-1".to_string()),
+        )
+        .await
+        .expect("Failed to send message");
+    let uuid = id
+        .strip_prefix("orders-")
+        .expect("ID should carry the queue prefix");
+    assert!(uuid::Uuid::parse_str(uuid).is_ok());
+}
+
+#[tokio::test]
+async fn test_visibility_jitter_stays_within_bounds() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("jitter.db");
+    let mut config = Config::default();
+    config.chaos.visibility_jitter = 10;
+    let service = QueueService::new_with_config(db_path.to_str().unwrap(), &config)
+        .await
+        .expect("Failed to create queue service");
+    service
+        .create_queue("chaos-queue")
+        .await
+        .expect("Failed to create queue");
+
+    for i in 0..30 {
+        service
+            .send_message("chaos-queue", &format!("msg-{}", i), None, None)
+            .await
+            .expect("Failed to send message");
+    }
+    let before = chrono::Utc::now();
+    for _ in 0..20 {
+        service
+            .receive_message("chaos-queue")
+            .await
+            .expect("Failed to receive message")
+            .expect("Message should be available");
+    }
+    service
+        .receive_messages_batch("chaos-queue", 10)
+        .await
+        .expect("Failed to receive batch");
+    let after = chrono::Utc::now();
+
+    let conn = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+    let timeouts: Vec<String> = conn
+        .prepare("SELECT visibility_timeout FROM messages WHERE status = 'processing'")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(timeouts.len(), 30);
+
+    // The default 30 second timeout, moved by at most 10 seconds either way
+    let earliest = before + chrono::Duration::seconds(20);
+    let latest = after + chrono::Duration::seconds(40);
+    let mut offsets = std::collections::HashSet::new();
+    for timeout in &timeouts {
+        let timeout = chrono::DateTime::parse_from_rfc3339(timeout).unwrap();
+        assert!(timeout >= earliest && timeout <= latest, "{}", timeout);
+        offsets.insert((timeout.with_timezone(&chrono::Utc) - before).num_seconds());
+    }
+    assert!(offsets.len() > 1, "timeouts should be perturbed");
+}
+
+#[tokio::test]
+async fn test_time_to_first_receive_is_observed_once() {
+    let (_temp_dir, service) = create_service().await;
+    service.create_queue("lag-queue").await.unwrap();
+    service
+        .send_message("lag-queue", "waiting", None, None)
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    let message = service.receive_message("lag-queue").await.unwrap().unwrap();
+
+    let rendered = service.metrics().render();
+    let sample = |name: &str| -> f64 {
+        rendered
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or_else(|| panic!("{} missing from:\n{}", name, rendered))
+    };
+    let waited = sample("qlite_time_to_first_receive_seconds_sum");
+    assert!((0.6..5.0).contains(&waited), "waited {}", waited);
+    assert_eq!(sample("qlite_time_to_first_receive_seconds_count"), 1.0);
+    assert_eq!(
+        sample("qlite_time_to_first_receive_seconds_bucket{le=\"0.5\"}"),
+        0.0
+    );
+    assert_eq!(
+        sample("qlite_time_to_first_receive_seconds_bucket{le=\"1\"}"),
+        1.0
+    );
+
+    // Redeliveries aren't first receives
+    service.nack_message(&message.id).await.unwrap();
+    let redelivered = service
+        .receive_messages_batch("lag-queue", 10)
+        .await
+        .unwrap();
+    assert_eq!(redelivered.len(), 1);
+    assert!(
+        service
+            .metrics()
+            .render()
+            .contains("qlite_time_to_first_receive_seconds_count 1\n")
+    );
+}
+
+#[tokio::test]
+async fn test_queue_stats_counts_each_message_state() {
+    let (_temp_dir, service) = create_service().await;
+    let config = QueueConfig {
+        name: "stats-queue".to_string(),
+        dead_letter_target_arn: Some("stats-dlq".to_string()),
+        ..Default::default()
+    };
+    service.create_queue_with_config(&config).await.unwrap();
+    service.create_queue("stats-dlq").await.unwrap();
+
+    for body in ["in flight", "visible", "dead"] {
+        service
+            .send_message("stats-queue", body, None, None)
+            .await
+            .unwrap();
+    }
+    service
+        .send_message_enhanced_with_group("stats-queue", "delayed", None, None, Some(600), None)
+        .await
+        .unwrap();
+
+    service
+        .receive_message("stats-queue")
+        .await
+        .unwrap()
+        .unwrap();
+    let dead = service
+        .receive_message("stats-queue")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(
+        service
+            .move_message_to_dlq(&dead.id, "test failure")
+            .await
+            .unwrap()
+    );
+
+    let stats = service.queue_stats(Some("stats-queue")).await.unwrap();
+    assert_eq!(
+        stats,
+        vec![QueueStats {
+            queue: "stats-queue".to_string(),
+            visible: 1,
+            in_flight: 1,
+            delayed: 1,
+            dead_lettered: 1,
+        }]
+    );
+
+    let all = service.queue_stats(None).await.unwrap();
+    assert_eq!(all.len(), 2);
+    assert!(
+        service
+            .queue_stats(Some("missing"))
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_routing_rule_copies_matching_messages() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("routing.db");
+    let mut config = Config::default();
+    config.routing.rules.push(RoutingRule {
+        source_queue: "events".to_string(),
+        attribute: "type".to_string(),
+        value: "order".to_string(),
+        target_queue: "orders".to_string(),
+    });
+    let service = QueueService::new_with_config(db_path.to_str().unwrap(), &config)
+        .await
+        .expect("Failed to create queue service");
+    service.create_queue("events").await.unwrap();
+    service.create_queue("orders").await.unwrap();
+
+    let typed = |value: &str| {
+        let mut attributes = HashMap::new();
+        attributes.insert(
+            "type".to_string(),
+            MessageAttributeValue {
+                string_value: Some(value.to_string()),
+                binary_value: None,
+                data_type: "String".to_string(),
+            },
+        );
+        Some(attributes)
+    };
+    service
+        .send_message_enhanced_with_group("events", "order #1", typed("order"), None, Some(0), None)
+        .await
+        .unwrap();
+    service
+        .send_message_enhanced_with_group(
+            "events",
+            "refund #1",
+            typed("refund"),
+            None,
+            Some(0),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let routed = service.receive_message("orders").await.unwrap().unwrap();
+    assert_eq!(routed.body, "order #1");
+    assert_eq!(
+        routed.attributes.unwrap()["type"].string_value.as_deref(),
+        Some("order")
+    );
+    assert!(service.receive_message("orders").await.unwrap().is_none());
+
+    // The source queue keeps both originals
+    let stats = service.queue_stats(Some("events")).await.unwrap();
+    assert_eq!(stats[0].visible, 2);
+}
+
+#[tokio::test]
+async fn test_receive_honors_queue_visibility_timeout() {
+    let (_temp_dir, service) = create_service().await;
+    service.create_queue("short-visibility").await.unwrap();
+    service
+        .set_queue_attributes(
+            "short-visibility",
+            HashMap::from([("VisibilityTimeout".to_string(), "5".to_string())]),
+        )
+        .await
+        .unwrap();
+    service
+        .send_message("short-visibility", "comes back", None, None)
+        .await
+        .unwrap();
+
+    let first = service
+        .receive_message("short-visibility")
+        .await
+        .unwrap()
+        .expect("message should be visible");
+
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    assert!(
+        service
+            .receive_message("short-visibility")
+            .await
+            .unwrap()
+            .is_none(),
+        "message reappeared before its 5 second visibility timeout"
+    );
+
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    let second = service
+        .receive_message("short-visibility")
+        .await
+        .unwrap()
+        .expect("message should be visible again after 5 seconds");
+    assert_eq!(second.id, first.id);
+    assert_ne!(second.receipt_handle, first.receipt_handle);
+}
+
+#[tokio::test]
+async fn test_set_queue_attributes_keeps_unlisted_attributes() {
+    let (_temp_dir, service) = create_service().await;
+    service.create_queue("orders.fifo").await.unwrap();
+    service
+        .set_queue_attributes(
+            "orders.fifo",
+            HashMap::from([("DeduplicationWindowSeconds".to_string(), "60".to_string())]),
+        )
+        .await
+        .unwrap();
+    service
+        .set_queue_attributes(
+            "orders.fifo",
+            HashMap::from([("VisibilityTimeout".to_string(), "45".to_string())]),
+        )
+        .await
+        .unwrap();
+
+    let config = service
+        .get_queue_config("orders.fifo")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(config.is_fifo);
+    assert!(config.content_based_deduplication);
+    assert_eq!(config.deduplication_window_seconds, 60);
+    assert_eq!(config.visibility_timeout_seconds, 45);
+    assert!(config.count_receives);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_producers_and_consumers_deliver_each_message_once() {
+    const PRODUCERS: usize = 4;
+    const MESSAGES_PER_PRODUCER: usize = 50;
+    const CONSUMERS: usize = 4;
+    const TOTAL: usize = PRODUCERS * MESSAGES_PER_PRODUCER;
+
+    let (_temp_dir, service) = create_service().await;
+    service.create_queue("contended").await.unwrap();
+    let received = Arc::new(tokio::sync::Mutex::new(HashSet::new()));
+
+    // Consumers start first so long polls are woken by sends as well as finding backlog.
+    // Half use the long-polling receive, half the batch receive.
+    let consumers: Vec<_> = (0..CONSUMERS)
+        .map(|consumer| {
+            let service = Arc::clone(&service);
+            let received = Arc::clone(&received);
+            tokio::spawn(async move {
+                let deadline = Instant::now() + Duration::from_secs(20);
+                while received.lock().await.len() < TOTAL && Instant::now() < deadline {
+                    let messages = if consumer % 2 == 0 {
+                        service.receive_messages_enhanced("contended", 10, 1).await
+                    } else {
+                        service.receive_messages_batch("contended", 10).await
+                    }
+                    .unwrap();
+
+                    for message in messages {
+                        assert!(
+                            received.lock().await.insert(message.id.clone()),
+                            "message {} was delivered twice",
+                            message.id
+                        );
+                        assert!(
+                            service
+                                .delete_message(&message.receipt_handle)
+                                .await
+                                .unwrap()
+                        );
+                    }
+                }
+            })
+        })
+        .collect();
+
+    let producers: Vec<_> = (0..PRODUCERS)
+        .map(|producer| {
+            let service = Arc::clone(&service);
+            tokio::spawn(async move {
+                let mut ids = Vec::with_capacity(MESSAGES_PER_PRODUCER);
+                for i in 0..MESSAGES_PER_PRODUCER {
+                    let body = format!("producer {} message {}", producer, i);
+                    ids.push(
+                        service
+                            .send_message("contended", &body, None, None)
+                            .await
+                            .unwrap(),
+                    );
+                }
+                ids
+            })
+        })
+        .collect();
+
+    let mut sent = HashSet::new();
+    for producer in producers {
+        sent.extend(producer.await.unwrap());
+    }
+    for consumer in consumers {
+        consumer.await.unwrap();
+    }
+
+    assert_eq!(sent.len(), TOTAL);
+    assert_eq!(*received.lock().await, sent);
+    let attributes = service
+        .get_queue_attributes("contended")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(attributes.approximate_number_of_messages, 0);
+    assert_eq!(attributes.approximate_number_of_messages_not_visible, 0);
+}
+
+#[tokio::test]
+async fn test_standard_queue_without_strict_order_delivers_out_of_send_order() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("unordered.db");
+    let mut config = Config::default();
+    config.queues.standard_queue_strict_order = false;
+    let service = QueueService::new_with_config(db_path.to_str().unwrap(), &config)
+        .await
+        .expect("Failed to create queue service");
+    service
+        .create_queue("unordered-queue")
+        .await
+        .expect("Failed to create queue");
+
+    let sent: Vec<String> = (0..20).map(|i| format!("msg-{}", i)).collect();
+    for body in &sent {
+        service
+            .send_message("unordered-queue", body, None, None)
+            .await
+            .expect("Failed to send message");
+    }
+
+    let mut received = Vec::new();
+    while let Some(message) = service
+        .receive_message("unordered-queue")
+        .await
+        .expect("Failed to receive message")
+    {
+        received.push(message.body);
+    }
+
+    // Every message arrives exactly once, just not in send order (1 in 20! odds of a false failure)
+    assert_ne!(received, sent);
+    let mut sorted_received = received.clone();
+    sorted_received.sort();
+    let mut sorted_sent = sent.clone();
+    sorted_sent.sort();
+    assert_eq!(sorted_received, sorted_sent);
+}
+
+#[tokio::test]
+async fn test_receive_moves_message_past_max_receive_count_to_dlq() {
+    let (_temp_dir, service) = create_service().await;
+    for name in ["redrive-source", "redrive-dlq"] {
+        service
+            .create_queue(name)
+            .await
+            .expect("Failed to create queue");
+    }
+    service
+        .set_queue_attributes(
+            "redrive-source",
+            HashMap::from([(
+                "RedrivePolicy".to_string(),
+                r#"{"deadLetterTargetArn":"redrive-dlq","maxReceiveCount":2}"#.to_string(),
+            )]),
+        )
+        .await
+        .expect("Failed to set redrive policy");
+
+    let poison_id = service
+        .send_message("redrive-source", "poison", None, None)
+        .await
+        .expect("Failed to send message");
+
+    // maxReceiveCount deliveries, each followed by the visibility timeout running out
+    for _ in 0..2 {
+        let message = service
+            .receive_message("redrive-source")
+            .await
+            .expect("Failed to receive message")
+            .expect("Message should still be delivered");
+        assert_eq!(message.id, poison_id);
+        service
+            .change_message_visibility("redrive-source", &message.receipt_handle, 0)
+            .await
+            .expect("Failed to expire visibility");
+    }
+
+    // A later message is still handed out by the receive that dead-letters the first
+    service
+        .send_message("redrive-source", "healthy", None, None)
+        .await
+        .expect("Failed to send message");
+    let message = service
+        .receive_message("redrive-source")
+        .await
+        .expect("Failed to receive message")
+        .expect("The next message should be delivered");
+    assert_eq!(message.body, "healthy");
+
+    let dead_lettered = service
+        .get_dlq_messages("redrive-dlq")
+        .await
+        .expect("Failed to list DLQ messages");
+    assert_eq!(dead_lettered.len(), 1);
+    assert_eq!(dead_lettered[0].0, poison_id);
+    assert_eq!(dead_lettered[0].1, "poison");
+    assert!(
+        service
+            .receive_message("redrive-source")
+            .await
+            .expect("Failed to receive message")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_batch_receive_moves_message_past_max_receive_count_to_dlq() {
+    let (_temp_dir, service) = create_service().await;
+    for name in ["batch-source", "batch-dlq"] {
+        service
+            .create_queue(name)
+            .await
+            .expect("Failed to create queue");
+    }
+    service
+        .set_queue_attributes(
+            "batch-source",
+            HashMap::from([(
+                "RedrivePolicy".to_string(),
+                r#"{"deadLetterTargetArn":"batch-dlq","maxReceiveCount":1}"#.to_string(),
+            )]),
+        )
+        .await
+        .expect("Failed to set redrive policy");
+
+    let poison_id = service
+        .send_message("batch-source", "poison", None, None)
+        .await
+        .expect("Failed to send message");
+    let messages = service
+        .receive_messages_batch("batch-source", 10)
+        .await
+        .expect("Failed to receive batch");
+    assert_eq!(messages.len(), 1);
+    service
+        .change_message_visibility("batch-source", &messages[0].receipt_handle, 0)
+        .await
+        .expect("Failed to expire visibility");
+
+    // The second delivery would pass maxReceiveCount, so the batch skips to later messages
+    service
+        .send_message("batch-source", "healthy", None, None)
+        .await
+        .expect("Failed to send message");
+    let messages = service
+        .receive_messages_batch("batch-source", 10)
+        .await
+        .expect("Failed to receive batch");
+    let bodies: Vec<&str> = messages.iter().map(|m| m.body.as_str()).collect();
+    assert_eq!(bodies, vec!["healthy"]);
+
+    let dead_lettered = service
+        .get_dlq_messages("batch-dlq")
+        .await
+        .expect("Failed to list DLQ messages");
+    assert_eq!(dead_lettered.len(), 1);
+    assert_eq!(dead_lettered[0].0, poison_id);
+}
+
+#[tokio::test]
+async fn test_batch_receive_refills_after_dead_lettering() {
+    let (_temp_dir, service) = create_service().await;
+    for name in ["refill-source", "refill-dlq"] {
+        service
+            .create_queue(name)
+            .await
+            .expect("Failed to create queue");
+    }
+    service
+        .set_queue_attributes(
+            "refill-source",
+            HashMap::from([(
+                "RedrivePolicy".to_string(),
+                r#"{"deadLetterTargetArn":"refill-dlq","maxReceiveCount":1}"#.to_string(),
+            )]),
+        )
+        .await
+        .expect("Failed to set redrive policy");
+
+    for i in 0..2 {
+        service
+            .send_message("refill-source", &format!("poison-{}", i), None, None)
+            .await
+            .expect("Failed to send message");
+    }
+    for message in service
+        .receive_messages_batch("refill-source", 2)
+        .await
+        .expect("Failed to receive batch")
+    {
+        service
+            .change_message_visibility("refill-source", &message.receipt_handle, 0)
+            .await
+            .expect("Failed to expire visibility");
+    }
+    for i in 0..2 {
+        service
+            .send_message("refill-source", &format!("healthy-{}", i), None, None)
+            .await
+            .expect("Failed to send message");
+    }
+
+    // The first page is all poison, so a full batch needs a second query
+    let mut bodies: Vec<String> = service
+        .receive_messages_batch("refill-source", 2)
+        .await
+        .expect("Failed to receive batch")
+        .into_iter()
+        .map(|m| m.body)
+        .collect();
+    bodies.sort();
+    assert_eq!(bodies, vec!["healthy-0", "healthy-1"]);
+    assert_eq!(
+        service
+            .get_dlq_messages("refill-dlq")
+            .await
+            .expect("Failed to list DLQ messages")
+            .len(),
+        2
+    );
+}
@@ -1,6 +1,6 @@
 use tempfile::TempDir;
 
-use qlite::database::Database;
+use qlite::database::{Database, SendMessageParams};
 use qlite::queue_service::QueueService;
 
 /// Simple integration test to verify the testing infrastructure works
@@ -62,13 +62,15 @@ async fn test_database_operations() {
         .expect("Failed to create queue in database");
 
     // Test sending message directly to database
-    db.send_message(
-        "db-test-queue",
-        "msg-id-1",
-        "Database test message",
-        None,
-        None,
-    )
+    db.send_message_with_delay_and_group(SendMessageParams {
+        queue_name: "db-test-queue",
+        message_id: "msg-id-1",
+        body: "Database test message",
+        attributes: None,
+        deduplication_id: None,
+        delay_until: None,
+        message_group_id: None,
+    })
     .await
     .expect("Failed to send message to database");
 
@@ -0,0 +1,210 @@
+use httpmock::prelude::*;
+use std::time::Duration;
+use tempfile::TempDir;
+
+use qlite::config::{Config, QueueConfig, WebhookEvent, WebhookSubscription};
+use qlite::queue_service::{NackOutcome, QueueService};
+
+async fn create_service(config: &Config) -> (TempDir, QueueService) {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("webhooks.db");
+    let service = QueueService::new_with_config(db_path.to_str().unwrap(), config)
+        .await
+        .expect("Failed to create queue service");
+    (temp_dir, service)
+}
+
+// Webhooks are fire-and-forget, so poll the mock until it sees the expected hits
+async fn wait_for_hits(mock: &httpmock::Mock<'_>, expected: usize) -> usize {
+    for _ in 0..50 {
+        let hits = mock.hits_async().await;
+        if hits >= expected {
+            return hits;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    mock.hits_async().await
+}
+
+#[tokio::test]
+async fn test_dlq_webhook_fires_on_dead_letter() {
+    let server = MockServer::start_async().await;
+
+    let mut config = Config::default();
+    config.alerts.dlq_webhook_url = Some(server.url("/dlq-alert"));
+    let (_temp_dir, service) = create_service(&config).await;
+
+    let queue_config = QueueConfig {
+        name: "alert-queue".to_string(),
+        max_receive_count: Some(1),
+        dead_letter_target_arn: Some("alert-dlq".to_string()),
+        ..Default::default()
+    };
+    service
+        .create_queue_with_config(&queue_config)
+        .await
+        .expect("Failed to create queue");
+    let message_id = service
+        .send_message("alert-queue", "Will fail", None, None)
+        .await
+        .expect("Failed to send message");
+
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/dlq-alert")
+                .json_body_partial(format!(
+                    r#"{{"queue":"alert-queue","dlq":"alert-dlq","message_id":"{}"}}"#,
+                    message_id
+                ));
+            then.status(200);
+        })
+        .await;
+
+    service
+        .receive_message("alert-queue")
+        .await
+        .expect("Failed to receive message");
+    let outcome = service
+        .nack_message(&message_id)
+        .await
+        .expect("Failed to nack message");
+    assert_eq!(outcome, Some(NackOutcome::DeadLettered { nack_count: 1 }));
+
+    assert_eq!(wait_for_hits(&mock, 1).await, 1);
+}
+
+fn subscription(url: String, events: &[WebhookEvent], queue: Option<&str>) -> WebhookSubscription {
+    WebhookSubscription {
+        url,
+        events: events.to_vec(),
+        queue: queue.map(str::to_string),
+    }
+}
+
+#[tokio::test]
+async fn test_message_events_fire_for_each_operation() {
+    let server = MockServer::start_async().await;
+
+    let mut config = Config::default();
+    config.webhooks.subscriptions = vec![subscription(
+        server.url("/events"),
+        &[
+            WebhookEvent::Sent,
+            WebhookEvent::Received,
+            WebhookEvent::Deleted,
+            WebhookEvent::Dlq,
+        ],
+        None,
+    )];
+    let (_temp_dir, service) = create_service(&config).await;
+
+    let queue_config = QueueConfig {
+        name: "events-queue".to_string(),
+        max_receive_count: Some(1),
+        dead_letter_target_arn: Some("events-dlq".to_string()),
+        ..Default::default()
+    };
+    service
+        .create_queue_with_config(&queue_config)
+        .await
+        .expect("Failed to create queue");
+
+    let event_mock = |event: &'static str| {
+        server.mock_async(move |when, then| {
+            when.method(POST)
+                .path("/events")
+                .json_body_partial(format!(r#"{{"event":"{}","queue":"events-queue"}}"#, event));
+            then.status(200);
+        })
+    };
+    let sent = event_mock("message_sent").await;
+    let received = event_mock("message_received").await;
+    let deleted = event_mock("message_deleted").await;
+    let dlq = event_mock("message_dlq").await;
+
+    // Sent + received + deleted
+    let first_id = service
+        .send_message("events-queue", "First", None, None)
+        .await
+        .unwrap();
+    service.receive_message("events-queue").await.unwrap();
+    assert!(service.delete_message(&first_id).await.unwrap());
+
+    // Sent + received + dead-lettered
+    let second_id = service
+        .send_message("events-queue", "Second", None, None)
+        .await
+        .unwrap();
+    service.receive_message("events-queue").await.unwrap();
+    service.nack_message(&second_id).await.unwrap();
+
+    assert_eq!(wait_for_hits(&sent, 2).await, 2);
+    assert_eq!(wait_for_hits(&received, 2).await, 2);
+    assert_eq!(wait_for_hits(&deleted, 1).await, 1);
+    assert_eq!(wait_for_hits(&dlq, 1).await, 1);
+}
+
+#[tokio::test]
+async fn test_webhook_subscriptions_filter_by_event_and_queue() {
+    let server = MockServer::start_async().await;
+
+    let mut config = Config::default();
+    config.webhooks.subscriptions = vec![subscription(
+        server.url("/orders-sent"),
+        &[WebhookEvent::Sent],
+        Some("orders"),
+    )];
+    let (_temp_dir, service) = create_service(&config).await;
+
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(POST).path("/orders-sent");
+            then.status(200);
+        })
+        .await;
+
+    for queue in ["orders", "payments"] {
+        service.create_queue(queue).await.unwrap();
+        service
+            .send_message(queue, "Hello", None, None)
+            .await
+            .unwrap();
+    }
+    service.receive_message("orders").await.unwrap();
+
+    assert_eq!(wait_for_hits(&mock, 1).await, 1);
+    // Give any stray deliveries a chance to arrive before checking again
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(mock.hits_async().await, 1);
+}
+
+#[tokio::test]
+async fn test_webhook_delivery_retries_failures() {
+    let server = MockServer::start_async().await;
+
+    let mut config = Config::default();
+    config.webhooks.max_retries = 2;
+    config.webhooks.subscriptions = vec![subscription(
+        server.url("/flaky"),
+        &[WebhookEvent::Sent],
+        None,
+    )];
+    let (_temp_dir, service) = create_service(&config).await;
+
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(POST).path("/flaky");
+            then.status(500);
+        })
+        .await;
+
+    service.create_queue("retry-queue").await.unwrap();
+    service
+        .send_message("retry-queue", "Hello", None, None)
+        .await
+        .unwrap();
+
+    // One initial attempt plus two retries
+    assert_eq!(wait_for_hits(&mock, 3).await, 3);
+}
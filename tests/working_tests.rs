@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use tempfile::TempDir;
 
 use qlite::config::{RetentionConfig, RetentionMode};
-use qlite::database::Database;
+use qlite::database::{DEFAULT_MESSAGE_LIST_LIMIT, Database, QueueDeletion, SendMessageParams};
 use qlite::message::MessageAttributeValue;
 use qlite::queue_service::QueueService;
 
@@ -28,17 +28,34 @@ async fn test_database_basic_operations() {
     assert_eq!(queues[0].0, "test-queue");
 
     // Test message sending
-    db.send_message("test-queue", "msg1", "Hello World", None, None)
-        .await
-        .expect("Failed to send message");
+    db.send_message_with_delay_and_group(SendMessageParams {
+        queue_name: "test-queue",
+        message_id: "msg1",
+        body: "Hello World",
+        attributes: None,
+        deduplication_id: None,
+        delay_until: None,
+        message_group_id: None,
+    })
+    .await
+    .expect("Failed to send message");
 
     // Test message receiving
-    let received = db
-        .receive_message("test-queue")
+    let (received, _) = db
+        .receive_next_message("test-queue", &[])
         .await
         .expect("Failed to receive message");
     assert!(received.is_some());
-    let (id, body, _created_at, _attributes) = received.unwrap();
+    let (
+        id,
+        body,
+        _created_at,
+        _attributes,
+        _receipt_handle,
+        _receive_count,
+        _first_received_at,
+        _visibility_deadline,
+    ) = received.unwrap();
     assert_eq!(id, "msg1");
     assert_eq!(body, "Hello World");
 
@@ -167,10 +184,10 @@ async fn test_queue_deletion() {
 
     // Delete queue
     let deleted = service
-        .delete_queue("queue-to-delete")
+        .delete_queue("queue-to-delete", false)
         .await
         .expect("Failed to delete queue");
-    assert!(deleted);
+    assert_eq!(deleted, QueueDeletion::Deleted);
 
     // Verify queue is gone
     let queues_after = service.list_queues().await.expect("Failed to list queues");
@@ -202,6 +219,8 @@ async fn test_retention_cleanup() {
         batch_size: 100,
         mode: RetentionMode::Delete,
         delete_after_days: Some(1),
+        dlq_retention_seconds: None,
+        prune_deleted_after_seconds: None,
     };
 
     // Run cleanup (this tests the function runs without error)
@@ -216,7 +235,7 @@ async fn test_retention_cleanup() {
 }
 
 #[tokio::test]
-async fn test_get_all_queue_messages() {
+async fn test_get_oldest_queue_messages() {
     let temp_dir = TempDir::new().expect("Failed to create temp directory");
     let db_path = temp_dir.path().join("test.db");
     let service = QueueService::new(db_path.to_str().unwrap())
@@ -238,7 +257,7 @@ async fn test_get_all_queue_messages() {
 
     // Get all messages
     let all_messages = service
-        .get_all_queue_messages("all-messages-queue")
+        .get_oldest_queue_messages("all-messages-queue", DEFAULT_MESSAGE_LIST_LIMIT)
         .await
         .expect("Failed to get all messages");
 
@@ -253,6 +272,14 @@ async fn test_get_all_queue_messages() {
         let expected_body = format!("Message {}", i);
         assert!(bodies.contains(&expected_body.as_str()));
     }
+
+    // The limit keeps the oldest messages
+    let oldest = service
+        .get_oldest_queue_messages("all-messages-queue", 2)
+        .await
+        .expect("Failed to get messages");
+    let bodies: Vec<&str> = oldest.iter().map(|m| m.1.as_str()).collect();
+    assert_eq!(bodies, vec!["Message 1", "Message 2"]);
 }
 
 #[tokio::test]
@@ -333,9 +360,86 @@ async fn test_message_deduplication() {
         .await
         .expect("Failed to send second message");
 
-    // Both should succeed (implementation may handle deduplication differently)
+    // The duplicate send succeeds but returns the original id without enqueueing again
     assert!(!message_id1.is_empty());
-    assert!(!message_id2.is_empty());
+    assert_eq!(message_id2, message_id1);
+
+    let all_messages = service
+        .get_oldest_queue_messages("dedup-queue", DEFAULT_MESSAGE_LIST_LIMIT)
+        .await
+        .expect("Failed to get all messages");
+    assert_eq!(all_messages.len(), 1);
+    assert_eq!(all_messages[0].1, "Deduplicated message");
+
+    let received = service
+        .receive_message("dedup-queue")
+        .await
+        .expect("Failed to receive message");
+    assert!(received.is_some());
+    let second = service
+        .receive_message("dedup-queue")
+        .await
+        .expect("Failed to receive message");
+    assert!(second.is_none(), "duplicate message was enqueued");
+}
+
+#[tokio::test]
+async fn test_batch_message_deduplication() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db_path = temp_dir.path().join("test.db");
+    let service = QueueService::new(db_path.to_str().unwrap())
+        .await
+        .expect("Failed to create queue service");
+
+    service
+        .create_queue("dedup-batch-queue")
+        .await
+        .expect("Failed to create queue");
+
+    // A duplicate within the batch and one of a message sent before it
+    service
+        .send_message(
+            "dedup-batch-queue",
+            "Earlier message",
+            None,
+            Some("earlier".to_string()),
+        )
+        .await
+        .expect("Failed to send message");
+    let entries = [
+        ("First", "batch"),
+        ("First again", "batch"),
+        ("Earlier again", "earlier"),
+    ]
+    .into_iter()
+    .map(|(body, dedup_id)| {
+        (
+            "dedup-batch-queue".to_string(),
+            service.id_generator().generate("dedup-batch-queue"),
+            body.to_string(),
+            None,
+            Some(dedup_id.to_string()),
+            0,
+            None,
+        )
+    })
+    .collect();
+    let results = service
+        .send_messages_batch(entries)
+        .await
+        .expect("Failed to send batch");
+    assert!(results.iter().all(|r| r.is_ok()));
+    assert_eq!(results[1], results[0]);
+
+    let mut bodies: Vec<String> = service
+        .get_oldest_queue_messages("dedup-batch-queue", DEFAULT_MESSAGE_LIST_LIMIT)
+        .await
+        .expect("Failed to get all messages")
+        .into_iter()
+        .map(|message| message.1)
+        .collect();
+    bodies.sort();
+    assert_eq!(bodies, vec!["Earlier message", "First"]);
 }
 
 #[tokio::test]
@@ -401,7 +505,7 @@ async fn test_error_conditions() {
     assert!(!restore_result.unwrap()); // Should return false
 
     // Test deleting non-existent queue
-    let delete_queue_result = service.delete_queue("nonexistent-queue").await;
+    let delete_queue_result = service.delete_queue("nonexistent-queue", false).await;
     assert!(delete_queue_result.is_ok());
-    assert!(!delete_queue_result.unwrap()); // Should return false
+    assert_eq!(delete_queue_result.unwrap(), QueueDeletion::NotFound);
 }